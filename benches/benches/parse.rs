@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use parselnk::{Lnk, ShellLinkHeader};
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+const TEST_FILES: [&str; 4] = ["commander", "firefox", "notepad", "outlook_express"];
+
+fn read(name: &str) -> Vec<u8> {
+    std::fs::read(format!(
+        "{}/../test_data/{}.lnk",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
+    .unwrap()
+}
+
+fn full_parse(c: &mut Criterion) {
+    for name in TEST_FILES.iter() {
+        let data = read(name);
+        c.bench_function(&format!("parse {}.lnk", name), |b| {
+            b.iter(|| Lnk::parse(black_box(&data)).unwrap())
+        });
+    }
+}
+
+fn header_only(c: &mut Criterion) {
+    let data = read("firefox");
+    c.bench_function("parse firefox.lnk header", |b| {
+        b.iter(|| ShellLinkHeader::try_from(&mut Cursor::new(black_box(data.as_slice()))).unwrap())
+    });
+}
+
+fn batch(c: &mut Criterion) {
+    let data = read("firefox");
+    c.bench_function("parse firefox.lnk 1000x", |b| {
+        b.iter(|| {
+            for _ in 0..1000 {
+                Lnk::parse(black_box(&data)).unwrap();
+            }
+        })
+    });
+
+    let files = TEST_FILES.iter().map(|name| read(name)).collect::<Vec<_>>();
+    c.bench_function("parse all test files", |b| {
+        b.iter(|| {
+            for data in &files {
+                Lnk::parse(black_box(data)).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, full_parse, header_only, batch);
+criterion_main!(benches);
@@ -1,4 +1,3 @@
-use parselnk;
 use std::{convert::TryFrom, path::Path};
 
 fn main() {
@@ -0,0 +1,106 @@
+use parselnk::{escape_for_display, Lnk};
+use std::fmt::Display;
+
+/// Print `value` with its control and format characters escaped, as every string in a
+/// shortcut may be attacker controlled
+fn field<T: Display>(label: &str, value: Option<T>) {
+    if let Some(value) = value {
+        println!("{:<20} {}", label, escape_for_display(&value.to_string()));
+    }
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Please provide a lnk to parse");
+    let lnk = Lnk::from_file(&path)
+        .map_err(|e| e.to_string())
+        .expect("Could not parse lnk: ")
+        .sanitize_for_display();
+
+    let string_data = &lnk.string_data;
+    let extra_data = &lnk.extra_data;
+    let volume_id = lnk.link_info.volume_id.as_ref();
+    let tracker = extra_data.tracker_props.as_ref();
+    let flat = lnk.flatten();
+
+    println!("{}", escape_for_display(&path));
+    field("Target:", flat.target);
+    field("Arguments:", lnk.arguments());
+    field(
+        "Working dir:",
+        string_data.working_dir.as_ref().map(|dir| dir.display()),
+    );
+    field(
+        "Relative path:",
+        string_data
+            .relative_path
+            .as_ref()
+            .map(|path| path.display()),
+    );
+    field("Description:", lnk.description());
+    field(
+        "Icon:",
+        string_data
+            .icon_location
+            .as_ref()
+            .map(|icon| icon.display()),
+    );
+    field("Created:", lnk.created_on());
+    field("Modified:", lnk.modified_on());
+    field("Accessed:", lnk.accessed_on());
+    field("File size:", Some(lnk.target_file_size()));
+    field("Show window:", Some(format!("{:?}", lnk.show_window())));
+    field(
+        "Volume label:",
+        volume_id
+            .and_then(|volume_id| volume_id.volume_label.as_deref())
+            .filter(|label| !label.is_empty()),
+    );
+    field(
+        "Volume serial:",
+        volume_id.map(|volume_id| format!("{:08X}", volume_id.drive_serial_number)),
+    );
+    field("Machine ID:", lnk.created_by_machine());
+    field(
+        "MAC address:",
+        tracker.and_then(|tracker| tracker.creator_mac_string()),
+    );
+    field("AppUserModelID:", lnk.app_user_model_id());
+    field("Application ID:", flat.application_id);
+
+    let blocks = [
+        ("Darwin", extra_data.darwin_props.is_some()),
+        ("SpecialFolder", extra_data.special_folder_props.is_some()),
+        ("Console", extra_data.console_props.is_some()),
+        ("ConsoleFE", extra_data.console_fe_props.is_some()),
+        (
+            "EnvironmentVariable",
+            extra_data.environment_props.is_some(),
+        ),
+        (
+            "IconEnvironment",
+            extra_data.icon_environment_props.is_some(),
+        ),
+        ("KnownFolder", extra_data.known_folder_props.is_some()),
+        ("PropertyStore", extra_data.property_store_props.is_some()),
+        ("Shim", extra_data.shim_props.is_some()),
+        ("Tracker", extra_data.tracker_props.is_some()),
+        (
+            "VistaAndAboveIDList",
+            extra_data.vista_and_above_idlist_props.is_some(),
+        ),
+    ]
+    .iter()
+    .filter(|(_, present)| *present)
+    .map(|(name, _)| *name)
+    .collect::<Vec<_>>();
+    field(
+        "Extra data blocks:",
+        Some(blocks.join(", ")).filter(|b| !b.is_empty()),
+    );
+
+    for warning in lnk.warnings() {
+        field("Warning:", Some(warning));
+    }
+}
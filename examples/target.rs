@@ -1,4 +1,4 @@
-use parselnk;
+use parselnk::{error::ExtraDataError, escape_for_display};
 use std::{convert::TryFrom, path::Path};
 
 fn main() {
@@ -9,7 +9,18 @@ fn main() {
         .map_err(|e| e.to_string())
         .expect("Could not parse lnk: ");
 
-    let extra = lnk.extra_data.environment_props.unwrap();
-    println!("ansi:    {}", extra.target_ansi().unwrap());
-    println!("unicode: {}", extra.target_unicode().unwrap());
+    let extra = match lnk.extra_data.environment_props {
+        Some(extra) => extra,
+        None => {
+            println!("No environment variable target");
+            return;
+        }
+    };
+
+    let show = |target: Result<String, ExtraDataError>| match target {
+        Ok(target) => escape_for_display(&target),
+        Err(e) => e.to_string(),
+    };
+    println!("ansi:    {}", show(extra.target_ansi()));
+    println!("unicode: {}", show(extra.target_unicode()));
 }
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::convert::TryFrom;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(lnk) = parselnk::Lnk::try_from(data) {
+        let _ = lnk.to_bytes();
+    }
+});
@@ -0,0 +1,452 @@
+//! Decoding and encoding of strings stored in a Windows code page.
+//!
+//! Strings that are not stored as unicode are encoded using the system default code page of
+//! the machine that created the link. The single-byte Windows code pages are built in, and
+//! [`decode`] and [`encode`] fall back to Windows-1252 for the others. Use [`is_supported`]
+//! to check a code page first.
+//!
+//! With the `encoding_rs` feature enabled, strings are converted with the `encoding_rs`
+//! crate instead, which adds the double-byte East Asian code pages 932 (Shift-JIS),
+//! 936 (GBK), 949 (Unified Hangul) and 950 (Big5) among others. Without it, asking for one
+//! of those in [`crate::LnkOptions`] is an error, and a `ConsoleFEDataBlock` that names one
+//! is reported as `ParseWarning::UnsupportedCodePage`.
+//!
+
+/// The code page used when the code page of a string is unknown or unsupported (Windows-1252)
+pub const DEFAULT_CODE_PAGE: u32 = 1252;
+
+/// Decode `data` using the Windows code page `code_page`, falling back to
+/// [`DEFAULT_CODE_PAGE`] when `code_page` is `None` or not supported. Bytes that are not
+/// defined in the code page decode to U+FFFD.
+pub fn decode(data: &[u8], code_page: Option<u32>) -> String {
+    let code_page = code_page.unwrap_or(DEFAULT_CODE_PAGE);
+
+    #[cfg(feature = "encoding_rs")]
+    if let Some(encoding) = encoding(code_page) {
+        return encoding.decode_without_bom_handling(data).0.into_owned();
+    }
+
+    match code_page {
+        65001 => String::from_utf8_lossy(data).into_owned(),
+        20127 => data
+            .iter()
+            .map(|b| if b.is_ascii() { *b as char } else { '\u{fffd}' })
+            .collect(),
+        28591 => data.iter().map(|b| *b as char).collect(),
+        code_page => {
+            let table = table(code_page).unwrap_or(&CP1252);
+
+            data.iter()
+                .map(|b| match b {
+                    0x00..=0x7f => *b as char,
+                    _ => std::char::from_u32(table[(*b - 0x80) as usize] as u32)
+                        .unwrap_or('\u{fffd}'),
+                })
+                .collect()
+        }
+    }
+}
+
+/// Encode `s` using the Windows code page `code_page`, falling back to [`DEFAULT_CODE_PAGE`]
+/// like [`decode`]. Characters that are not defined in the code page encode to `?`.
+pub fn encode(s: &str, code_page: Option<u32>) -> Vec<u8> {
+    let code_page = code_page.unwrap_or(DEFAULT_CODE_PAGE);
+
+    #[cfg(feature = "encoding_rs")]
+    if let Some(encoding) = encoding(code_page) {
+        return encode_with(encoding, s);
+    }
+
+    match code_page {
+        65001 => s.as_bytes().to_vec(),
+        20127 => s
+            .chars()
+            .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+            .collect(),
+        28591 => s
+            .chars()
+            .map(|c| std::convert::TryFrom::try_from(c).unwrap_or(b'?'))
+            .collect(),
+        code_page => {
+            let table = table(code_page).unwrap_or(&CP1252);
+
+            s.chars()
+                .map(|c| match c {
+                    '\u{0}'..='\u{7f}' => c as u8,
+                    '\u{fffd}' => b'?',
+                    _ => table
+                        .iter()
+                        .position(|t| *t as u32 == c as u32)
+                        .map(|i| i as u8 + 0x80)
+                        .unwrap_or(b'?'),
+                })
+                .collect()
+        }
+    }
+}
+
+/// Whether strings of the code page `code_page` can be decoded, rather than falling back to
+/// [`DEFAULT_CODE_PAGE`]
+pub fn is_supported(code_page: u32) -> bool {
+    #[cfg(feature = "encoding_rs")]
+    if encoding(code_page).is_some() {
+        return true;
+    }
+
+    matches!(code_page, 65001 | 20127 | 28591) || table(code_page).is_some()
+}
+
+/// The `encoding_rs` encoding of the Windows code page `code_page`. ASCII (20127) and
+/// ISO-8859-1 (28591) are left to the built-in conversion, as `encoding_rs` treats both as
+/// Windows-1252.
+#[cfg(feature = "encoding_rs")]
+fn encoding(code_page: u32) -> Option<&'static encoding_rs::Encoding> {
+    use encoding_rs::*;
+
+    match code_page {
+        866 => Some(IBM866),
+        874 => Some(WINDOWS_874),
+        932 => Some(SHIFT_JIS),
+        936 => Some(GBK),
+        949 => Some(EUC_KR),
+        950 => Some(BIG5),
+        1250 => Some(WINDOWS_1250),
+        1251 => Some(WINDOWS_1251),
+        1252 => Some(WINDOWS_1252),
+        1253 => Some(WINDOWS_1253),
+        1254 => Some(WINDOWS_1254),
+        1255 => Some(WINDOWS_1255),
+        1256 => Some(WINDOWS_1256),
+        1257 => Some(WINDOWS_1257),
+        1258 => Some(WINDOWS_1258),
+        10000 => Some(MACINTOSH),
+        10007 => Some(X_MAC_CYRILLIC),
+        20866 => Some(KOI8_R),
+        20932 => Some(EUC_JP),
+        21866 => Some(KOI8_U),
+        28592 => Some(ISO_8859_2),
+        28593 => Some(ISO_8859_3),
+        28594 => Some(ISO_8859_4),
+        28595 => Some(ISO_8859_5),
+        28596 => Some(ISO_8859_6),
+        28597 => Some(ISO_8859_7),
+        28598 => Some(ISO_8859_8),
+        28603 => Some(ISO_8859_13),
+        28605 => Some(ISO_8859_15),
+        38598 => Some(ISO_8859_8_I),
+        50220 => Some(ISO_2022_JP),
+        51932 => Some(EUC_JP),
+        51936 => Some(GBK),
+        51949 => Some(EUC_KR),
+        54936 => Some(GB18030),
+        65001 => Some(UTF_8),
+        _ => None,
+    }
+}
+
+/// Encode `s` with `encoding`, replacing characters it cannot represent with `?` like the
+/// built-in conversion does
+#[cfg(feature = "encoding_rs")]
+fn encode_with(encoding: &'static encoding_rs::Encoding, s: &str) -> Vec<u8> {
+    use encoding_rs::EncoderResult;
+
+    let mut encoder = encoding.new_encoder();
+    let mut data = Vec::with_capacity(s.len());
+    let mut rest = s;
+
+    loop {
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut data, true);
+        rest = &rest[read..];
+
+        match result {
+            EncoderResult::InputEmpty => return data,
+            EncoderResult::OutputFull => data.reserve(rest.len() + 16),
+            EncoderResult::Unmappable(_) => data.push(b'?'),
+        }
+    }
+}
+
+/// The upper half (0x80 - 0xFF) of the single-byte code page `code_page`
+fn table(code_page: u32) -> Option<&'static [u16; 128]> {
+    match code_page {
+        874 => Some(&CP874),
+        1250 => Some(&CP1250),
+        1251 => Some(&CP1251),
+        1252 => Some(&CP1252),
+        1253 => Some(&CP1253),
+        1254 => Some(&CP1254),
+        1255 => Some(&CP1255),
+        1256 => Some(&CP1256),
+        1257 => Some(&CP1257),
+        1258 => Some(&CP1258),
+        _ => None,
+    }
+}
+
+/// Windows-874 (Thai)
+#[rustfmt::skip]
+const CP874: [u16; 128] = [
+    0x20ac, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0x2026, 0xfffd, 0xfffd,
+    0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0x00a0, 0x0e01, 0x0e02, 0x0e03, 0x0e04, 0x0e05, 0x0e06, 0x0e07,
+    0x0e08, 0x0e09, 0x0e0a, 0x0e0b, 0x0e0c, 0x0e0d, 0x0e0e, 0x0e0f,
+    0x0e10, 0x0e11, 0x0e12, 0x0e13, 0x0e14, 0x0e15, 0x0e16, 0x0e17,
+    0x0e18, 0x0e19, 0x0e1a, 0x0e1b, 0x0e1c, 0x0e1d, 0x0e1e, 0x0e1f,
+    0x0e20, 0x0e21, 0x0e22, 0x0e23, 0x0e24, 0x0e25, 0x0e26, 0x0e27,
+    0x0e28, 0x0e29, 0x0e2a, 0x0e2b, 0x0e2c, 0x0e2d, 0x0e2e, 0x0e2f,
+    0x0e30, 0x0e31, 0x0e32, 0x0e33, 0x0e34, 0x0e35, 0x0e36, 0x0e37,
+    0x0e38, 0x0e39, 0x0e3a, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0x0e3f,
+    0x0e40, 0x0e41, 0x0e42, 0x0e43, 0x0e44, 0x0e45, 0x0e46, 0x0e47,
+    0x0e48, 0x0e49, 0x0e4a, 0x0e4b, 0x0e4c, 0x0e4d, 0x0e4e, 0x0e4f,
+    0x0e50, 0x0e51, 0x0e52, 0x0e53, 0x0e54, 0x0e55, 0x0e56, 0x0e57,
+    0x0e58, 0x0e59, 0x0e5a, 0x0e5b, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+];
+
+/// Windows-1250 (Central European)
+#[rustfmt::skip]
+const CP1250: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0xfffd, 0x201e, 0x2026, 0x2020, 0x2021,
+    0xfffd, 0x2030, 0x0160, 0x2039, 0x015a, 0x0164, 0x017d, 0x0179,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0xfffd, 0x2122, 0x0161, 0x203a, 0x015b, 0x0165, 0x017e, 0x017a,
+    0x00a0, 0x02c7, 0x02d8, 0x0141, 0x00a4, 0x0104, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x015e, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x017b,
+    0x00b0, 0x00b1, 0x02db, 0x0142, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x0105, 0x015f, 0x00bb, 0x013d, 0x02dd, 0x013e, 0x017c,
+    0x0154, 0x00c1, 0x00c2, 0x0102, 0x00c4, 0x0139, 0x0106, 0x00c7,
+    0x010c, 0x00c9, 0x0118, 0x00cb, 0x011a, 0x00cd, 0x00ce, 0x010e,
+    0x0110, 0x0143, 0x0147, 0x00d3, 0x00d4, 0x0150, 0x00d6, 0x00d7,
+    0x0158, 0x016e, 0x00da, 0x0170, 0x00dc, 0x00dd, 0x0162, 0x00df,
+    0x0155, 0x00e1, 0x00e2, 0x0103, 0x00e4, 0x013a, 0x0107, 0x00e7,
+    0x010d, 0x00e9, 0x0119, 0x00eb, 0x011b, 0x00ed, 0x00ee, 0x010f,
+    0x0111, 0x0144, 0x0148, 0x00f3, 0x00f4, 0x0151, 0x00f6, 0x00f7,
+    0x0159, 0x016f, 0x00fa, 0x0171, 0x00fc, 0x00fd, 0x0163, 0x02d9,
+];
+
+/// Windows-1251 (Cyrillic)
+#[rustfmt::skip]
+const CP1251: [u16; 128] = [
+    0x0402, 0x0403, 0x201a, 0x0453, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x20ac, 0x2030, 0x0409, 0x2039, 0x040a, 0x040c, 0x040b, 0x040f,
+    0x0452, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0xfffd, 0x2122, 0x0459, 0x203a, 0x045a, 0x045c, 0x045b, 0x045f,
+    0x00a0, 0x040e, 0x045e, 0x0408, 0x00a4, 0x0490, 0x00a6, 0x00a7,
+    0x0401, 0x00a9, 0x0404, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x0407,
+    0x00b0, 0x00b1, 0x0406, 0x0456, 0x0491, 0x00b5, 0x00b6, 0x00b7,
+    0x0451, 0x2116, 0x0454, 0x00bb, 0x0458, 0x0405, 0x0455, 0x0457,
+    0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417,
+    0x0418, 0x0419, 0x041a, 0x041b, 0x041c, 0x041d, 0x041e, 0x041f,
+    0x0420, 0x0421, 0x0422, 0x0423, 0x0424, 0x0425, 0x0426, 0x0427,
+    0x0428, 0x0429, 0x042a, 0x042b, 0x042c, 0x042d, 0x042e, 0x042f,
+    0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437,
+    0x0438, 0x0439, 0x043a, 0x043b, 0x043c, 0x043d, 0x043e, 0x043f,
+    0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447,
+    0x0448, 0x0449, 0x044a, 0x044b, 0x044c, 0x044d, 0x044e, 0x044f,
+];
+
+/// Windows-1252 (Western European)
+#[rustfmt::skip]
+const CP1252: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0x0160, 0x2039, 0x0152, 0xfffd, 0x017d, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0xfffd, 0x017e, 0x0178,
+    0x00a0, 0x00a1, 0x00a2, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x00aa, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x00ba, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00bf,
+    0x00c0, 0x00c1, 0x00c2, 0x00c3, 0x00c4, 0x00c5, 0x00c6, 0x00c7,
+    0x00c8, 0x00c9, 0x00ca, 0x00cb, 0x00cc, 0x00cd, 0x00ce, 0x00cf,
+    0x00d0, 0x00d1, 0x00d2, 0x00d3, 0x00d4, 0x00d5, 0x00d6, 0x00d7,
+    0x00d8, 0x00d9, 0x00da, 0x00db, 0x00dc, 0x00dd, 0x00de, 0x00df,
+    0x00e0, 0x00e1, 0x00e2, 0x00e3, 0x00e4, 0x00e5, 0x00e6, 0x00e7,
+    0x00e8, 0x00e9, 0x00ea, 0x00eb, 0x00ec, 0x00ed, 0x00ee, 0x00ef,
+    0x00f0, 0x00f1, 0x00f2, 0x00f3, 0x00f4, 0x00f5, 0x00f6, 0x00f7,
+    0x00f8, 0x00f9, 0x00fa, 0x00fb, 0x00fc, 0x00fd, 0x00fe, 0x00ff,
+];
+
+/// Windows-1253 (Greek)
+#[rustfmt::skip]
+const CP1253: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0xfffd, 0x2030, 0xfffd, 0x2039, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0xfffd, 0x2122, 0xfffd, 0x203a, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0x00a0, 0x0385, 0x0386, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0xfffd, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x2015,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x0384, 0x00b5, 0x00b6, 0x00b7,
+    0x0388, 0x0389, 0x038a, 0x00bb, 0x038c, 0x00bd, 0x038e, 0x038f,
+    0x0390, 0x0391, 0x0392, 0x0393, 0x0394, 0x0395, 0x0396, 0x0397,
+    0x0398, 0x0399, 0x039a, 0x039b, 0x039c, 0x039d, 0x039e, 0x039f,
+    0x03a0, 0x03a1, 0xfffd, 0x03a3, 0x03a4, 0x03a5, 0x03a6, 0x03a7,
+    0x03a8, 0x03a9, 0x03aa, 0x03ab, 0x03ac, 0x03ad, 0x03ae, 0x03af,
+    0x03b0, 0x03b1, 0x03b2, 0x03b3, 0x03b4, 0x03b5, 0x03b6, 0x03b7,
+    0x03b8, 0x03b9, 0x03ba, 0x03bb, 0x03bc, 0x03bd, 0x03be, 0x03bf,
+    0x03c0, 0x03c1, 0x03c2, 0x03c3, 0x03c4, 0x03c5, 0x03c6, 0x03c7,
+    0x03c8, 0x03c9, 0x03ca, 0x03cb, 0x03cc, 0x03cd, 0x03ce, 0xfffd,
+];
+
+/// Windows-1254 (Turkish)
+#[rustfmt::skip]
+const CP1254: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0x0160, 0x2039, 0x0152, 0xfffd, 0xfffd, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0xfffd, 0xfffd, 0x0178,
+    0x00a0, 0x00a1, 0x00a2, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x00aa, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x00ba, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00bf,
+    0x00c0, 0x00c1, 0x00c2, 0x00c3, 0x00c4, 0x00c5, 0x00c6, 0x00c7,
+    0x00c8, 0x00c9, 0x00ca, 0x00cb, 0x00cc, 0x00cd, 0x00ce, 0x00cf,
+    0x011e, 0x00d1, 0x00d2, 0x00d3, 0x00d4, 0x00d5, 0x00d6, 0x00d7,
+    0x00d8, 0x00d9, 0x00da, 0x00db, 0x00dc, 0x0130, 0x015e, 0x00df,
+    0x00e0, 0x00e1, 0x00e2, 0x00e3, 0x00e4, 0x00e5, 0x00e6, 0x00e7,
+    0x00e8, 0x00e9, 0x00ea, 0x00eb, 0x00ec, 0x00ed, 0x00ee, 0x00ef,
+    0x011f, 0x00f1, 0x00f2, 0x00f3, 0x00f4, 0x00f5, 0x00f6, 0x00f7,
+    0x00f8, 0x00f9, 0x00fa, 0x00fb, 0x00fc, 0x0131, 0x015f, 0x00ff,
+];
+
+/// Windows-1255 (Hebrew)
+#[rustfmt::skip]
+const CP1255: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0xfffd, 0x2039, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0xfffd, 0x203a, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0x00a0, 0x00a1, 0x00a2, 0x00a3, 0x20aa, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x00d7, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x00f7, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00bf,
+    0x05b0, 0x05b1, 0x05b2, 0x05b3, 0x05b4, 0x05b5, 0x05b6, 0x05b7,
+    0x05b8, 0x05b9, 0xfffd, 0x05bb, 0x05bc, 0x05bd, 0x05be, 0x05bf,
+    0x05c0, 0x05c1, 0x05c2, 0x05c3, 0x05f0, 0x05f1, 0x05f2, 0x05f3,
+    0x05f4, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd, 0xfffd,
+    0x05d0, 0x05d1, 0x05d2, 0x05d3, 0x05d4, 0x05d5, 0x05d6, 0x05d7,
+    0x05d8, 0x05d9, 0x05da, 0x05db, 0x05dc, 0x05dd, 0x05de, 0x05df,
+    0x05e0, 0x05e1, 0x05e2, 0x05e3, 0x05e4, 0x05e5, 0x05e6, 0x05e7,
+    0x05e8, 0x05e9, 0x05ea, 0xfffd, 0xfffd, 0x200e, 0x200f, 0xfffd,
+];
+
+/// Windows-1256 (Arabic)
+#[rustfmt::skip]
+const CP1256: [u16; 128] = [
+    0x20ac, 0x067e, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0x0679, 0x2039, 0x0152, 0x0686, 0x0698, 0x0688,
+    0x06af, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x06a9, 0x2122, 0x0691, 0x203a, 0x0153, 0x200c, 0x200d, 0x06ba,
+    0x00a0, 0x060c, 0x00a2, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x06be, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x061b, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x061f,
+    0x06c1, 0x0621, 0x0622, 0x0623, 0x0624, 0x0625, 0x0626, 0x0627,
+    0x0628, 0x0629, 0x062a, 0x062b, 0x062c, 0x062d, 0x062e, 0x062f,
+    0x0630, 0x0631, 0x0632, 0x0633, 0x0634, 0x0635, 0x0636, 0x00d7,
+    0x0637, 0x0638, 0x0639, 0x063a, 0x0640, 0x0641, 0x0642, 0x0643,
+    0x00e0, 0x0644, 0x00e2, 0x0645, 0x0646, 0x0647, 0x0648, 0x00e7,
+    0x00e8, 0x00e9, 0x00ea, 0x00eb, 0x0649, 0x064a, 0x00ee, 0x00ef,
+    0x064b, 0x064c, 0x064d, 0x064e, 0x00f4, 0x064f, 0x0650, 0x00f7,
+    0x0651, 0x00f9, 0x0652, 0x00fb, 0x00fc, 0x200e, 0x200f, 0x06d2,
+];
+
+/// Windows-1257 (Baltic)
+#[rustfmt::skip]
+const CP1257: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0xfffd, 0x201e, 0x2026, 0x2020, 0x2021,
+    0xfffd, 0x2030, 0xfffd, 0x2039, 0xfffd, 0x00a8, 0x02c7, 0x00b8,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0xfffd, 0x2122, 0xfffd, 0x203a, 0xfffd, 0x00af, 0x02db, 0xfffd,
+    0x00a0, 0xfffd, 0x00a2, 0x00a3, 0x00a4, 0xfffd, 0x00a6, 0x00a7,
+    0x00d8, 0x00a9, 0x0156, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00c6,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00f8, 0x00b9, 0x0157, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00e6,
+    0x0104, 0x012e, 0x0100, 0x0106, 0x00c4, 0x00c5, 0x0118, 0x0112,
+    0x010c, 0x00c9, 0x0179, 0x0116, 0x0122, 0x0136, 0x012a, 0x013b,
+    0x0160, 0x0143, 0x0145, 0x00d3, 0x014c, 0x00d5, 0x00d6, 0x00d7,
+    0x0172, 0x0141, 0x015a, 0x016a, 0x00dc, 0x017b, 0x017d, 0x00df,
+    0x0105, 0x012f, 0x0101, 0x0107, 0x00e4, 0x00e5, 0x0119, 0x0113,
+    0x010d, 0x00e9, 0x017a, 0x0117, 0x0123, 0x0137, 0x012b, 0x013c,
+    0x0161, 0x0144, 0x0146, 0x00f3, 0x014d, 0x00f5, 0x00f6, 0x00f7,
+    0x0173, 0x0142, 0x015b, 0x016b, 0x00fc, 0x017c, 0x017e, 0x02d9,
+];
+
+/// Windows-1258 (Vietnamese)
+#[rustfmt::skip]
+const CP1258: [u16; 128] = [
+    0x20ac, 0xfffd, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0xfffd, 0x2039, 0x0152, 0xfffd, 0xfffd, 0xfffd,
+    0xfffd, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0xfffd, 0x203a, 0x0153, 0xfffd, 0xfffd, 0x0178,
+    0x00a0, 0x00a1, 0x00a2, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x00aa, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x00ba, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00bf,
+    0x00c0, 0x00c1, 0x00c2, 0x0102, 0x00c4, 0x00c5, 0x00c6, 0x00c7,
+    0x00c8, 0x00c9, 0x00ca, 0x00cb, 0x0300, 0x00cd, 0x00ce, 0x00cf,
+    0x0110, 0x00d1, 0x0309, 0x00d3, 0x00d4, 0x01a0, 0x00d6, 0x00d7,
+    0x00d8, 0x00d9, 0x00da, 0x00db, 0x00dc, 0x01af, 0x0303, 0x00df,
+    0x00e0, 0x00e1, 0x00e2, 0x0103, 0x00e4, 0x00e5, 0x00e6, 0x00e7,
+    0x00e8, 0x00e9, 0x00ea, 0x00eb, 0x0301, 0x00ed, 0x00ee, 0x00ef,
+    0x0111, 0x00f1, 0x0323, 0x00f3, 0x00f4, 0x01a1, 0x00f6, 0x00f7,
+    0x00f8, 0x00f9, 0x00fa, 0x00fb, 0x00fc, 0x01b0, 0x20ab, 0x00ff,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, is_supported};
+
+    #[test]
+    fn windows_1252() {
+        assert_eq!(decode(b"Caf\xe9 \x80 na\xefve", Some(1252)), "Café € naïve");
+    }
+
+    #[test]
+    fn windows_1251() {
+        assert_eq!(decode(b"\xcf\xf0\xe8\xe2\xe5\xf2", Some(1251)), "Привет");
+    }
+
+    #[test]
+    #[cfg(not(feature = "encoding_rs"))]
+    fn unsupported_code_page() {
+        use crate::{error::Error, Lnk, LnkOptions};
+
+        assert!(is_supported(1251));
+        assert!(is_supported(65001));
+        assert!([932, 936, 949, 950].iter().all(|cp| !is_supported(*cp)));
+        assert_eq!(decode(b"Caf\xe9", None), "Café");
+
+        // Shift-JIS is not supported, so asking for it is an error rather than mojibake
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let options = LnkOptions {
+            ansi_code_page: Some(932),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Lnk::parse_with_options(&data, &options),
+            Err(Error::UnsupportedCodePage(932))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "encoding_rs")]
+    fn double_byte_code_pages() {
+        assert!([932, 936, 949, 950].iter().all(|cp| is_supported(*cp)));
+        assert_eq!(decode(b"\x93\xfa\x96\x7b\x8c\xea", Some(932)), "日本語");
+        assert_eq!(decode(b"\xd6\xd0\xce\xc4", Some(936)), "中文");
+        assert_eq!(decode(b"\xc7\xd1\xb1\xb9\xbe\xee", Some(949)), "한국어");
+        assert_eq!(decode(b"\xa4\xa4\xa4\xe5", Some(950)), "中文");
+
+        assert_eq!(encode("日本語", Some(932)), b"\x93\xfa\x96\x7b\x8c\xea");
+        assert_eq!(encode("中文 😀", Some(950)), b"\xa4\xa4\xa4\xe5 ?");
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        assert_eq!(encode("Café € naïve", Some(1252)), b"Caf\xe9 \x80 na\xefve");
+        assert_eq!(encode("Привет", Some(1251)), b"\xcf\xf0\xe8\xe2\xe5\xf2");
+        assert_eq!(encode("Привет", None), b"??????");
+    }
+}
@@ -0,0 +1,95 @@
+//! Splitting of command line arguments according to the Windows quoting rules.
+//!
+
+/// Split `arguments` into separate arguments the way `CommandLineToArgvW` does for everything
+/// after the program name:
+///
+/// * arguments are separated by spaces and tabs outside of double quotes
+/// * `2n` backslashes followed by a double quote produce `n` backslashes, and the double
+///   quote starts or ends a quoted section
+/// * `2n + 1` backslashes followed by a double quote produce `n` backslashes and a literal
+///   double quote
+/// * backslashes not followed by a double quote are kept as they are
+/// * two double quotes inside a quoted section produce a literal double quote
+pub(crate) fn split(arguments: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut started = false;
+    let mut in_quotes = false;
+    let mut chars = arguments.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+
+                if chars.peek() == Some(&'"') {
+                    arg.push_str(&"\\".repeat(backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        arg.push('"');
+                        chars.next();
+                    }
+                } else {
+                    arg.push_str(&"\\".repeat(backslashes));
+                }
+                started = true;
+            }
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                arg.push('"');
+                chars.next();
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                started = true;
+            }
+            ' ' | '\t' if !in_quotes => {
+                if started {
+                    args.push(std::mem::take(&mut arg));
+                    started = false;
+                }
+            }
+            c => {
+                arg.push(c);
+                started = true;
+            }
+        }
+    }
+
+    if started {
+        args.push(arg);
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+
+    #[test]
+    fn quoted_paths() {
+        assert_eq!(
+            split(r#"/c "C:\Program Files\app.exe"  -name "two words" tail"#),
+            vec![
+                "/c",
+                r"C:\Program Files\app.exe",
+                "-name",
+                "two words",
+                "tail"
+            ]
+        );
+    }
+
+    #[test]
+    fn backslashes_and_quotes() {
+        assert_eq!(
+            split(r#"a\\\"b "c\\" d\e \\\\"f g" """#),
+            vec![r#"a\"b"#, r"c\", r"d\e", r"\\f g", ""]
+        );
+        assert_eq!(split(r#""say ""hi""""#), vec![r#"say "hi""#]);
+        assert!(split(" \t ").is_empty());
+    }
+}
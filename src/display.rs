@@ -0,0 +1,67 @@
+//! Escaping of shortcut strings before they are shown to a user.
+//!
+
+use std::path::{Path, PathBuf};
+
+/// Escape the characters of `s` that can change how a terminal or text view renders the
+/// output: control characters, which include ANSI escape sequences, and the invisible format
+/// characters such as bidirectional overrides (`U+202E`) and zero-width spaces that can make
+/// a path read differently from what it is. Each one is replaced with its `\u{...}` escape.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(
+///     parselnk::escape_for_display("\u{1b}[2Jinvoice\u{202e}fdp.exe"),
+///     r"\u{1b}[2Jinvoice\u{202e}fdp.exe"
+/// );
+/// ```
+///
+pub fn escape_for_display(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut out, c| {
+            if needs_escape(c) {
+                out.extend(c.escape_unicode());
+            } else {
+                out.push(c);
+            }
+            out
+        })
+}
+
+/// Escape the path `p` like [`escape_for_display`]
+pub(crate) fn escape_path(p: &Path) -> PathBuf {
+    PathBuf::from(escape_for_display(&p.to_string_lossy()))
+}
+
+/// Whether `c` is a control character or an invisible format character
+fn needs_escape(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00ad}'
+                | '\u{061c}'
+                | '\u{180e}'
+                | '\u{200b}'..='\u{200f}'
+                | '\u{2028}'..='\u{202e}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{206f}'
+                | '\u{feff}'
+                | '\u{fff9}'..='\u{fffb}'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_for_display;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape_for_display(r"C:\Café\app.exe"), r"C:\Café\app.exe");
+        assert_eq!(escape_for_display("a\tb\r\n"), r"a\u{9}b\u{d}\u{a}");
+        assert_eq!(
+            escape_for_display("\u{2066}x\u{2069}\u{200b}\u{feff}"),
+            r"\u{2066}x\u{2069}\u{200b}\u{feff}"
+        );
+    }
+}
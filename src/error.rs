@@ -10,6 +10,10 @@ pub enum Error {
     #[error("Could not open file: {0}")]
     Open(#[from] std::io::Error),
 
+    /// The .lnk file could not be written
+    #[error("Could not save file: {0}")]
+    Save(std::io::Error),
+
     /// An error occurred while parsing the header fields
     #[error("Error parsing header: {0}")]
     HeaderError(#[from] HeaderError),
@@ -29,6 +33,23 @@ pub enum Error {
     /// An Error occured while parsing the `ExtraData` section
     #[error("Error parsing extra data: {0}")]
     ExtraDataError(#[from] ExtraDataError),
+
+    /// The `ansi_code_page` of the `LnkOptions` is not a supported code page
+    #[error("Unsupported code page: {0}")]
+    UnsupportedCodePage(u16),
+
+    /// The .lnk file was parsed by `Lnk::new_strict`, and deviates from the specification
+    #[error("Shortcut does not conform to the specification: {}", issue_list(.0))]
+    Nonconforming(Vec<ValidationIssue>),
+}
+
+/// Join the issues of an `Error::Nonconforming`
+fn issue_list(issues: &[ValidationIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 #[derive(Debug, Error)]
@@ -37,6 +58,14 @@ pub enum HeaderError {
     /// An error occurred while reading the data
     #[error("could not read header: {0}")]
     Read(#[from] std::io::Error),
+
+    /// No `ShellLinkHeader` starts at the given offset of the data
+    #[error("no shell link header at offset 0x{0:x}")]
+    InvalidSignature(usize),
+
+    /// The input is larger than the `max_size` of the `LnkOptions`, given here
+    #[error("input exceeds the maximum size of {0} bytes")]
+    TooLarge(u64),
 }
 
 #[derive(Debug, Error)]
@@ -45,6 +74,15 @@ pub enum LinkTargetIdListError {
     /// An error occurred while reading the data
     #[error("could not read link target id list data: {0}")]
     Read(#[from] std::io::Error),
+
+    /// The `IDListSize` declares more data than remains after it
+    #[error("id list size 0x{size:x} exceeds the 0x{available:x} bytes remaining")]
+    InvalidSize {
+        /// The declared `IDListSize`
+        size: u16,
+        /// The number of bytes following the `IDListSize`
+        available: u64,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -61,6 +99,22 @@ pub enum LinkInfoError {
     /// An error occurred while converting string data
     #[error("could not convert data to wide string: {0}")]
     WideStringConversion(widestring::error::Utf16Error),
+
+    /// The `VolumeID` structure declared an invalid size
+    #[error("invalid volume id size: 0x{0:08x}")]
+    InvalidVolumeId(u32),
+
+    /// The `CommonNetworkRelativeLink` structure declared an invalid size
+    #[error("invalid common network relative link size: 0x{0:08x}")]
+    InvalidCommonNetworkRelativeLink(u32),
+
+    /// A field offset does not lie within the `LinkInfo` structure, after its header
+    #[error("offset 0x{0:08x} is outside the link info")]
+    InvalidOffset(u32),
+
+    /// The `LinkInfoSize` is smaller than the `LinkInfoHeaderSize`
+    #[error("link info size 0x{0:08x} is smaller than its header")]
+    InvalidSize(u32),
 }
 
 #[derive(Debug, Error)]
@@ -87,13 +141,44 @@ pub enum StringDataError {
 /// An Error occured while parsing the `ExtraData` section
 pub enum ExtraDataError {
     /// An error occurred while reading the data
-    #[error("could not read extra data: {0}")]
-    Read(#[from] std::io::Error),
+    #[error("could not read extra data at offset 0x{offset:x}{}: {source}", block_context(.signature))]
+    Read {
+        /// The offset of the read that failed, from the start of the data
+        offset: u64,
+
+        /// The signature of the block being read, if the failure was inside a block
+        signature: Option<u32>,
+
+        /// The underlying error
+        source: std::io::Error,
+    },
 
     /// An extra block of unknown size or signature was encountered
     #[error("unknown extra block: size: 0x{0:08x}, signature: 0x{1:08x}")]
     UnknownBlock(u32, u32),
 
+    /// An extra block declared a size other than the one the specification requires for it.
+    /// For variable-length blocks, `expected` is the minimum size.
+    #[error("invalid size for extra block 0x{signature:08x}: expected 0x{expected:x}, found 0x{found:x}")]
+    InvalidBlockSize {
+        /// The signature of the block
+        signature: u32,
+
+        /// The size the specification requires
+        expected: u32,
+
+        /// The size the block declared
+        found: u32,
+    },
+
+    /// An extra block declared a size that does not fit in the remaining data
+    #[error("truncated extra block: size: 0x{0:08x}, signature: 0x{1:08x}")]
+    TruncatedBlock(u32, u32),
+
+    /// An extra block declared a size too small to hold its own size and signature
+    #[error("undersized extra block: size: 0x{0:08x}, signature: 0x{1:08x}")]
+    UndersizedBlock(u32, u32),
+
     /// Converting unicode string failed
     #[error("error converting unicode string: {0}")]
     WideStringConversion(#[from] widestring::error::Utf16Error),
@@ -102,3 +187,154 @@ pub enum ExtraDataError {
     #[error("missing string data")]
     MissingStringData,
 }
+
+/// Describe the block an `ExtraDataError::Read` occurred in
+fn block_context(signature: &Option<u32>) -> String {
+    signature
+        .map(|signature| format!(" in block 0x{:08x}", signature))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Error)]
+/// The target path of a `Lnk` could not be resolved
+pub enum TargetResolutionError {
+    /// The `Lnk` does not contain a `LinkInfo` structure
+    #[error("no link info present")]
+    NoLinkInfo,
+
+    /// The `LinkInfo` is present but the `ForceNoLinkInfo` flag says to ignore it
+    #[error("link info is ignored")]
+    LinkInfoIgnored,
+
+    /// The `LinkInfo` does not have the `VolumeIDAndLocalBasePath` flag set
+    #[error("link info has no local base path")]
+    NoLocalBasePath,
+
+    /// The `LinkInfo` only describes a network location, and it has no `NetName`
+    #[error("link info only contains a network link without a net name")]
+    NetworkOnly,
+
+    /// The local base path and common path suffix decoded to an empty path
+    #[error("target path decoded empty")]
+    EmptyPath,
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+/// A recoverable problem encountered while parsing a .lnk file. Unless noted otherwise, the
+/// affected field is left empty and parsing continues.
+pub enum ParseWarning {
+    /// A string field could not be read or decoded
+    #[error("could not decode {0}: {1}")]
+    StringDecode(&'static str, String),
+
+    /// The `VolumeID` structure could not be parsed
+    #[error("could not parse volume id: {0}")]
+    VolumeId(String),
+
+    /// The `CommonNetworkRelativeLink` structure could not be parsed
+    #[error("could not parse common network relative link: {0}")]
+    CommonNetworkRelativeLink(String),
+
+    /// An extra block of unknown size or signature was skipped
+    #[error("skipped unknown extra block: size: 0x{0:08x}, signature: 0x{1:08x}")]
+    UnknownBlock(u32, u32),
+
+    /// An extra block could not be parsed and was skipped
+    #[error("skipped invalid extra block: {0}")]
+    InvalidBlock(String),
+
+    /// The extra data ends partway through a block, after the given number of bytes of it.
+    /// The incomplete block is skipped.
+    #[error("extra data ends {0} bytes into an incomplete block")]
+    TruncatedExtraData(usize),
+
+    /// The `LinkInfo` declares a size that runs past the end of the data, or that is smaller
+    /// than its header. Parsing continues at the end of the data, or after the header.
+    #[error("invalid link info size: 0x{0:08x}")]
+    InvalidLinkInfoSize(u32),
+
+    /// An extra data block declared an invalid size, and the given number of bytes from
+    /// its start were skipped to reach the next valid block.
+    #[error("skipped {0} bytes of extra data to resynchronize")]
+    Resynchronized(usize),
+
+    /// The given number of bytes follow the TerminalBlock of the extra data. They are not
+    /// part of the shortcut, and may be appended data.
+    #[error("{0} bytes of trailing data after the extra data")]
+    TrailingData(usize),
+
+    /// An extra data block was parsed from fewer or more bytes than it declared. Parsing
+    /// continues after the declared size.
+    #[error("extra block 0x{signature:08x} declares 0x{declared:x} bytes but 0x{consumed:x} were parsed")]
+    BlockSizeMismatch {
+        /// The signature of the block
+        signature: u32,
+
+        /// The size the block declared
+        declared: u32,
+
+        /// The number of bytes its parser read
+        consumed: u32,
+    },
+
+    /// The reserved fields of the header, which must be zero, are not. The fields are kept
+    /// as they were read.
+    #[error("reserved header fields are not zero")]
+    ReservedFields,
+
+    /// The `ConsoleFEDataBlock` specifies a code page that is not supported, so ANSI strings
+    /// were decoded as Windows-1252 instead
+    #[error("unsupported code page {0}, decoded as Windows-1252")]
+    UnsupportedCodePage(u32),
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+/// A violation of the specification found by `Lnk::validate`
+pub enum ValidationIssue {
+    /// The `HeaderSize` is not 0x0000004C
+    #[error("invalid header size: 0x{0:08x}")]
+    InvalidHeaderSize(u32),
+
+    /// The `LinkCLSID` is not 00021401-0000-0000-C000-000000000046
+    #[error("invalid link clsid: {0}")]
+    InvalidClsid(crate::Guid),
+
+    /// The reserved fields of the header are not zero
+    #[error("reserved header fields are not zero")]
+    ReservedFields,
+
+    /// The `LinkInfoHeaderSize` is neither 0x0000001C nor at least 0x00000024
+    #[error("invalid link info header size: 0x{0:08x}")]
+    InvalidLinkInfoHeaderSize(u32),
+
+    /// The `LinkInfoSize` is smaller than the `LinkInfoHeaderSize`
+    #[error("link info size 0x{0:08x} is smaller than its header")]
+    InvalidLinkInfoSize(u32),
+
+    /// The offset of the named `LinkInfo` field does not lie within the structure, after
+    /// its header
+    #[error("{0} offset 0x{1:08x} is outside the link info")]
+    InvalidLinkInfoOffset(&'static str, u32),
+
+    /// An extra data block declares a size other than the one the specification requires
+    /// for it. For variable-length blocks, `expected` is the minimum size.
+    #[error("invalid size for extra block 0x{signature:08x}: expected 0x{expected:x}, found 0x{found:x}")]
+    InvalidBlockSize {
+        /// The signature of the block
+        signature: u32,
+
+        /// The size the specification requires
+        expected: u32,
+
+        /// The size the block declared
+        found: u32,
+    },
+
+    /// The header declares the named `StringData` string, but it could not be decoded
+    #[error("{0} is declared but could not be decoded")]
+    MissingString(&'static str),
+
+    /// A problem that the lenient parser recovered from
+    #[error("{0}")]
+    Recovered(ParseWarning),
+}
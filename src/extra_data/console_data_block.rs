@@ -1,11 +1,10 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, write_fixed, Result};
 use bitflags::bitflags;
 use byteorder::{ReadBytesExt, LE};
 use std::io::{Cursor, Read};
 
 /// The ConsoleDataBlock structure specifies the display settings to use when a link target specifies an application that is run in a console window.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ConsoleDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the ConsoleDataBlock
     /// structure. This value MUST be 0x000000CC.
@@ -187,88 +186,344 @@ bitflags! {
         const FF_DONTCARE = 0x0000;
 
         /// The font is variable-width with serifs; for example, "Times New Roman".
-        const FF_ROMAN = 0x0000;
+        const FF_ROMAN = 0x0010;
 
         /// The font is variable-width without serifs; for example, "Arial".
-        const FF_SWISS = 0x0000;
+        const FF_SWISS = 0x0020;
 
         /// The font is fixed-width, with or without serifs; for example, "Courier New".
-        const FF_MODERN = 0x0000;
+        const FF_MODERN = 0x0030;
 
         /// The font is designed to look like handwriting; for example, "Cursive".
-        const FF_SCRIPT = 0x0000;
+        const FF_SCRIPT = 0x0040;
 
         /// The font is a novelty font; for example, "Old English".
-        const FF_DECORATIVE = 0x0000;
+        const FF_DECORATIVE = 0x0050;
 
         /// A font pitch does not apply.
-        const TMPF_NONE= 0x0000;
+        const TMPF_NONE = 0x0000;
 
         /// The font is a fixed-pitch font.
-        const TMPF_FIXED_PITCH = 0x0000;
+        const TMPF_FIXED_PITCH = 0x0001;
 
         /// The font is a vector font.
-        const TMPF_VECTOR = 0x0000;
+        const TMPF_VECTOR = 0x0002;
 
         /// The font is a true-type font.
-        const TMPF_TRUETYPE = 0x0000;
+        const TMPF_TRUETYPE = 0x0004;
 
         /// The font is specific to the device.
-        const TMPF_DEVICE = 0x0000;
+        const TMPF_DEVICE = 0x0008;
     }
 }
 
+impl FontFamily {
+    /// The font family, stored in the high nibble. Families are distinct values rather than
+    /// individual bits, so compare this against the `FF_*` constants with `==`.
+    pub fn family(&self) -> FontFamily {
+        FontFamily::from_bits_truncate(self.bits() & 0x00f0)
+    }
+
+    /// The font pitch flags, stored in the low nibble
+    pub fn pitch(&self) -> FontFamily {
+        FontFamily::from_bits_truncate(self.bits() & 0x000f)
+    }
+}
+
+/// A size in the geometry of a console window, see [`ConsoleDataBlock::window_size`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Size {
+    /// The horizontal size (X axis)
+    pub width: i16,
+
+    /// The vertical size (Y axis)
+    pub height: i16,
+}
+
+/// A position in the geometry of a console window, see [`ConsoleDataBlock::window_origin`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    /// The horizontal coordinate (X axis)
+    pub x: i16,
+
+    /// The vertical coordinate (Y axis)
+    pub y: i16,
+}
+
 impl ConsoleDataBlock {
     /// Construct a new `ConsoleDataBlock`
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
+    ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_00cc)?;
+
+        Self::read(block_size, block_signature, cursor)
+    }
+
+    /// Construct a new `ConsoleDataBlock` from a block that may declare a size other than
+    /// 0x000000CC, as some third-party writers emit. The fixed layout is read from the
+    /// declared extent of the block, padded with zeros when it is shorter.
+    pub(crate) fn new_lenient(
+        block_size: u32,
+        block_signature: u32,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        if block_size == Self::SIZE {
+            return Self::new(block_size, block_signature, cursor);
+        }
+
+        let mut payload = vec![0; Self::SIZE as usize - 8];
+        let len = (block_size as usize).saturating_sub(8).min(payload.len());
+        read(cursor, |c| c.read_exact(&mut payload[..len]))?;
+
+        Self::read(
+            block_size,
+            block_signature,
+            &mut Cursor::new(payload.as_slice()),
+        )
+    }
+
+    /// The size of the block required by the specification
+    pub(crate) const SIZE: u32 = 0x0000_00cc;
+
+    fn read(block_size: u32, block_signature: u32, cursor: &mut Cursor<&[u8]>) -> Result<Self> {
         let cdb = ConsoleDataBlock {
             block_size,
             block_signature,
-            file_attributes: FileAttributes::from_bits_truncate(
-                cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            ),
-            popup_file_attributes: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            screen_buffer_size_x: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            screen_buffer_size_y: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            window_size_x: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            window_size_y: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            window_origin_x: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            window_origin_y: cursor.read_u16::<LE>().map_err(ExtraDataError::Read)?,
-            _unused_1: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            _unused_2: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            font_size: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            font_family: FontFamily::from_bits_truncate(
-                cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            ),
-            font_weight: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
+            file_attributes: FileAttributes::from_bits_truncate(read(cursor, |c| {
+                c.read_u16::<LE>()
+            })?),
+            popup_file_attributes: read(cursor, |c| c.read_u16::<LE>())?,
+            screen_buffer_size_x: read(cursor, |c| c.read_u16::<LE>())?,
+            screen_buffer_size_y: read(cursor, |c| c.read_u16::<LE>())?,
+            window_size_x: read(cursor, |c| c.read_u16::<LE>())?,
+            window_size_y: read(cursor, |c| c.read_u16::<LE>())?,
+            window_origin_x: read(cursor, |c| c.read_u16::<LE>())?,
+            window_origin_y: read(cursor, |c| c.read_u16::<LE>())?,
+            _unused_1: read(cursor, |c| c.read_u32::<LE>())?,
+            _unused_2: read(cursor, |c| c.read_u32::<LE>())?,
+            font_size: read(cursor, |c| c.read_u32::<LE>())?,
+            font_family: FontFamily::from_bits_truncate(read(cursor, |c| c.read_u32::<LE>())?),
+            font_weight: read(cursor, |c| c.read_u32::<LE>())?,
             face_name: {
                 let mut face_name = [0u8; 64];
-                cursor
-                    .read_exact(&mut face_name)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut face_name))?;
                 face_name.to_vec()
             },
-            cursor_size: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            full_screen: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            quick_edit: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            insert_mode: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            auto_position: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            history_buffer_size: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            number_of_history_buffers: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            history_no_dup: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
+            cursor_size: read(cursor, |c| c.read_u32::<LE>())?,
+            full_screen: read(cursor, |c| c.read_u32::<LE>())?,
+            quick_edit: read(cursor, |c| c.read_u32::<LE>())?,
+            insert_mode: read(cursor, |c| c.read_u32::<LE>())?,
+            auto_position: read(cursor, |c| c.read_u32::<LE>())?,
+            history_buffer_size: read(cursor, |c| c.read_u32::<LE>())?,
+            number_of_history_buffers: read(cursor, |c| c.read_u32::<LE>())?,
+            history_no_dup: read(cursor, |c| c.read_u32::<LE>())?,
             color_table: {
                 let mut face_name = [0u8; 64];
-                cursor
-                    .read_exact(&mut face_name)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut face_name))?;
                 face_name.to_vec()
             },
         };
 
         Ok(cdb)
     }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        let fields16 = [
+            self.file_attributes.bits(),
+            self.popup_file_attributes,
+            self.screen_buffer_size_x,
+            self.screen_buffer_size_y,
+            self.window_size_x,
+            self.window_size_y,
+            self.window_origin_x,
+            self.window_origin_y,
+        ];
+        fields16
+            .iter()
+            .for_each(|field| buf.extend_from_slice(&field.to_le_bytes()));
+
+        let fields32 = [
+            self._unused_1,
+            self._unused_2,
+            self.font_size,
+            self.font_family.bits(),
+            self.font_weight,
+        ];
+        fields32
+            .iter()
+            .for_each(|field| buf.extend_from_slice(&field.to_le_bytes()));
+        write_fixed(buf, &self.face_name, 64);
+
+        let fields32 = [
+            self.cursor_size,
+            self.full_screen,
+            self.quick_edit,
+            self.insert_mode,
+            self.auto_position,
+            self.history_buffer_size,
+            self.number_of_history_buffers,
+            self.history_no_dup,
+        ];
+        fields32
+            .iter()
+            .for_each(|field| buf.extend_from_slice(&field.to_le_bytes()));
+        write_fixed(buf, &self.color_table, 64);
+    }
+
+    /// The size of the console window buffer, in characters. The fields are signed
+    /// 16-bit integers per the specification.
+    pub fn screen_buffer_size(&self) -> Size {
+        Size {
+            width: self.screen_buffer_size_x as i16,
+            height: self.screen_buffer_size_y as i16,
+        }
+    }
+
+    /// The size of the console window, in characters
+    pub fn window_size(&self) -> Size {
+        Size {
+            width: self.window_size_x as i16,
+            height: self.window_size_y as i16,
+        }
+    }
+
+    /// The origin of the console window, in pixels. This may be negative on a
+    /// multi-monitor desktop, and is ignored when [`ConsoleDataBlock::is_auto_position`].
+    pub fn window_origin(&self) -> Point {
+        Point {
+            x: self.window_origin_x as i16,
+            y: self.window_origin_y as i16,
+        }
+    }
+
+    /// Whether the font is bold, a `font_weight` of 700 or more
+    pub fn is_bold(&self) -> bool {
+        self.font_weight >= 700
+    }
+
+    /// Whether the console window opens in full-screen mode
+    pub fn is_full_screen(&self) -> bool {
+        self.full_screen != 0
+    }
+
+    /// Whether the console window opens in QuickEdit mode
+    pub fn is_quick_edit(&self) -> bool {
+        self.quick_edit != 0
+    }
+
+    /// Whether insert mode is enabled in the console window
+    pub fn is_insert_mode(&self) -> bool {
+        self.insert_mode != 0
+    }
+
+    /// Whether the console window is positioned automatically, ignoring the window origin
+    pub fn is_auto_position(&self) -> bool {
+        self.auto_position != 0
+    }
+
+    /// Whether duplicates are allowed in the history buffer. Note that, per the
+    /// specification, a zero `history_no_dup` means duplicates are *not* allowed.
+    pub fn history_allows_duplicates(&self) -> bool {
+        self.history_no_dup != 0
+    }
+
+    /// The width of the console font in pixels, the low word of `font_size`. This is zero
+    /// for vector fonts.
+    pub fn font_width(&self) -> u16 {
+        self.font_size as u16
+    }
+
+    /// The height of the console font in pixels, the high word of `font_size`
+    pub fn font_height(&self) -> u16 {
+        (self.font_size >> 16) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsoleDataBlock, FontFamily, Point, Size};
+    use std::io::Cursor;
+
+    #[test]
+    fn font_family() {
+        let mut data = vec![0u8; 0xcc - 8];
+        data[24..28].copy_from_slice(&0x0010_0008u32.to_le_bytes());
+        data[28..32].copy_from_slice(&0x0036u32.to_le_bytes());
+
+        let block =
+            ConsoleDataBlock::new(0xcc, 0xa000_0002, &mut Cursor::new(data.as_slice())).unwrap();
+
+        assert_eq!(block.font_family.family(), FontFamily::FF_MODERN);
+        assert_eq!(
+            block.font_family.pitch(),
+            FontFamily::TMPF_VECTOR | FontFamily::TMPF_TRUETYPE
+        );
+        assert_eq!(block.font_height(), 16);
+    }
+
+    #[test]
+    fn geometry() {
+        let mut data = vec![0u8; 0xcc - 8];
+        for (i, value) in [120u16, 9001, 120, 30, 0xfe70, 40].iter().enumerate() {
+            data[4 + i * 2..6 + i * 2].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let block =
+            ConsoleDataBlock::new(0xcc, 0xa000_0002, &mut Cursor::new(data.as_slice())).unwrap();
+
+        assert_eq!(
+            block.screen_buffer_size(),
+            Size {
+                width: 120,
+                height: 9001
+            }
+        );
+        assert_eq!(
+            block.window_size(),
+            Size {
+                width: 120,
+                height: 30
+            }
+        );
+        assert_eq!(block.window_origin(), Point { x: -400, y: 40 });
+    }
+
+    #[test]
+    fn font_size() {
+        let block = ConsoleDataBlock {
+            font_size: 0x0010_0008,
+            ..Default::default()
+        };
+
+        assert_eq!(block.font_width(), 8);
+        assert_eq!(block.font_height(), 16);
+    }
+
+    #[test]
+    fn settings() {
+        let mut block = ConsoleDataBlock::default();
+        assert!(!block.is_bold());
+        assert!(!block.is_full_screen());
+        assert!(!block.is_quick_edit());
+        assert!(!block.is_insert_mode());
+        assert!(!block.is_auto_position());
+        assert!(!block.history_allows_duplicates());
+
+        block.font_weight = 700;
+        block.full_screen = 1;
+        block.quick_edit = 2;
+        block.insert_mode = 1;
+        block.auto_position = 0xffff_ffff;
+        block.history_no_dup = 1;
+        assert!(block.is_bold());
+        assert!(block.is_full_screen());
+        assert!(block.is_quick_edit());
+        assert!(block.is_insert_mode());
+        assert!(block.is_auto_position());
+        assert!(block.history_allows_duplicates());
+    }
 }
@@ -1,10 +1,9 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
 use byteorder::{ReadBytesExt, LE};
 use std::io::Cursor;
 
 /// The ConsoleFEDataBlock structure specifies the code page to use for displaying text when a link target specifies an application that is run in a console window.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ConsoleFEDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the ConsoleFEDataBlock structure. This value MUST be 0x0000000C.
     pub block_size: u32,
@@ -21,14 +20,21 @@ impl ConsoleFEDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_000c)?;
+
         let this = Self {
             block_size,
             block_signature,
-            code_page: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
+            code_page: read(cursor, |c| c.read_u32::<LE>())?,
         };
 
         Ok(this)
     }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.code_page.to_le_bytes());
+    }
 }
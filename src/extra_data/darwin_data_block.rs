@@ -1,10 +1,10 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, write_fixed, Result};
+use crate::{code_page, error::ExtraDataError, escape_for_display, Guid};
 use byteorder::{ReadBytesExt, LE};
 use std::convert::TryFrom;
 use std::io::{Cursor, Read};
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[allow(dead_code)]
 /// The DarwinDataBlock structure specifies an application identifier that can be used instead of a link target IDList to install an application when a shell link is activated.
 pub struct DarwinDataBlock {
@@ -19,41 +19,193 @@ impl DarwinDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_0314)?;
+
         let this = Self {
             block_size,
             block_signature,
             darwin_data_ansi: {
                 let mut darwin_data_ansi = vec![0; 260];
-                cursor
-                    .read_exact(&mut darwin_data_ansi)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut darwin_data_ansi))?;
                 darwin_data_ansi
             },
             darwin_data_unicode: {
                 let mut darwin_data_unicode = vec![0; 520];
-                cursor
-                    .read_exact(&mut darwin_data_unicode)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut darwin_data_unicode))?;
                 Some(darwin_data_unicode)
             },
         };
 
         Ok(this)
     }
+
+    /// The ANSI application identifier, decoded using the system default code page, or
+    /// `None` if it is empty
+    pub fn darwin_data_ansi(&self) -> Option<String> {
+        let len = self
+            .darwin_data_ansi
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(self.darwin_data_ansi.len());
+
+        Some(code_page::decode(&self.darwin_data_ansi[..len], None)).filter(|id| !id.is_empty())
+    }
+
+    /// The unicode application identifier, or `None` if it is empty or missing
+    pub fn darwin_data_unicode(&self) -> Option<String> {
+        let unicode = self
+            .darwin_data_unicode
+            .iter()
+            .flat_map(|data| data.chunks_exact(2))
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .take_while(|c| *c != 0)
+            .collect::<Vec<u16>>();
+
+        Some(String::from_utf16_lossy(&unicode)).filter(|id| !id.is_empty())
+    }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        write_fixed(buf, &self.darwin_data_ansi, 260);
+        write_fixed(
+            buf,
+            self.darwin_data_unicode.as_deref().unwrap_or_default(),
+            520,
+        );
+    }
+
+    /// Escape the application identifiers with [`crate::escape_for_display`], rewriting only
+    /// those that change, see [`crate::Lnk::sanitize_for_display`]
+    pub(crate) fn sanitize_for_display(&mut self) {
+        if let Some(id) = self.darwin_data_ansi() {
+            let escaped = escape_for_display(&id);
+            if escaped != id {
+                self.darwin_data_ansi = code_page::encode(&escaped, None);
+            }
+        }
+
+        if let Some(id) = self.darwin_data_unicode() {
+            let escaped = escape_for_display(&id);
+            if escaped != id {
+                self.darwin_data_unicode =
+                    Some(escaped.encode_utf16().flat_map(u16::to_le_bytes).collect());
+            }
+        }
+    }
+
+    /// The application identifier, preferring the unicode form when it is not empty
+    pub(crate) fn application_id(&self) -> Option<String> {
+        self.darwin_data_unicode()
+            .or_else(|| self.darwin_data_ansi())
+    }
+
+    /// The Windows Installer product code of an advertised shortcut, taken from the Darwin
+    /// descriptor in the application identifier. The descriptor starts with the product code
+    /// compressed into 20 characters, followed by the feature name and the component code.
+    /// Returns `None` if the identifier does not start with a compressed GUID.
+    pub fn installer_product_code(&self) -> Option<String> {
+        let descriptor = self.application_id()?;
+        decode_compressed_guid(descriptor.get(..20)?).map(|guid| guid.to_string())
+    }
 }
 
-impl TryFrom<&mut Cursor<Vec<u8>>> for DarwinDataBlock {
+/// Decode a GUID compressed into 20 characters, as used by Windows Installer descriptors.
+/// Each group of five characters encodes one 32-bit part of the GUID in base 85, least
+/// significant digit first.
+fn decode_compressed_guid(s: &str) -> Option<Guid> {
+    let mut guid = [0u8; 16];
+
+    for (part, chunk) in s.as_bytes().chunks(5).enumerate() {
+        let mut value = 0u64;
+        for c in chunk.iter().rev() {
+            // The alphabet is the printable ASCII characters, less `"#/:;<>\|`
+            let digit = match c {
+                b'!' => 0,
+                b'$'..=b'.' => c - b'$' + 1,
+                b'0'..=b'9' => c - b'0' + 12,
+                b'=' => 22,
+                b'?'..=b'[' => c - b'?' + 23,
+                b']'..=b'{' => c - b']' + 52,
+                b'}' | b'~' => c - b'}' + 83,
+                _ => return None,
+            };
+            value = value * 85 + digit as u64;
+        }
+
+        // Five digits can encode values up to 85^5 - 1, which do not fit in a part
+        let value = u32::try_from(value).ok()?;
+        guid[part * 4..part * 4 + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    Some(Guid(guid))
+}
+
+impl TryFrom<&mut Cursor<&[u8]>> for DarwinDataBlock {
     type Error = ExtraDataError;
 
-    fn try_from(cursor: &mut Cursor<Vec<u8>>) -> std::result::Result<Self, Self::Error> {
+    fn try_from(cursor: &mut Cursor<&[u8]>) -> std::result::Result<Self, Self::Error> {
         let this = Self {
-            block_size: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
-            block_signature: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
+            block_size: read(cursor, |c| c.read_u32::<LE>())?,
+            block_signature: read(cursor, |c| c.read_u32::<LE>())?,
             ..Default::default()
         };
 
         Ok(this)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DarwinDataBlock;
+
+    fn block(descriptor: &str) -> DarwinDataBlock {
+        let mut darwin_data_ansi = descriptor.as_bytes().to_vec();
+        darwin_data_ansi.resize(260, 0);
+
+        let mut darwin_data_unicode = descriptor
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        darwin_data_unicode.resize(520, 0);
+
+        DarwinDataBlock {
+            block_size: 0x0000_0314,
+            block_signature: 0xa000_0006,
+            darwin_data_ansi,
+            darwin_data_unicode: Some(darwin_data_unicode),
+        }
+    }
+
+    #[test]
+    fn installer_product_code() {
+        let block = block("']gAVn-}f(ZXfeAR6.jiFollowTheWhiteRabbit>3w2x^IGfe?CxI5heAvk.");
+
+        assert_eq!(
+            block.darwin_data_ansi(),
+            Some("']gAVn-}f(ZXfeAR6.jiFollowTheWhiteRabbit>3w2x^IGfe?CxI5heAvk.".to_string())
+        );
+        assert_eq!(block.darwin_data_unicode(), block.darwin_data_ansi());
+        assert_eq!(
+            block.installer_product_code(),
+            Some("{90110409-6000-11D3-8CFE-0150048383C9}".to_string())
+        );
+    }
+
+    #[test]
+    fn not_a_descriptor() {
+        assert_eq!(block("").darwin_data_ansi(), None);
+        assert_eq!(block("").installer_product_code(), None);
+        assert_eq!(block("short").installer_product_code(), None);
+        assert_eq!(
+            block("~~~~~-}f(ZXfeAR6.jiFollowTheWhiteRabbit>3w2x^IGfe?CxI5heAvk.")
+                .installer_product_code(),
+            None
+        );
+        assert_eq!(
+            block(r"C:\Program Files\App\app.exe").installer_product_code(),
+            None
+        );
+    }
+}
@@ -1,10 +1,11 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, write_fixed, Result};
+use crate::{code_page, error::ExtraDataError, escape_for_display};
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 use widestring::{U16Str, U16String};
 
 /// The EnvironmentVariableDataBlock structure specifies a path to environment variable information when the link target refers to a location that has a corresponding environment variable.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct EnvironmentVariableDataBlock {
     ///A 32-bit, unsigned integer that specifies the size of the EnvironmentVariableDataBlock structure. This value MUST be 0x00000314.
     pub block_size: u32,
@@ -17,6 +18,9 @@ pub struct EnvironmentVariableDataBlock {
 
     /// An optional, NULL-terminated, Unicode string that specifies a path to environment variable information.
     pub target_unicode: Option<Vec<u16>>,
+
+    /// The code page used to decode `target_ansi`, if known.
+    pub(crate) code_page: Option<u32>,
 }
 
 impl EnvironmentVariableDataBlock {
@@ -24,23 +28,21 @@ impl EnvironmentVariableDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_0314)?;
+
         let this = Self {
             block_size,
             block_signature,
             target_ansi: {
                 let mut target_ansi = vec![0; 260];
-                cursor
-                    .read_exact(&mut target_ansi)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut target_ansi))?;
                 Some(target_ansi)
             },
             target_unicode: {
                 let mut target_unicode = vec![0; 520];
-                cursor
-                    .read_exact(&mut target_unicode)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut target_unicode))?;
 
                 let result = target_unicode
                     .chunks_exact(2)
@@ -49,28 +51,24 @@ impl EnvironmentVariableDataBlock {
 
                 Some(result)
             },
+            code_page: None,
         };
 
         Ok(this)
     }
 
-    /// Attempt to parse the Target ANSI property to a valid string
+    /// Attempt to parse the Target ANSI property to a valid string. The string is decoded
+    /// using the code page of the link, or the default code page when it is not known.
     pub fn target_ansi(&self) -> Result<String> {
         let ansi = self
             .target_ansi
-            .clone()
-            .ok_or_else(|| ExtraDataError::MissingStringData)?;
+            .as_ref()
+            .ok_or(ExtraDataError::MissingStringData)?;
 
         let first_null = ansi.iter().position(|c| c == &0x00);
+        let ansi = &ansi[..first_null.unwrap_or(ansi.len())];
 
-        let c_str = match first_null {
-            Some(pos) => String::from_utf8((&ansi[0..pos]).to_vec()),
-            None => String::from_utf8(ansi),
-        };
-
-        Ok(c_str
-            .map_err(|_| ExtraDataError::MissingStringData)?
-            .to_string())
+        Ok(code_page::decode(ansi, self.code_page))
     }
 
     /// Attempt to parse the Target Unicode property to a valid string
@@ -78,7 +76,7 @@ impl EnvironmentVariableDataBlock {
         let unicode = self
             .target_unicode
             .clone()
-            .ok_or_else(|| ExtraDataError::MissingStringData)?;
+            .ok_or(ExtraDataError::MissingStringData)?;
 
         let first_null = unicode.iter().position(|c| c == &0x0000);
 
@@ -92,4 +90,166 @@ impl EnvironmentVariableDataBlock {
             .map_err(|_| ExtraDataError::MissingStringData)?
             .to_string())
     }
+
+    /// The environment variable target path, preferring the unicode form when it decodes to a
+    /// non-empty string and falling back to the ANSI form otherwise.
+    pub fn target(&self) -> Option<String> {
+        self.target_unicode()
+            .ok()
+            .filter(|target| !target.is_empty())
+            .or_else(|| self.target_ansi().ok())
+            .filter(|target| !target.is_empty())
+    }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        write_fixed(buf, self.target_ansi.as_deref().unwrap_or_default(), 260);
+
+        let target_unicode = self
+            .target_unicode
+            .iter()
+            .flatten()
+            .flat_map(|c| c.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        write_fixed(buf, &target_unicode, 520);
+    }
+
+    /// Escape the targets with [`crate::escape_for_display`], rewriting only those that
+    /// change, see [`crate::Lnk::sanitize_for_display`]
+    pub(crate) fn sanitize_for_display(&mut self) {
+        if let Ok(target) = self.target_ansi() {
+            let escaped = escape_for_display(&target);
+            if escaped != target {
+                self.target_ansi = Some(code_page::encode(&escaped, self.code_page));
+            }
+        }
+
+        if let Ok(target) = self.target_unicode() {
+            let escaped = escape_for_display(&target);
+            if escaped != target {
+                self.target_unicode = Some(escaped.encode_utf16().collect());
+            }
+        }
+    }
+
+    /// The environment variable target path with each `%VAR%` replaced by its value in
+    /// `env`. Variable names are matched case-insensitively, as on Windows, and variables
+    /// missing from `env` are left as they are.
+    pub fn target_expanded(&self, env: &HashMap<String, String>) -> Option<String> {
+        self.target().map(|target| expand(&target, env))
+    }
+}
+
+/// Substitute the `%VAR%` tokens in `s` with their values in `env`
+fn expand(s: &str, env: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let end = match after.find('%') {
+            Some(end) => end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+
+        let name = &after[..end];
+        match env
+            .iter()
+            .find(|(key, _)| !name.is_empty() && key.eq_ignore_ascii_case(name))
+        {
+            Some((_, value)) => {
+                expanded.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // Keep the closing `%`, it may open the next variable
+                expanded.push('%');
+                expanded.push_str(name);
+                rest = &after[end..];
+            }
+        }
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvironmentVariableDataBlock;
+    use std::collections::HashMap;
+
+    fn block(ansi: &str, unicode: &str) -> EnvironmentVariableDataBlock {
+        let mut target_ansi = ansi.as_bytes().to_vec();
+        target_ansi.resize(260, 0);
+
+        let mut target_unicode = unicode.encode_utf16().collect::<Vec<u16>>();
+        target_unicode.resize(260, 0);
+
+        EnvironmentVariableDataBlock {
+            block_size: 0x0000_0314,
+            block_signature: 0xa000_0001,
+            target_ansi: Some(target_ansi),
+            target_unicode: Some(target_unicode),
+            code_page: None,
+        }
+    }
+
+    #[test]
+    fn target_prefers_unicode() {
+        let block = block(r"%windir%\notepad.exe", r"%windir%\system32\notepad.exe");
+
+        assert_eq!(
+            block.target(),
+            Some(r"%windir%\system32\notepad.exe".to_string())
+        );
+    }
+
+    #[test]
+    fn target_falls_back_to_ansi() {
+        let block = block(r"%windir%\notepad.exe", "");
+
+        assert_eq!(block.target(), Some(r"%windir%\notepad.exe".to_string()));
+    }
+
+    #[test]
+    fn target_ansi_with_code_page() {
+        let mut block = block("", "");
+        block.target_ansi.as_mut().unwrap()[..7].copy_from_slice(b"C:\\Caf\xe9");
+
+        assert_eq!(block.target_ansi().unwrap(), r"C:\Café");
+
+        block.code_page = Some(1251);
+        assert_eq!(block.target_ansi().unwrap(), r"C:\Cafй");
+    }
+
+    #[test]
+    fn expand() {
+        let env = [
+            ("windir", r"C:\Windows"),
+            ("ProgramFiles", r"C:\Program Files"),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+
+        assert_eq!(
+            super::expand(r"%WINDIR%\notepad.exe", &env),
+            r"C:\Windows\notepad.exe"
+        );
+        assert_eq!(
+            super::expand(r"100% %ProgramFiles%\App", &env),
+            r"100% C:\Program Files\App"
+        );
+        assert_eq!(
+            super::expand(r"%missing%\%windir%", &env),
+            r"%missing%\C:\Windows"
+        );
+        assert_eq!(super::expand("50%", &env), "50%");
+    }
 }
@@ -1,9 +1,10 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, write_fixed, Result};
+use crate::{code_page, error::ExtraDataError, escape_for_display};
 use std::io::{Cursor, Read};
+use widestring::U16Str;
 
 /// The IconEnvironmentDataBlock structure specifies the path to an icon. The path is encoded using environment variables, which makes it possible to find the icon across machines where the locations vary but are expressed using environment variables.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct IconEnvironmentDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the IconEnvironmentDataBlock structure. This value MUST be 0x00000314.
     pub block_size: u32,
@@ -16,6 +17,9 @@ pub struct IconEnvironmentDataBlock {
 
     /// An optional, NULL-terminated, Unicode string that specifies a path that is constructed with environment variables.
     pub target_unicode: Option<Vec<u8>>,
+
+    /// The code page used to decode `target_ansi`, if known.
+    pub(crate) code_page: Option<u32>,
 }
 
 impl IconEnvironmentDataBlock {
@@ -23,27 +27,123 @@ impl IconEnvironmentDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_0314)?;
+
         let this = Self {
             block_size,
             block_signature,
             target_ansi: {
                 let mut target_ansi = vec![0; 260];
-                cursor
-                    .read_exact(&mut target_ansi)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut target_ansi))?;
                 Some(target_ansi)
             },
             target_unicode: {
                 let mut target_unicode = vec![0; 520];
-                cursor
-                    .read_exact(&mut target_unicode)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut target_unicode))?;
                 Some(target_unicode)
             },
+            code_page: None,
         };
 
         Ok(this)
     }
+
+    /// Attempt to parse the Target ANSI property to a valid string. The string is decoded
+    /// using the code page of the link, or the default code page when it is not known.
+    pub fn target_ansi(&self) -> Result<String> {
+        let ansi = self
+            .target_ansi
+            .as_ref()
+            .ok_or(ExtraDataError::MissingStringData)?;
+
+        let first_null = ansi.iter().position(|c| c == &0x00);
+        let ansi = &ansi[..first_null.unwrap_or(ansi.len())];
+
+        Ok(code_page::decode(ansi, self.code_page))
+    }
+
+    /// Attempt to parse the Target Unicode property to a valid string
+    pub fn target_unicode(&self) -> Result<String> {
+        let unicode = self
+            .target_unicode
+            .as_ref()
+            .ok_or(ExtraDataError::MissingStringData)?
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect::<Vec<u16>>();
+
+        let first_null = unicode.iter().position(|c| c == &0x0000);
+
+        U16Str::from_slice(&unicode[..first_null.unwrap_or(unicode.len())])
+            .to_string()
+            .map_err(|_| ExtraDataError::MissingStringData)
+    }
+
+    /// The icon path, preferring the unicode form when it decodes to a non-empty string and
+    /// falling back to the ANSI form otherwise.
+    pub fn target(&self) -> Option<String> {
+        self.target_unicode()
+            .ok()
+            .filter(|target| !target.is_empty())
+            .or_else(|| self.target_ansi().ok())
+            .filter(|target| !target.is_empty())
+    }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        write_fixed(buf, self.target_ansi.as_deref().unwrap_or_default(), 260);
+        write_fixed(buf, self.target_unicode.as_deref().unwrap_or_default(), 520);
+    }
+
+    /// Escape the targets with [`crate::escape_for_display`], rewriting only those that
+    /// change, see [`crate::Lnk::sanitize_for_display`]
+    pub(crate) fn sanitize_for_display(&mut self) {
+        if let Ok(target) = self.target_ansi() {
+            let escaped = escape_for_display(&target);
+            if escaped != target {
+                self.target_ansi = Some(code_page::encode(&escaped, self.code_page));
+            }
+        }
+
+        if let Ok(target) = self.target_unicode() {
+            let escaped = escape_for_display(&target);
+            if escaped != target {
+                self.target_unicode =
+                    Some(escaped.encode_utf16().flat_map(u16::to_le_bytes).collect());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IconEnvironmentDataBlock;
+
+    #[test]
+    fn target() {
+        let mut target_ansi = b"%SystemRoot%\\system32\\shell32.dll".to_vec();
+        target_ansi.resize(260, 0);
+
+        let mut block = IconEnvironmentDataBlock {
+            block_size: 0x314,
+            block_signature: 0xa000_0007,
+            target_ansi: Some(target_ansi),
+            target_unicode: Some(vec![0; 520]),
+            code_page: None,
+        };
+        assert_eq!(
+            block.target(),
+            Some(r"%SystemRoot%\system32\shell32.dll".to_string())
+        );
+
+        let mut target_unicode = "%ProgramFiles%\\app.ico"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        target_unicode.resize(520, 0);
+        block.target_unicode = Some(target_unicode);
+        assert_eq!(block.target(), Some(r"%ProgramFiles%\app.ico".to_string()));
+    }
 }
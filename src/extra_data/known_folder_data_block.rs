@@ -1,10 +1,10 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
+use crate::Guid;
 use byteorder::{ReadBytesExt, LE};
 use std::io::Cursor;
 
 /// The KnownFolderDataBlock structure specifies the location of a known folder. This data can be used when a link target is a known folder to keep track of the folder so that the link target IDList can be translated when the link is loaded.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct KnownFolderDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the KnownFolderDataBlock structure. This value MUST be 0x0000001C.
     pub block_size: u32,
@@ -13,26 +13,166 @@ pub struct KnownFolderDataBlock {
     pub block_signature: u32,
 
     /// A value in GUID packet representation ([MS-DTYP] section 2.3.4.2) that specifies the folder GUID ID.
-    pub known_folder_id: u128,
+    pub known_folder_id: Guid,
 
     /// A 32-bit, unsigned integer that specifies the location of the ItemID of the first child segment of the IDList specified by KnownFolderID. This value is the offset, in bytes, into the link target IDList.
     pub offset: u32,
 }
 
+/// Well known `FOLDERID` values, keyed by their `known_folder_id` representation, and the names of
+/// the `FOLDERID_` constants they are declared as.
+const KNOWN_FOLDERS: &[(u128, &str)] = &[
+    (0x4fba_6f84_0eb6_269f_4fef_a42d_724e_f170, "AdminTools"),
+    (0x58ca_500f_5a64_7e8a_42f0_89c2_1e87_508d, "AppsFolder"),
+    (0x5578_68f5_3043_b8ac_49df_f80d_9e52_ab10, "CDBurning"),
+    (
+        0xb592_b329_a2cb_148f_4797_bac3_d038_4e7d,
+        "CommonAdminTools",
+    ),
+    (0x9d7a_220b_a27a_ddbe_4334_10df_c1ba_e2d0, "CommonOEMLinks"),
+    (0xb8ff_e6ca_af3d_9086_49f2_6afe_0139_d44e, "CommonPrograms"),
+    (0x67b0_e38b_4be7_7caa_491d_d62e_a411_5719, "CommonStartMenu"),
+    (0x6e4e_712f_5de1_2996_47c5_d9cd_82a5_ea35, "CommonStartup"),
+    (0xf7d1_326c_8cb0_5191_4347_57ac_b942_37e7, "CommonTemplates"),
+    (0xa77c_668e_d079_0d85_452a_bbf8_0ac0_837c, "ComputerFolder"),
+    (0x82b8_ac50_e388_6981_462b_c6cb_5678_4854, "Contacts"),
+    (
+        0x636d_34ee_97d0_14a0_465c_aeb4_82a7_4aeb,
+        "ControlPanelFolder",
+    ),
+    (0xf64f_b811_a608_8e90_4171_c0e9_2b0f_765d, "Cookies"),
+    (0x41c6_879a_e97f_29b0_424c_db2c_b4bf_cc3a, "Desktop"),
+    (0xc769_0348_856c_b4ad_46af_238f_fdd3_9ad0, "Documents"),
+    (0x7b46_5e92_c439_6491_4565_123f_374d_e290, "Downloads"),
+    (0xdd33_fa59_b730_bd87_4d8a_68ad_1777_f761, "Favorites"),
+    (0xfeb8_0a91_f316_4c86_4ae3_ae11_fd22_8cb7, "Fonts"),
+    (0x6359_a730_115a_81a7_432e_b784_d9dc_8a3b, "History"),
+    (
+        0x46fd_02e4_34dc_25b7_4cee_79f6_bcb5_256f,
+        "ImplicitAppShortcuts",
+    ),
+    (0x9dcf_edca_0760_85ba_4251_33be_3524_81e8, "InternetCache"),
+    (0x4b3e_0cd2_b040_7b96_4904_4e0c_4d9f_7874, "InternetFolder"),
+    (0x6849_afb6_6dae_b2b2_404c_c6a9_bfb9_d5e0, "Links"),
+    (0x9170_157f_8e7b_559d_4fcf_6fba_f1b3_2785, "LocalAppData"),
+    (0x16af_c543_7316_18bd_4ff6_1780_a520_a1a4, "LocalAppDataLow"),
+    (
+        0xdc3d_eff7_0d44_d1b8_49de_224c_2a00_375e,
+        "LocalizedResourcesDir",
+    ),
+    (0x430e_0820_2242_97be_48d3_6d19_4bd8_d571, "Music"),
+    (0x73c9_c26f_6286_0089_4121_e17f_c5ab_bf53, "NetHood"),
+    (0x539b_a01e_25bf_3bae_4905_5ca8_d20b_eec4, "NetworkFolder"),
+    (0xbbc3_3b5c_3998_5a83_4676_4e1e_33e2_8130, "Pictures"),
+    (0xf458_a755_3fb1_5eb3_41c3_cfd1_9274_bd8d, "PrintHood"),
+    (0x8581_0656_bd37_63a6_4519_d6ad_76fc_4e2d, "PrintersFolder"),
+    (0x7371_b617_33ea_fe9a_4760_0e22_5e6c_858f, "Profile"),
+    (0x975d_491d_0d07_dda9_4dc3_fdc1_62ab_5d82, "ProgramData"),
+    (0x1ad2_d332_b765_9cb2_494e_c1bf_905e_63b6, "ProgramFiles"),
+    (
+        0x66f0_c617_d329_aeaa_47a2_9f6d_f7f1_ed05,
+        "ProgramFilesCommon",
+    ),
+    (
+        0x7d4f_6a6b_a50d_f687_45e5_0f0d_6365_d5a7,
+        "ProgramFilesCommonX64",
+    ),
+    (
+        0x17b9_2051_45f4_91bf_4d3e_d9c6_de97_4d24,
+        "ProgramFilesCommonX86",
+    ),
+    (0x0e20_023f_77a3_5789_444b_6af0_6d80_9377, "ProgramFilesX64"),
+    (0x8efa_b9e0_f2c0_4a87_4bfc_a0fb_7c5a_40ef, "ProgramFilesX86"),
+    (0x514a_0501_a6ab_a2a6_44c3_2e2b_a77f_5d77, "Programs"),
+    (0x8573_45ac_4456_6a90_4d63_c82a_dfdf_76a2, "Public"),
+    (0x25ba_e6f2_7ef8_efaf_4863_f20f_c4aa_340d, "PublicDesktop"),
+    (0x3436_0865_79fc_e281_45a8_dce4_ed48_24af, "PublicDocuments"),
+    (0xc079_5f23_70f6_459b_4f30_1fb8_3d64_4c9b, "PublicDownloads"),
+    (0xff44_aade_a992_61bb_4298_9757_3214_fab5, "PublicMusic"),
+    (0xc57c_f0ab_c24f_f79a_413c_6907_b6eb_fb86, "PublicPictures"),
+    (0xa32b_602a_394a_d8a2_49fb_6185_2400_183a, "PublicVideos"),
+    (0x8fbc_10a2_874b_6b9f_48a9_7b75_52a4_f021, "QuickLaunch"),
+    (0x7a98_342e_098a_5586_438a_ebd2_ae50_c081, "Recent"),
+    (
+        0xacd6_b74c_cd64_4ebe_4c18_3ecb_b753_4046,
+        "RecycleBinFolder",
+    ),
+    (0x72c9_3212_70e4_f7a8_4296_2adb_8ad1_0c31, "ResourceDir"),
+    (0x3d9f_7265_efe3_3aa0_4cf6_65f9_3eb6_85db, "RoamingAppData"),
+    (0xa4aa_4ee5_722d_b4b5_43b0_bb9d_4c5c_32ff, "SavedGames"),
+    (0x74fd_dc10_2d10_088f_404b_27c0_8983_036c, "SendTo"),
+    (0x19fc_4641_efa1_1fba_4ec1_ab48_625b_53c3, "StartMenu"),
+    (0x5408_4308_365e_10ba_4c97_f46a_b97d_20bb, "Startup"),
+    (0xb798_51ae_b12e_44b7_4e5d_02e7_1ac1_4e77, "System"),
+    (0x277d_eac6_e7a8_cea4_4857_b2f1_d652_31b0, "SystemX86"),
+    (0xf709_059e_75df_79a0_48db_664e_a632_93e8, "Templates"),
+    (0x7471_6c4b_b248_27b8_4f13_1f9c_9e39_95ab, "UserPinned"),
+    (0x809b_72cd_7d69_82a3_4bb0_c50a_0762_d272, "UserProfiles"),
+    (
+        0xcb60_56e1_9c6c_5db8_4a67_2219_5cd7_aee2,
+        "UserProgramFiles",
+    ),
+    (0x8fef_044b_d4d5_4886_4acc_4901_f3ce_0f7c, "UsersFiles"),
+    (0xfcdd_e474_7cab_1c84_455b_99b5_1898_9b1d, "Videos"),
+    (0x23fc_280b_de67_0593_42f2_1d43_f38b_f404, "Windows"),
+];
+
 impl KnownFolderDataBlock {
     /// Construct a new `KnownFolderDataBlock`
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_001c)?;
+
         let this = Self {
             block_size,
             block_signature,
-            known_folder_id: cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
-            offset: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
+            known_folder_id: Guid::from(read(cursor, |c| c.read_u128::<LE>())?),
+            offset: read(cursor, |c| c.read_u32::<LE>())?,
         };
 
         Ok(this)
     }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.known_folder_id.as_bytes());
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+    }
+
+    /// The name of the well known folder identified by `known_folder_id`, e.g. `"Desktop"` for
+    /// `FOLDERID_Desktop`. Returns `None` for folder ids not in the table.
+    pub fn known_folder_name(&self) -> Option<&'static str> {
+        KNOWN_FOLDERS
+            .iter()
+            .find(|(id, _)| *id == self.known_folder_id.to_u128())
+            .map(|(_, name)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Lnk;
+    use std::convert::TryFrom;
+    use std::path::Path;
+
+    #[test]
+    fn known_folder_name() {
+        let lnk = Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap();
+        let known_folder = lnk.extra_data.known_folder_props.unwrap();
+
+        assert_eq!(known_folder.known_folder_name(), Some("System"));
+    }
+
+    #[test]
+    fn unknown_folder_name() {
+        let known_folder = super::KnownFolderDataBlock {
+            known_folder_id: 0x1234.into(),
+            ..Default::default()
+        };
+
+        assert_eq!(known_folder.known_folder_name(), None);
+    }
 }
@@ -15,10 +15,13 @@ mod special_folder_data_block;
 mod tracker_data_block;
 mod vista_and_above_id_list_data_block;
 
-use crate::{error::ExtraDataError, header::ShellLinkHeader};
+use crate::{
+    error::{ExtraDataError, ParseWarning, ValidationIssue},
+    header::ShellLinkHeader,
+    LnkOptions,
+};
 use byteorder::{ReadBytesExt, LE};
 pub use console_data_block::*;
-pub use console_data_block::*;
 pub use console_fe_data_block::*;
 pub use darwin_data_block::*;
 pub use environment_variable_data_block::*;
@@ -34,7 +37,7 @@ pub use vista_and_above_id_list_data_block::*;
 /// Result for parsing `ExtraData` blocks
 type Result<T> = std::result::Result<T, ExtraDataError>;
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 /// ExtraData refers to a set of structures that convey additional information about a link target. These optional structures can be present in an extra data section that is appended to the basic Shell Link Binary File Format.
 /// The ExtraData structures conform to the following ABNF rules [RFC5234]:
 pub struct ExtraData {
@@ -70,90 +73,1016 @@ pub struct ExtraData {
 
     /// The VistaAndAboveIDListDataBlock structure specifies an alternate IDList that can be used instead of the LinkTargetIDList structure (section 2.2) on platforms that support it.
     pub vista_and_above_idlist_props: Option<VistaAndAboveIDListDataBlock>,
+
+    /// The raw bytes of each block, in the order they were read
+    blocks: Vec<Vec<u8>>,
+
+    /// The declared and consumed size of each block, in the order they were read
+    sizes: Vec<BlockSize>,
+}
+
+/// The size an extra data block declared, and the number of bytes its parser consumed, see
+/// [`ExtraData::block_sizes`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockSize {
+    /// The signature of the block
+    pub signature: u32,
+
+    /// The `BlockSize` field of the block
+    pub declared: u32,
+
+    /// The number of bytes, from the start of the block, that its parser read. For a block
+    /// that was rejected, this may be as little as its size and signature fields.
+    pub consumed: u32,
+}
+
+impl BlockSize {
+    /// Whether the parser consumed exactly the declared size. A mismatch points to a
+    /// corrupt block, or to a layout the parser does not know about.
+    pub fn size_matches(&self) -> bool {
+        self.declared == self.consumed
+    }
+}
+
+/// The signature of an extra data block, identifying its type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DataBlockSignature {
+    /// 0xA0000001, an `EnvironmentVariableDataBlock`
+    EnvironmentVariable,
+
+    /// 0xA0000002, a `ConsoleDataBlock`
+    Console,
+
+    /// 0xA0000003, a `TrackerDataBlock`
+    Tracker,
+
+    /// 0xA0000004, a `ConsoleFEDataBlock`
+    ConsoleFE,
+
+    /// 0xA0000005, a `SpecialFolderDataBlock`
+    SpecialFolder,
+
+    /// 0xA0000006, a `DarwinDataBlock`
+    Darwin,
+
+    /// 0xA0000007, an `IconEnvironmentDataBlock`
+    IconEnvironment,
+
+    /// 0xA0000008, a `ShimDataBlock`
+    Shim,
+
+    /// 0xA0000009, a `PropertyStoreDataBlock`
+    PropertyStore,
+
+    /// 0xA000000B, a `KnownFolderDataBlock`
+    KnownFolder,
+
+    /// 0xA000000C, a `VistaAndAboveIDListDataBlock`
+    VistaAndAboveIdList,
+
+    /// A signature not defined by the specification
+    Unknown(u32),
+}
+
+impl From<u32> for DataBlockSignature {
+    fn from(value: u32) -> Self {
+        match value {
+            0xa000_0001 => Self::EnvironmentVariable,
+            0xa000_0002 => Self::Console,
+            0xa000_0003 => Self::Tracker,
+            0xa000_0004 => Self::ConsoleFE,
+            0xa000_0005 => Self::SpecialFolder,
+            0xa000_0006 => Self::Darwin,
+            0xa000_0007 => Self::IconEnvironment,
+            0xa000_0008 => Self::Shim,
+            0xa000_0009 => Self::PropertyStore,
+            0xa000_000b => Self::KnownFolder,
+            0xa000_000c => Self::VistaAndAboveIdList,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<DataBlockSignature> for u32 {
+    fn from(signature: DataBlockSignature) -> Self {
+        match signature {
+            DataBlockSignature::EnvironmentVariable => 0xa000_0001,
+            DataBlockSignature::Console => 0xa000_0002,
+            DataBlockSignature::Tracker => 0xa000_0003,
+            DataBlockSignature::ConsoleFE => 0xa000_0004,
+            DataBlockSignature::SpecialFolder => 0xa000_0005,
+            DataBlockSignature::Darwin => 0xa000_0006,
+            DataBlockSignature::IconEnvironment => 0xa000_0007,
+            DataBlockSignature::Shim => 0xa000_0008,
+            DataBlockSignature::PropertyStore => 0xa000_0009,
+            DataBlockSignature::KnownFolder => 0xa000_000b,
+            DataBlockSignature::VistaAndAboveIdList => 0xa000_000c,
+            DataBlockSignature::Unknown(other) => other,
+        }
+    }
 }
 
 impl ExtraData {
-    /// Construct a new `ExtraData` instance from the data in `cursor`
-    pub fn new(cursor: &mut Cursor<Vec<u8>>, _header: &ShellLinkHeader) -> Result<Self> {
+    /// Construct a new `ExtraData` instance from the data in `cursor`. The `header` is not
+    /// used, see [`ExtraData::from_bytes`] to parse extra data on its own.
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, header: &ShellLinkHeader) -> Result<Self> {
+        Self::with_options(cursor, header, &LnkOptions::default())
+    }
+
+    /// Construct a new `ExtraData` instance from the data in `cursor`, decoding ANSI strings
+    /// using the `ansi_code_page` of `options`, or else the code page of the
+    /// `ConsoleFEDataBlock`.
+    pub fn with_options<T: AsRef<[u8]>>(
+        cursor: &mut Cursor<T>,
+        header: &ShellLinkHeader,
+        options: &LnkOptions,
+    ) -> Result<Self> {
+        crate::parser::borrowed(cursor, |cursor| {
+            Self::parse(cursor, header, options, &mut Vec::new())
+        })
+    }
+
+    /// Construct a new `ExtraData` instance from a standalone extra data section, such as
+    /// one carved out of a shell link by another tool. `data` starts with the first block
+    /// and normally ends with the TerminalBlock.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::new(&mut Cursor::new(data), &ShellLinkHeader::default())
+    }
+
+    pub(crate) fn parse(
+        cursor: &mut Cursor<&[u8]>,
+        _header: &ShellLinkHeader,
+        options: &LnkOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Self> {
         let mut this = Self::default();
 
-        while {
-            match this.parse_next_block(cursor) {
-                Err(ExtraDataError::UnknownBlock(a, b)) => Err(ExtraDataError::UnknownBlock(a, b)),
-                Err(_) => Ok(false),
-                Ok(_) => Ok(true),
-            }?
-        } {}
+        while this.parse_next_block(cursor, options, warnings)? {}
+
+        // The ANSI targets use the code page of the caller, or else that of the
+        // `ConsoleFEDataBlock`, which may follow them.
+        let code_page = options
+            .ansi_code_page
+            .map(u32::from)
+            .or_else(|| this.console_fe_props.as_ref().map(|props| props.code_page));
+        if let Some(ref mut environment_props) = this.environment_props {
+            environment_props.code_page = code_page;
+        }
+        if let Some(ref mut icon_environment_props) = this.icon_environment_props {
+            icon_environment_props.code_page = code_page;
+        }
 
         Ok(this)
     }
 
+    /// The raw bytes of the first block with `signature`, including its size and signature
+    /// fields. This gives access to blocks whose contents are not fully decoded.
+    pub fn raw_block(&self, signature: u32) -> Option<&[u8]> {
+        self.blocks
+            .iter()
+            .find(|block| block.get(4..8) == Some(&signature.to_le_bytes()[..]))
+            .map(|block| block.as_slice())
+    }
+
+    /// The raw bytes of the first block of the type `signature`, see [`ExtraData::raw_block`]
+    pub fn raw_block_by_signature(&self, signature: DataBlockSignature) -> Option<&[u8]> {
+        self.raw_block(signature.into())
+    }
+
+    /// The declared and consumed size of each block in the order they were read, including
+    /// the blocks that were skipped because their parser rejected them
+    pub fn block_sizes(&self) -> &[BlockSize] {
+        &self.sizes
+    }
+
+    /// Discard the raw bytes retained for each block, keeping only the decoded blocks and
+    /// their sizes, see [`crate::Lnk::drop_raw`]
+    pub fn drop_raw(&mut self) {
+        self.blocks = Vec::new();
+    }
+
+    /// Check the declared size of each block against the one the specification requires,
+    /// recording the blocks that differ in `issues`. Blocks with an unknown signature are
+    /// not checked. This uses the sizes recorded while parsing, which are kept by
+    /// [`ExtraData::drop_raw`].
+    pub(crate) fn validate_block_sizes(&self, issues: &mut Vec<ValidationIssue>) {
+        for size in &self.sizes {
+            let (found, signature) = (size.declared, size.signature);
+
+            let (expected, exact) = match DataBlockSignature::from(signature) {
+                DataBlockSignature::EnvironmentVariable
+                | DataBlockSignature::Darwin
+                | DataBlockSignature::IconEnvironment => (0x0000_0314, true),
+                DataBlockSignature::Console => (0x0000_00cc, true),
+                DataBlockSignature::Tracker => (0x0000_0060, true),
+                DataBlockSignature::ConsoleFE => (0x0000_000c, true),
+                DataBlockSignature::SpecialFolder => (0x0000_0010, true),
+                DataBlockSignature::Shim => (0x0000_0088, false),
+                DataBlockSignature::PropertyStore => (0x0000_000c, false),
+                DataBlockSignature::KnownFolder => (0x0000_001c, true),
+                DataBlockSignature::VistaAndAboveIdList => (0x0000_000a, false),
+                DataBlockSignature::Unknown(_) => continue,
+            };
+
+            if (exact && found != expected) || found < expected {
+                issues.push(ValidationIssue::InvalidBlockSize {
+                    signature,
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+
+    /// Serialize the blocks into `buf`, followed by the TerminalBlock. Blocks are written back
+    /// exactly as they were read. Without raw bytes, after [`ExtraData::drop_raw`] or for an
+    /// `ExtraData` built by hand, the decoded blocks are serialized instead, see
+    /// [`ExtraData::write_decoded`].
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        if self.blocks.is_empty() {
+            self.write_decoded(buf);
+        } else {
+            self.blocks
+                .iter()
+                .for_each(|block| buf.extend_from_slice(block));
+        }
+        buf.extend_from_slice(&0u32.to_le_bytes());
+    }
+
+    /// Serialize each decoded block into `buf`, in the order the blocks were read and then in
+    /// the order of their signatures. Blocks that were not decoded, such as those with an
+    /// unknown signature, cannot be written, and only one block of each type is.
+    fn write_decoded(&self, buf: &mut Vec<u8>) {
+        use DataBlockSignature::*;
+
+        let mut signatures = self
+            .sizes
+            .iter()
+            .map(|size| DataBlockSignature::from(size.signature))
+            .collect::<Vec<_>>();
+        signatures.extend_from_slice(&[
+            EnvironmentVariable,
+            Console,
+            Tracker,
+            ConsoleFE,
+            SpecialFolder,
+            Darwin,
+            IconEnvironment,
+            Shim,
+            PropertyStore,
+            KnownFolder,
+            VistaAndAboveIdList,
+        ]);
+
+        let mut written = Vec::new();
+        for signature in signatures {
+            if written.contains(&signature) {
+                continue;
+            }
+
+            let mut payload = Vec::new();
+            let decoded = match signature {
+                EnvironmentVariable => self
+                    .environment_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                Console => self.console_props.as_ref().map(|b| b.write(&mut payload)),
+                Tracker => self.tracker_props.as_ref().map(|b| b.write(&mut payload)),
+                ConsoleFE => self
+                    .console_fe_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                SpecialFolder => self
+                    .special_folder_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                Darwin => self.darwin_props.as_ref().map(|b| b.write(&mut payload)),
+                IconEnvironment => self
+                    .icon_environment_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                Shim => self.shim_props.as_ref().map(|b| b.write(&mut payload)),
+                PropertyStore => self
+                    .property_store_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                KnownFolder => self
+                    .known_folder_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                VistaAndAboveIdList => self
+                    .vista_and_above_idlist_props
+                    .as_ref()
+                    .map(|b| b.write(&mut payload)),
+                Unknown(_) => None,
+            };
+
+            if decoded.is_some() {
+                buf.extend_from_slice(&(payload.len() as u32 + 8).to_le_bytes());
+                buf.extend_from_slice(&u32::from(signature).to_le_bytes());
+                buf.extend_from_slice(&payload);
+                written.push(signature);
+            }
+        }
+    }
+
+    /// Parse the block at the current position of `cursor`. Returns `false` once the
+    /// TerminalBlock or the end of the data has been reached.
     fn parse_next_block(
         &mut self,
-        cursor: &mut Cursor<Vec<u8>>,
-    ) -> std::result::Result<(), ExtraDataError> {
-        let block_size = cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?;
-        let block_signature = cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?;
-
-        match (block_size, block_signature) {
-            (0x0000_0314, 0xa000_0001) => {
-                self.environment_props =
-                    EnvironmentVariableDataBlock::new(block_size, block_signature, cursor)
-                        .map(Some)?;
-                Ok(())
-            }
-            (0x0000_00cc, 0xa000_0002) => {
-                self.console_props =
-                    ConsoleDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_0060, 0xa000_0003) => {
-                self.tracker_props =
-                    TrackerDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_000c, 0xa000_0004) => {
-                self.console_fe_props =
-                    ConsoleFEDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_0010, 0xa000_0005) => {
-                self.special_folder_props =
-                    SpecialFolderDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_0314, 0xa000_0006) => {
-                self.darwin_props =
-                    DarwinDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_0314, 0xa000_0007) => {
-                self.icon_environment_props =
-                    IconEnvironmentDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (_, 0xa000_0008) => {
-                self.shim_props =
-                    ShimDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (_, 0xa000_0009) => {
-                self.property_store_props =
-                    PropertyStoreDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (0x0000_001c, 0xa000_000b) => {
-                self.known_folder_props =
-                    KnownFolderDataBlock::new(block_size, block_signature, cursor).map(Some)?;
-                Ok(())
-            }
-            (_, 0xa000_000c) => {
-                self.vista_and_above_idlist_props =
-                    VistaAndAboveIDListDataBlock::new(block_size, block_signature, cursor)
-                        .map(Some)?;
-                Ok(())
-            }
-            (size, signature) => Err(ExtraDataError::UnknownBlock(size, signature)),
+        cursor: &mut Cursor<&[u8]>,
+        options: &LnkOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> std::result::Result<bool, ExtraDataError> {
+        let block_start = cursor.position();
+        let available = (cursor.get_ref().len() as u64).saturating_sub(block_start);
+
+        // Some writers omit the TerminalBlock, ending the data on a block boundary instead
+        if available == 0 {
+            return Ok(false);
+        }
+
+        let block_size = match read(cursor, |c| c.read_u32::<LE>()) {
+            Ok(block_size) => block_size,
+            Err(e) if options.strict => return Err(e),
+            Err(_) => {
+                warnings.push(ParseWarning::TruncatedExtraData(available as usize));
+                cursor.set_position(block_start + available);
+                return Ok(false);
+            }
+        };
+
+        if block_size < 0x0000_0004 {
+            return Ok(false);
+        }
+
+        let block_signature = match read(cursor, |c| c.read_u32::<LE>()) {
+            Ok(block_signature) => block_signature,
+            Err(e) if options.strict => return Err(e),
+            Err(_) => {
+                warnings.push(ParseWarning::TruncatedExtraData(available as usize));
+                cursor.set_position(block_start + available);
+                return Ok(false);
+            }
+        };
+
+        let resync = options.resync_extra_data && !options.strict;
+
+        if block_size < 0x0000_0008 {
+            let error = ExtraDataError::UndersizedBlock(block_size, block_signature);
+            if options.strict {
+                return Err(error);
+            }
+            if resync && self.resync(cursor, block_start, false, warnings) {
+                return Ok(true);
+            }
+
+            warnings.push(ParseWarning::InvalidBlock(error.to_string()));
+            cursor.set_position(block_start + available);
+            return Ok(false);
+        }
+
+        if block_size as u64 > available {
+            if options.strict {
+                return Err(ExtraDataError::TruncatedBlock(block_size, block_signature));
+            }
+            if resync && self.resync(cursor, block_start, false, warnings) {
+                return Ok(true);
+            }
+
+            warnings.push(ParseWarning::TruncatedExtraData(available as usize));
+            cursor.set_position(block_start + available);
+            return Ok(false);
+        }
+
+        let block_start = block_start as usize;
+        self.blocks
+            .push(cursor.get_ref()[block_start..block_start + block_size as usize].to_vec());
+
+        let result = match DataBlockSignature::from(block_signature) {
+            DataBlockSignature::EnvironmentVariable => {
+                EnvironmentVariableDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.environment_props = Some(block))
+            }
+            DataBlockSignature::Console if options.strict => {
+                ConsoleDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.console_props = Some(block))
+            }
+            DataBlockSignature::Console => {
+                ConsoleDataBlock::new_lenient(block_size, block_signature, cursor)
+                    .map(|block| self.console_props = Some(block))
+            }
+            DataBlockSignature::Tracker => {
+                TrackerDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.tracker_props = Some(block))
+            }
+            DataBlockSignature::ConsoleFE => {
+                ConsoleFEDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.console_fe_props = Some(block))
+            }
+            DataBlockSignature::SpecialFolder => {
+                SpecialFolderDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.special_folder_props = Some(block))
+            }
+            DataBlockSignature::Darwin => DarwinDataBlock::new(block_size, block_signature, cursor)
+                .map(|block| self.darwin_props = Some(block)),
+            DataBlockSignature::IconEnvironment => {
+                IconEnvironmentDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.icon_environment_props = Some(block))
+            }
+            DataBlockSignature::Shim => ShimDataBlock::new(block_size, block_signature, cursor)
+                .map(|block| self.shim_props = Some(block)),
+            DataBlockSignature::PropertyStore => {
+                PropertyStoreDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.property_store_props = Some(block))
+            }
+            DataBlockSignature::KnownFolder => {
+                KnownFolderDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.known_folder_props = Some(block))
+            }
+            DataBlockSignature::VistaAndAboveIdList => {
+                VistaAndAboveIDListDataBlock::new(block_size, block_signature, cursor)
+                    .map(|block| self.vista_and_above_idlist_props = Some(block))
+            }
+            DataBlockSignature::Unknown(_) => {
+                Err(ExtraDataError::UnknownBlock(block_size, block_signature))
+            }
+        };
+
+        let consumed = match DataBlockSignature::from(block_signature) {
+            // A console block is always read with its fixed layout
+            DataBlockSignature::Console if result.is_ok() => ConsoleDataBlock::SIZE,
+            _ => (cursor.position() - block_start as u64) as u32,
+        };
+        let size = BlockSize {
+            signature: block_signature,
+            declared: block_size,
+            consumed,
+        };
+        if result.is_ok() && !size.size_matches() {
+            warnings.push(ParseWarning::BlockSizeMismatch {
+                signature: size.signature,
+                declared: size.declared,
+                consumed: size.consumed,
+            });
+        }
+        self.sizes.push(size);
+
+        // Continue after the declared size, whatever the block parser consumed
+        cursor.set_position(block_start as u64 + block_size as u64);
+
+        let result = result.map_err(|e| match e {
+            ExtraDataError::Read { offset, source, .. } => ExtraDataError::Read {
+                offset,
+                signature: Some(block_signature),
+                source,
+            },
+            e => e,
+        });
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) if options.strict => Err(e),
+            Err(ExtraDataError::UnknownBlock(size, signature)) => {
+                warnings.push(ParseWarning::UnknownBlock(size, signature));
+                Ok(true)
+            }
+            Err(e) => {
+                let misaligned = matches!(e, ExtraDataError::InvalidBlockSize { .. })
+                    && !is_block_boundary(cursor.get_ref(), cursor.position() as usize);
+
+                warnings.push(ParseWarning::InvalidBlock(e.to_string()));
+                if resync && misaligned {
+                    self.resync(cursor, block_start as u64, true, warnings);
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Move `cursor` to the first valid block header after the header of the block at
+    /// `block_start`, so that parsing can continue past a block with a corrupt size. The raw
+    /// bytes of the corrupt block are kept up to that point, replacing the bytes already
+    /// `recorded` for it. Returns `false`, leaving `cursor` where it is, if there is no later
+    /// valid block.
+    fn resync(
+        &mut self,
+        cursor: &mut Cursor<&[u8]>,
+        block_start: u64,
+        recorded: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> bool {
+        let data = *cursor.get_ref();
+        let block_start = block_start as usize;
+
+        let next = match (block_start + 8..data.len()).find(|pos| is_block_start(data, *pos)) {
+            Some(next) => next,
+            None => return false,
+        };
+
+        // Keep the corrupt block up to the next one, in place of its declared extent
+        if recorded {
+            self.blocks.pop();
         }
+        self.blocks.push(data[block_start..next].to_vec());
+
+        warnings.push(ParseWarning::Resynchronized(next - block_start));
+        cursor.set_position(next as u64);
+        true
+    }
+}
+
+/// Run the read `f` on `cursor`, recording the position it started at if it fails
+fn read<T>(
+    cursor: &mut Cursor<&[u8]>,
+    f: impl FnOnce(&mut Cursor<&[u8]>) -> std::io::Result<T>,
+) -> Result<T> {
+    let offset = cursor.position();
+
+    f(cursor).map_err(|source| ExtraDataError::Read {
+        offset,
+        signature: None,
+        source,
+    })
+}
+
+/// Whether a block with a known signature, and a size that fits the data, starts at `pos`
+fn is_block_start(data: &[u8], pos: usize) -> bool {
+    let read_u32 = |pos: usize| {
+        data.get(pos..pos + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    match (read_u32(pos), read_u32(pos + 4)) {
+        (Some(size), Some(signature)) => {
+            size >= 8
+                && pos + size as usize <= data.len()
+                && !matches!(
+                    DataBlockSignature::from(signature),
+                    DataBlockSignature::Unknown(_)
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Whether `pos` is a plausible place for the next block to start: the end of the data, a
+/// TerminalBlock, or a block with a known signature
+fn is_block_boundary(data: &[u8], pos: usize) -> bool {
+    match data.get(pos..pos + 4) {
+        None => pos >= data.len(),
+        Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) < 4 || is_block_start(data, pos),
+    }
+}
+
+/// Append `data` to `buf` as a field of `len` bytes, truncated or padded with zeros
+fn write_fixed(buf: &mut Vec<u8>, data: &[u8], len: usize) {
+    buf.extend_from_slice(&data[..data.len().min(len)]);
+    buf.resize(buf.len() + len.saturating_sub(data.len()), 0);
+}
+
+/// Check that a block declares the size the specification requires for it
+fn check_size(block_size: u32, block_signature: u32, expected: u32) -> Result<()> {
+    if block_size == expected {
+        Ok(())
+    } else {
+        Err(ExtraDataError::InvalidBlockSize {
+            signature: block_signature,
+            expected,
+            found: block_size,
+        })
+    }
+}
+
+/// Check that a variable-length block declares at least the minimum size the specification
+/// requires for it
+fn check_min_size(block_size: u32, block_signature: u32, minimum: u32) -> Result<()> {
+    if block_size >= minimum {
+        Ok(())
+    } else {
+        Err(ExtraDataError::InvalidBlockSize {
+            signature: block_signature,
+            expected: minimum,
+            found: block_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockSize, DataBlockSignature, ExtraData};
+    use crate::error::{ExtraDataError, ParseWarning};
+    use crate::{Lnk, LnkOptions, ShellLinkHeader};
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    fn header() -> ShellLinkHeader {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        Lnk::try_from(data).unwrap().header
+    }
+
+    #[test]
+    fn undersized_block() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0000_0004u32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0009u32.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+
+        let strict = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let result = ExtraData::with_options(&mut Cursor::new(data.as_slice()), &header(), &strict);
+
+        assert!(matches!(
+            result,
+            Err(ExtraDataError::UndersizedBlock(0x0000_0004, 0xa000_0009))
+        ));
+
+        let mut warnings = Vec::new();
+        ExtraData::parse(
+            &mut Cursor::new(data.as_slice()),
+            &header(),
+            &LnkOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::InvalidBlock(
+                "undersized extra block: size: 0x00000004, signature: 0xa0000009".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn oversized_block() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0xffff_fff0u32.to_le_bytes());
+        data.extend_from_slice(&0xa000_000cu32.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+
+        let strict = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let result = ExtraData::with_options(&mut Cursor::new(data.as_slice()), &header(), &strict);
+
+        assert!(matches!(
+            result,
+            Err(ExtraDataError::TruncatedBlock(0xffff_fff0, 0xa000_000c))
+        ));
+
+        let mut warnings = Vec::new();
+        ExtraData::parse(
+            &mut Cursor::new(data.as_slice()),
+            &header(),
+            &LnkOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(warnings, vec![ParseWarning::TruncatedExtraData(24)]);
+    }
+
+    #[test]
+    fn terminated() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert_eq!(&data[data.len() - 4..], &[0; 4]);
+        assert!(lnk.extra_data.tracker_props.is_some());
+        assert!(lnk.warnings().is_empty());
+    }
+
+    #[test]
+    fn missing_terminal_block() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data.truncate(data.len() - 4);
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert!(lnk.extra_data.tracker_props.is_some());
+        assert!(lnk.warnings().is_empty());
+    }
+
+    #[test]
+    fn resync() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data[0x2d6..0x2da].copy_from_slice(&0x70u32.to_le_bytes());
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(lnk.extra_data.tracker_props.is_none());
+        assert!(lnk.extra_data.property_store_props.is_none());
+
+        let options = LnkOptions {
+            resync_extra_data: true,
+            ..Default::default()
+        };
+        let lnk = Lnk::parse_with_options(&data, &options).unwrap();
+
+        assert!(lnk.extra_data.tracker_props.is_none());
+        assert!(lnk.extra_data.property_store_props.is_some());
+        assert!(matches!(
+            lnk.warnings(),
+            [
+                ParseWarning::InvalidBlock(_),
+                ParseWarning::Resynchronized(0x60)
+            ]
+        ));
+        assert_eq!(lnk.to_bytes(), data);
+    }
+
+    #[test]
+    fn undersized_block_in_file() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data[0x2d6..0x2da].copy_from_slice(&0x05u32.to_le_bytes());
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(lnk.extra_data.tracker_props.is_none());
+        assert!(matches!(lnk.warnings(), [ParseWarning::InvalidBlock(_)]));
+
+        let options = LnkOptions {
+            resync_extra_data: true,
+            ..Default::default()
+        };
+        let lnk = Lnk::parse_with_options(&data, &options).unwrap();
+        assert!(lnk.extra_data.property_store_props.is_some());
+        assert!(matches!(
+            lnk.warnings(),
+            [ParseWarning::Resynchronized(0x60)]
+        ));
+
+        let options = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(Lnk::parse_with_options(&data, &options).is_err());
+    }
+
+    #[test]
+    fn trailing_data() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data.extend_from_slice(b"appended payload");
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert!(lnk.extra_data.property_store_props.is_some());
+        assert_eq!(lnk.warnings(), [ParseWarning::TrailingData(16)]);
+    }
+
+    #[test]
+    fn from_bytes() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+
+        let extra_data = ExtraData::from_bytes(&data[0x2d6..]).unwrap();
+        assert_eq!(extra_data, lnk.extra_data);
+        assert!(extra_data.tracker_props.is_some());
+        assert!(extra_data.property_store_props.is_some());
+    }
+
+    #[test]
+    fn raw_block() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+        let raw = lnk
+            .extra_data
+            .raw_block_by_signature(DataBlockSignature::PropertyStore)
+            .unwrap();
+
+        assert_eq!(
+            raw.len(),
+            u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize
+        );
+        assert_eq!(raw, &data[0x336..0x336 + 0xa7]);
+        assert_eq!(lnk.extra_data.raw_block(0xa000_0002), None);
+    }
+
+    #[test]
+    fn signature_round_trip() {
+        for value in (0xa000_0001..=0xa000_000c).chain([0, 0xa000_000a, 0xa000_000d]) {
+            let signature = DataBlockSignature::from(value);
+            assert_eq!(u32::from(signature), value);
+
+            let known = !matches!(value, 0 | 0xa000_000a | 0xa000_000d);
+            assert_eq!(known, signature != DataBlockSignature::Unknown(value));
+        }
+
+        assert_eq!(
+            DataBlockSignature::from(0xa000_000b),
+            DataBlockSignature::KnownFolder
+        );
+        assert_eq!(
+            u32::from(DataBlockSignature::VistaAndAboveIdList),
+            0xa000_000c
+        );
+    }
+
+    #[test]
+    fn read_error_offset() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data.truncate(0x336 + 6);
+        let options = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+
+        let err = Lnk::parse_with_options(&data, &options).unwrap_err();
+        assert!(err.to_string().contains("at offset 0x33a"), "{}", err);
+        assert!(matches!(
+            err,
+            crate::error::Error::ExtraDataError(ExtraDataError::Read {
+                offset: 0x33a,
+                signature: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn truncated_mid_block() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data.truncate(data.len() - 10);
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert!(lnk.extra_data.tracker_props.is_some());
+        assert!(lnk.extra_data.property_store_props.is_none());
+        assert_eq!(lnk.warnings(), [ParseWarning::TruncatedExtraData(0xa1)]);
+
+        data.truncate(data.len() - 0xa1 + 2);
+        let lnk = Lnk::parse(&data).unwrap();
+        assert_eq!(lnk.warnings(), [ParseWarning::TruncatedExtraData(2)]);
+
+        let strict = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(Lnk::parse_with_options(&data, &strict).is_err());
+    }
+
+    #[test]
+    fn invalid_console_block_size() {
+        for block_size in [0xcausize, 0xce] {
+            let mut data = Vec::new();
+            data.extend_from_slice(&(block_size as u32).to_le_bytes());
+            data.extend_from_slice(&0xa000_0002u32.to_le_bytes());
+            data.extend_from_slice(&[0; 0x20]);
+            data.extend_from_slice(&700u32.to_le_bytes());
+            data.resize(block_size, 0);
+            data.extend_from_slice(&0x0000_000cu32.to_le_bytes());
+            data.extend_from_slice(&0xa000_0004u32.to_le_bytes());
+            data.extend_from_slice(&1252u32.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+
+            let strict = LnkOptions {
+                strict: true,
+                ..Default::default()
+            };
+            let result =
+                ExtraData::with_options(&mut Cursor::new(data.as_slice()), &header(), &strict);
+
+            assert!(matches!(
+                result,
+                Err(ExtraDataError::InvalidBlockSize {
+                    signature: 0xa000_0002,
+                    expected: 0xcc,
+                    found,
+                }) if found == block_size as u32
+            ));
+
+            let mut warnings = Vec::new();
+            let extra_data = ExtraData::parse(
+                &mut Cursor::new(data.as_slice()),
+                &header(),
+                &LnkOptions::default(),
+                &mut warnings,
+            )
+            .unwrap();
+
+            let console_props = extra_data.console_props.unwrap();
+            assert_eq!(console_props.block_size, block_size as u32);
+            assert!(console_props.is_bold());
+            assert_eq!(extra_data.console_fe_props.unwrap().code_page, 1252);
+            assert_eq!(
+                warnings,
+                vec![ParseWarning::BlockSizeMismatch {
+                    signature: 0xa000_0002,
+                    declared: block_size as u32,
+                    consumed: 0xcc,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn ansi_targets_use_console_fe_code_page() {
+        let mut target = vec![0; 260 + 520];
+        target[..7].copy_from_slice(b"D:\\Caf\xe9");
+
+        let mut data = Vec::new();
+        for signature in &[0xa000_0001u32, 0xa000_0007] {
+            data.extend_from_slice(&0x0000_0314u32.to_le_bytes());
+            data.extend_from_slice(&signature.to_le_bytes());
+            data.extend_from_slice(&target);
+        }
+        data.extend_from_slice(&0x0000_000cu32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0004u32.to_le_bytes());
+        data.extend_from_slice(&1252u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let extra_data = ExtraData::new(&mut Cursor::new(data.as_slice()), &header()).unwrap();
+        assert_eq!(
+            extra_data.environment_props.unwrap().target(),
+            Some(r"D:\Café".to_string())
+        );
+        assert_eq!(
+            extra_data.icon_environment_props.unwrap().target(),
+            Some(r"D:\Café".to_string())
+        );
+
+        let options = LnkOptions {
+            ansi_code_page: Some(1251),
+            ..Default::default()
+        };
+        let extra_data =
+            ExtraData::with_options(&mut Cursor::new(data.as_slice()), &header(), &options)
+                .unwrap();
+        assert_eq!(
+            extra_data.environment_props.unwrap().target(),
+            Some(r"D:\Cafй".to_string())
+        );
+    }
+
+    #[test]
+    fn block_sizes() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0000_0062u32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0003u32.to_le_bytes());
+        data.extend_from_slice(&[0; 0x62 - 8]);
+        data.extend_from_slice(&0x0000_000cu32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0004u32.to_le_bytes());
+        data.extend_from_slice(&1252u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let extra_data = ExtraData::new(&mut Cursor::new(data.as_slice()), &header()).unwrap();
+        let sizes = extra_data.block_sizes();
+
+        assert_eq!(
+            sizes[0],
+            BlockSize {
+                signature: 0xa000_0003,
+                declared: 0x62,
+                consumed: 8,
+            }
+        );
+        assert!(!sizes[0].size_matches());
+        assert!(sizes[1].size_matches());
+
+        let lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk
+            .extra_data
+            .block_sizes()
+            .iter()
+            .all(BlockSize::size_matches));
+    }
+
+    #[test]
+    fn unknown_block_is_skipped() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0000_000cu32.to_le_bytes());
+        data.extend_from_slice(&0xa000_00ffu32.to_le_bytes());
+        data.extend_from_slice(&[0xff; 4]);
+        data.extend_from_slice(&0x0000_0010u32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0005u32.to_le_bytes());
+        data.extend_from_slice(&0x0000_0025u32.to_le_bytes());
+        data.extend_from_slice(&0x0000_00ddu32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut warnings = Vec::new();
+        let extra_data = ExtraData::parse(
+            &mut Cursor::new(data.as_slice()),
+            &header(),
+            &LnkOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(
+            extra_data.special_folder_props.unwrap().special_folder_id,
+            0x25
+        );
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::UnknownBlock(0x0c, 0xa000_00ff)]
+        );
+
+        let strict = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ExtraData::with_options(&mut Cursor::new(data.as_slice()), &header(), &strict),
+            Err(ExtraDataError::UnknownBlock(0x0c, 0xa000_00ff))
+        ));
     }
 }
@@ -1,9 +1,9 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
+use crate::{escape_for_display, Guid};
 use std::io::{Cursor, Read};
 
 /// A PropertyStoreDataBlock structure specifies a set of properties that can be used by applications to store extra data in the shell link.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct PropertyStoreDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the PropertyStoreDataBlock structure. This value MUST be greater than or equal to 0x0000000C.
     pub block_size: u32,
@@ -16,21 +16,22 @@ pub struct PropertyStoreDataBlock {
 }
 
 impl PropertyStoreDataBlock {
-    /// Construct a new `KnownFolderDataBlock`
+    /// Construct a new `PropertyStoreDataBlock`
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_min_size(block_size, block_signature, 0x0000_000c)?;
+
         let this = Self {
             block_size,
             block_signature,
             property_store: {
-                let store_size = block_size as usize - (std::mem::size_of::<u32>() * 2);
+                let store_size =
+                    (block_size as usize).saturating_sub(std::mem::size_of::<u32>() * 2);
                 let mut property_store = vec![0; store_size];
-                cursor
-                    .read_exact(&mut property_store)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut property_store))?;
                 property_store
             },
         };
@@ -38,3 +39,298 @@ impl PropertyStoreDataBlock {
         Ok(this)
     }
 }
+
+impl PropertyStoreDataBlock {
+    /// The format ID of the `System.AppUserModel` properties,
+    /// `{9F4C2855-9F79-4B39-A8D0-E1D42DE1D5F3}`
+    pub const APP_USER_MODEL_FMTID: Guid =
+        Guid::from_u128(0xf3d5_e12d_d4e1_d0a8_4b39_9f79_9f4c_2855);
+
+    /// The format ID of property storages whose properties are identified by name,
+    /// `{D5CDD505-2E9C-101B-9397-08002B2CF9AE}`
+    const STRING_NAME_FMTID: Guid = Guid::from_u128(0xaef9_2c2b_0008_9793_101b_2e9c_d5cd_d505);
+
+    /// The properties stored with an integer ID, in the order they appear. Storages of
+    /// properties identified by name are skipped, and decoding stops at the first malformed
+    /// storage or value.
+    pub fn properties(&self) -> Vec<Property> {
+        let mut properties = Vec::new();
+        let mut storages = self.property_store.as_slice();
+
+        while let Some(storage_size) = read_u32(storages, 0).filter(|size| *size >= 24) {
+            let storage = match storages.get(..storage_size as usize) {
+                Some(storage) => storage,
+                None => break,
+            };
+            storages = &storages[storage_size as usize..];
+
+            if read_u32(storage, 4) != Some(0x5350_5331) {
+                break;
+            }
+
+            let format_id = Guid::from_slice(&storage[8..24]).unwrap_or_default();
+            if format_id == Self::STRING_NAME_FMTID {
+                continue;
+            }
+
+            let mut values = &storage[24..];
+            while let Some(value_size) = read_u32(values, 0).filter(|size| *size >= 13) {
+                let value = match values.get(..value_size as usize) {
+                    Some(value) => value,
+                    None => break,
+                };
+                values = &values[value_size as usize..];
+
+                properties.push(Property {
+                    format_id,
+                    id: read_u32(value, 4).unwrap_or_default(),
+                    value: PropertyValue::parse(&value[9..]),
+                });
+            }
+        }
+
+        properties
+    }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.property_store);
+    }
+
+    /// Escape the string properties with [`crate::escape_for_display`], see
+    /// [`crate::Lnk::sanitize_for_display`]. A value that changes is stored again as a
+    /// `VT_LPWSTR`, anything that is not decoded by [`PropertyStoreDataBlock::properties`] is
+    /// kept as it is.
+    pub(crate) fn sanitize_for_display(&mut self) {
+        let mut store = Vec::with_capacity(self.property_store.len());
+        let mut storages = self.property_store.as_slice();
+
+        while let Some(storage) = read_u32(storages, 0)
+            .filter(|size| *size >= 24)
+            .and_then(|size| storages.get(..size as usize))
+            .filter(|storage| read_u32(storage, 4) == Some(0x5350_5331))
+        {
+            storages = &storages[storage.len()..];
+
+            if Guid::from_slice(&storage[8..24]) == Some(Self::STRING_NAME_FMTID) {
+                store.extend_from_slice(storage);
+                continue;
+            }
+
+            let storage_start = store.len();
+            store.extend_from_slice(&storage[..24]);
+
+            let mut values = &storage[24..];
+            while let Some(value) = read_u32(values, 0)
+                .filter(|size| *size >= 13)
+                .and_then(|size| values.get(..size as usize))
+            {
+                values = &values[value.len()..];
+
+                match PropertyValue::parse(&value[9..]) {
+                    PropertyValue::String(s) if escape_for_display(&s) != s => {
+                        write_string_value(&mut store, &value[4..9], &escape_for_display(&s))
+                    }
+                    _ => store.extend_from_slice(value),
+                }
+            }
+            store.extend_from_slice(values);
+
+            let storage_size = (store.len() - storage_start) as u32;
+            store[storage_start..storage_start + 4].copy_from_slice(&storage_size.to_le_bytes());
+        }
+        store.extend_from_slice(storages);
+
+        self.block_size = (store.len() + std::mem::size_of::<u32>() * 2) as u32;
+        self.property_store = store;
+    }
+
+    /// The value of the property `id` in the format `format_id`
+    pub fn property(&self, format_id: Guid, id: u32) -> Option<PropertyValue> {
+        self.properties()
+            .into_iter()
+            .find(|property| property.format_id == format_id && property.id == id)
+            .map(|property| property.value)
+    }
+}
+
+/// A property of a [`PropertyStoreDataBlock`], identified by its property key: the GUID of
+/// its format and its integer ID within that format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Property {
+    /// The GUID of the property format
+    pub format_id: Guid,
+
+    /// The ID of the property within its format
+    pub id: u32,
+
+    /// The value of the property
+    pub value: PropertyValue,
+}
+
+/// The value of a [`Property`], a TypedPropertyValue structure ([MS-OLEPS] section 2.15)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyValue {
+    /// A `VT_LPWSTR`, `VT_BSTR` or `VT_LPSTR` string
+    String(String),
+
+    /// A `VT_I4` signed integer
+    I32(i32),
+
+    /// A `VT_UI4` unsigned integer
+    U32(u32),
+
+    /// A `VT_UI8` unsigned integer
+    U64(u64),
+
+    /// A `VT_BOOL` boolean
+    Bool(bool),
+
+    /// A `VT_FILETIME` timestamp
+    FileTime(u64),
+
+    /// A `VT_CLSID` GUID
+    Guid(Guid),
+
+    /// A value of any other type, with its `VARTYPE` and undecoded bytes
+    Other(u16, Vec<u8>),
+}
+
+impl PropertyValue {
+    /// Decode a TypedPropertyValue, starting with its type and padding
+    fn parse(data: &[u8]) -> Self {
+        let var_type = data
+            .get(..2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .unwrap_or_default();
+        let value = data.get(4..).unwrap_or_default();
+
+        let decoded = match var_type {
+            0x0003 => read_u32(value, 0).map(|v| Self::I32(v as i32)),
+            0x0013 => read_u32(value, 0).map(Self::U32),
+            0x0015 => read_u64(value).map(Self::U64),
+            0x000b => value
+                .get(..2)
+                .map(|b| Self::Bool(u16::from_le_bytes([b[0], b[1]]) != 0)),
+            0x0040 => read_u64(value).map(Self::FileTime),
+            0x0048 => Guid::from_slice(value).map(Self::Guid),
+            // VT_BSTR and VT_LPSTR, with a size in bytes
+            0x0008 | 0x001e => read_u32(value, 0)
+                .and_then(|size| value.get(4..4 + size as usize))
+                .map(|b| {
+                    let len = b.iter().position(|c| *c == 0).unwrap_or(b.len());
+                    Self::String(crate::code_page::decode(&b[..len], None))
+                }),
+            // VT_LPWSTR, with a length in characters
+            0x001f => read_u32(value, 0)
+                .and_then(|len| value.get(4..4 + 2 * len as usize))
+                .map(|b| {
+                    let wide = b
+                        .chunks_exact(2)
+                        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                        .take_while(|c| *c != 0)
+                        .collect::<Vec<u16>>();
+                    Self::String(String::from_utf16_lossy(&wide))
+                }),
+            _ => None,
+        };
+
+        decoded.unwrap_or_else(|| Self::Other(var_type, value.to_vec()))
+    }
+}
+
+/// Append a serialized property value of `VT_LPWSTR` `s`, with the property ID and reserved
+/// byte in `id`
+fn write_string_value(buf: &mut Vec<u8>, id: &[u8], s: &str) {
+    let wide = s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let padding = (4 - wide.len() * 2 % 4) % 4;
+    let value_size = 17 + wide.len() * 2 + padding;
+
+    buf.extend_from_slice(&(value_size as u32).to_le_bytes());
+    buf.extend_from_slice(id);
+    buf.extend_from_slice(&0x001fu16.to_le_bytes());
+    buf.extend_from_slice(&[0; 2]);
+    buf.extend_from_slice(&(wide.len() as u32).to_le_bytes());
+    wide.iter()
+        .for_each(|c| buf.extend_from_slice(&c.to_le_bytes()));
+    buf.resize(buf.len() + padding, 0);
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(data: &[u8]) -> Option<u64> {
+    let mut value = [0; 8];
+    value.copy_from_slice(data.get(..8)?);
+    Some(u64::from_le_bytes(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PropertyStoreDataBlock, PropertyValue};
+    use crate::Lnk;
+    use std::convert::TryFrom;
+    use std::path::Path;
+
+    #[test]
+    fn properties() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let props = lnk.extra_data.property_store_props.unwrap();
+        let properties = props.properties();
+
+        assert_eq!(properties.len(), 3);
+        assert_eq!(
+            props.property(PropertyStoreDataBlock::APP_USER_MODEL_FMTID, 5),
+            Some(PropertyValue::String("308046B0AF4A39CB".to_string()))
+        );
+        assert_eq!(
+            props.property(PropertyStoreDataBlock::APP_USER_MODEL_FMTID, 11),
+            Some(PropertyValue::Bool(true))
+        );
+        assert!(matches!(properties[2].value, PropertyValue::Guid(_)));
+    }
+
+    #[test]
+    fn sanitize_for_display() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let mut props = lnk.extra_data.property_store_props.unwrap();
+        let property_store = props.property_store.clone();
+
+        props.sanitize_for_display();
+        assert_eq!(props.property_store, property_store);
+
+        let id = "308046B0"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        let position = props
+            .property_store
+            .windows(id.len())
+            .position(|w| w == id.as_slice())
+            .unwrap();
+        props.property_store[position] = 0x1b;
+
+        props.sanitize_for_display();
+        assert_eq!(props.properties().len(), 3);
+        assert_eq!(
+            props.property(PropertyStoreDataBlock::APP_USER_MODEL_FMTID, 5),
+            Some(PropertyValue::String("\\u{1b}08046B0AF4A39CB".to_string()))
+        );
+        assert_eq!(
+            props.property(PropertyStoreDataBlock::APP_USER_MODEL_FMTID, 11),
+            Some(PropertyValue::Bool(true))
+        );
+        assert_eq!(props.block_size as usize, props.property_store.len() + 8);
+    }
+
+    #[test]
+    fn truncated_store() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let mut props = lnk.extra_data.property_store_props.unwrap();
+        props.property_store.truncate(0x40);
+
+        assert!(props.properties().is_empty());
+    }
+}
@@ -1,9 +1,9 @@
-use super::Result;
+use super::{read, Result};
 use crate::error::ExtraDataError;
 use std::io::{Cursor, Read};
 
 /// The ShimDataBlock structure specifies the name of a shim that can be applied when activating a link target.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ShimDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the ShimDataBlock structure. This value MUST be greater than or equal to 0x00000088.
     pub block_size: u32,
@@ -20,17 +20,18 @@ impl ShimDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_min_size(block_size, block_signature, 0x0000_0088)?;
+
         let this = Self {
             block_size,
             block_signature,
             layer_name: {
-                let layer_name_size = block_size as usize - (std::mem::size_of::<u32>() * 2);
+                let layer_name_size =
+                    (block_size as usize).saturating_sub(std::mem::size_of::<u32>() * 2);
                 let mut layer_name = vec![0; layer_name_size];
-                cursor
-                    .read_exact(&mut layer_name)
-                    .map_err(ExtraDataError::Read)?;
+                read(cursor, |c| c.read_exact(&mut layer_name))?;
                 Some(layer_name)
             },
         };
@@ -38,6 +39,11 @@ impl ShimDataBlock {
         Ok(this)
     }
 
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.layer_name.as_deref().unwrap_or_default());
+    }
+
     /// Convert `layer_name` into human readable string
     pub fn to_string(&self) -> Result<String> {
         if let Some(ref layer_name) = self.layer_name {
@@ -1,10 +1,9 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
 use byteorder::{ReadBytesExt, LE};
 use std::io::Cursor;
 
 /// The SpecialFolderDataBlock structure specifies the location of a special folder. This data can be used when a link target is a special folder to keep track of the folder, so that the link target IDList can be translated when the link is loaded.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct SpecialFolderDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the SpecialFolderDataBlock structure. This value MUST be 0x00000010.
     pub block_size: u32,
@@ -24,15 +23,23 @@ impl SpecialFolderDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_0010)?;
+
         let this = Self {
             block_size,
             block_signature,
-            special_folder_id: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            offset: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
+            special_folder_id: read(cursor, |c| c.read_u32::<LE>())?,
+            offset: read(cursor, |c| c.read_u32::<LE>())?,
         };
 
         Ok(this)
     }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.special_folder_id.to_le_bytes());
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+    }
 }
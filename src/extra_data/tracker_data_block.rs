@@ -1,10 +1,10 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
+use crate::Guid;
 use byteorder::{ReadBytesExt, LE};
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 /// The TrackerDataBlock structure specifies data that can be used to resolve a link target if it is not found in its original location when the link is resolved. This data is passed to the Link Tracking service [MS-DLTW] to find the link target.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct TrackerDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the TrackerDataBlock structure. This value MUST be 0x00000060.
     pub block_size: u32,
@@ -19,13 +19,13 @@ pub struct TrackerDataBlock {
     pub version: u32,
 
     /// A NULL–terminated character string, as defined by the system default code page, which specifies the NetBIOS name of the machine where the link target was last known to reside.
-    pub machine_id: u128,
+    pub machine_id: [u8; 16],
 
     /// Two values in GUID packet representation ([MS-DTYP] section 2.3.4.2) that are used to find the link target with the Link Tracking service, as described in [MS-DLTW].
-    pub droid: [u128; 2],
+    pub droid: [Guid; 2],
 
     /// Two values in GUID packet representation that are used to find the link target with the Link Tracking service
-    pub droid_birth: [u128; 2],
+    pub droid_birth: [Guid; 2],
 }
 
 impl TrackerDataBlock {
@@ -33,28 +33,156 @@ impl TrackerDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
+        super::check_size(block_size, block_signature, 0x0000_0060)?;
+
         let this = Self {
             block_size,
             block_signature,
-            length: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            version: cursor.read_u32::<LE>().map_err(ExtraDataError::Read)?,
-            machine_id: cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
+            length: read(cursor, |c| c.read_u32::<LE>())?,
+            version: read(cursor, |c| c.read_u32::<LE>())?,
+            machine_id: {
+                let mut machine_id = [0; 16];
+                read(cursor, |c| c.read_exact(&mut machine_id))?;
+                machine_id
+            },
             droid: {
                 [
-                    cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
-                    cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
+                    Guid::from(read(cursor, |c| c.read_u128::<LE>())?),
+                    Guid::from(read(cursor, |c| c.read_u128::<LE>())?),
                 ]
             },
             droid_birth: {
                 [
-                    cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
-                    cursor.read_u128::<LE>().map_err(ExtraDataError::Read)?,
+                    Guid::from(read(cursor, |c| c.read_u128::<LE>())?),
+                    Guid::from(read(cursor, |c| c.read_u128::<LE>())?),
                 ]
             },
         };
 
         Ok(this)
     }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.length.to_le_bytes());
+        buf.extend_from_slice(&self.version.to_le_bytes());
+        buf.extend_from_slice(&self.machine_id);
+        self.droid
+            .iter()
+            .chain(&self.droid_birth)
+            .for_each(|guid| buf.extend_from_slice(guid.as_bytes()));
+    }
+
+    /// The NetBIOS name of the machine where the link target was last known to reside, up to
+    /// the NULL terminator. Bytes outside of printable ASCII are replaced with U+FFFD, so
+    /// the name is safe to display.
+    pub fn machine_id_string(&self) -> String {
+        self.machine_id
+            .iter()
+            .take_while(|c| **c != 0)
+            .map(|c| {
+                if c.is_ascii() && !c.is_ascii_control() {
+                    *c as char
+                } else {
+                    '\u{fffd}'
+                }
+            })
+            .collect()
+    }
+
+    /// The volume and object identifiers of `droid`, formatted as GUIDs
+    pub fn droid_guids(&self) -> [String; 2] {
+        [self.droid[0].to_string(), self.droid[1].to_string()]
+    }
+
+    /// The volume and object identifiers of `droid_birth`, formatted as GUIDs
+    pub fn droid_birth_guids(&self) -> [String; 2] {
+        [
+            self.droid_birth[0].to_string(),
+            self.droid_birth[1].to_string(),
+        ]
+    }
+
+    /// The MAC address of the machine that created the birth object identifier, if it is a
+    /// version 1 (time-based) UUID
+    pub fn creator_mac(&self) -> Option<[u8; 6]> {
+        let object_id = self.droid_birth[1].as_bytes();
+
+        if object_id[7] >> 4 == 1 {
+            let mut mac_address = [0; 6];
+            mac_address.copy_from_slice(&object_id[10..]);
+            Some(mac_address)
+        } else {
+            None
+        }
+    }
+
+    /// The MAC address of [`TrackerDataBlock::creator_mac`], formatted as `00:1A:2B:3C:4D:5E`
+    pub fn creator_mac_string(&self) -> Option<String> {
+        self.creator_mac().map(|mac| {
+            mac.iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(":")
+        })
+    }
+
+    /// The MAC address stored in the birth object identifier, the same as
+    /// [`TrackerDataBlock::creator_mac`] under the name used by the `ObjectID` fields
+    pub fn object_id_mac_address(&self) -> Option<[u8; 6]> {
+        self.creator_mac()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Lnk;
+    use std::convert::TryFrom;
+    use std::path::Path;
+
+    #[test]
+    fn machine_id_string() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let tracker_props = firefox.extra_data.tracker_props.unwrap();
+        assert_eq!(&tracker_props.machine_id, b"desktop-q73bsl6\0");
+        assert_eq!(tracker_props.machine_id_string(), "desktop-q73bsl6");
+
+        let xp = Lnk::try_from(Path::new("./test_data/outlook_express.lnk")).unwrap();
+        assert_eq!(
+            xp.extra_data.tracker_props.unwrap().machine_id_string(),
+            "xp64"
+        );
+    }
+
+    #[test]
+    fn droid() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let mut tracker_props = firefox.extra_data.tracker_props.unwrap();
+
+        let guids = [
+            "{20A9CF1E-6DE6-45F2-B560-BA1E04E80C45}".to_string(),
+            "{BBB24E88-0F35-11EA-AD58-080027D7FCD0}".to_string(),
+        ];
+        assert_eq!(tracker_props.droid_guids(), guids);
+        assert_eq!(tracker_props.droid_birth_guids(), guids);
+        assert_eq!(
+            tracker_props.creator_mac(),
+            Some([0x08, 0x00, 0x27, 0xd7, 0xfc, 0xd0])
+        );
+        assert_eq!(
+            tracker_props.object_id_mac_address(),
+            tracker_props.creator_mac()
+        );
+
+        assert_eq!(
+            tracker_props.creator_mac_string(),
+            Some("08:00:27:D7:FC:D0".to_string())
+        );
+
+        tracker_props.droid_birth[1] = tracker_props.droid_birth[0];
+        assert_eq!(tracker_props.creator_mac(), None);
+        assert_eq!(tracker_props.creator_mac_string(), None);
+    }
 }
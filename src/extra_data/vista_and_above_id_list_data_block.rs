@@ -1,9 +1,9 @@
-use super::Result;
-use crate::error::ExtraDataError;
+use super::{read, Result};
+use crate::ItemId;
 use std::io::{Cursor, Read};
 
 /// The VistaAndAboveIDListDataBlock structure specifies an alternate IDList that can be used instead of the LinkTargetIDList structure (section 2.2) on platforms that support it.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct VistaAndAboveIDListDataBlock {
     /// A 32-bit, unsigned integer that specifies the size of the VistaAndAboveIDListDataBlock structure. This value MUST be greater than or equal to 0x0000000A.
     pub block_size: u32,
@@ -13,6 +13,9 @@ pub struct VistaAndAboveIDListDataBlock {
 
     /// An IDList structure (section 2.2.1).
     pub id_list: Vec<u8>,
+
+    /// The ItemIDs parsed from `id_list`.
+    pub item_ids: Vec<ItemId>,
 }
 
 impl VistaAndAboveIDListDataBlock {
@@ -20,21 +23,51 @@ impl VistaAndAboveIDListDataBlock {
     pub(crate) fn new(
         block_size: u32,
         block_signature: u32,
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
     ) -> Result<Self> {
-        let this = Self {
+        super::check_min_size(block_size, block_signature, 0x0000_000a)?;
+
+        let id_list_size = (block_size as usize).saturating_sub(std::mem::size_of::<u32>() * 2);
+        let mut id_list = vec![0; id_list_size];
+        read(cursor, |c| c.read_exact(&mut id_list))?;
+
+        Ok(Self {
             block_size,
             block_signature,
-            id_list: {
-                let id_list_size = block_size as usize - (std::mem::size_of::<u32>() * 2);
-                let mut id_list = vec![0; id_list_size];
-                cursor
-                    .read_exact(&mut id_list)
-                    .map_err(ExtraDataError::Read)?;
-                id_list
-            },
-        };
-
-        Ok(this)
+            item_ids: ItemId::parse_list(&id_list),
+            id_list,
+        })
+    }
+
+    /// Serialize the fields following the size and signature into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.id_list);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtraData, Lnk};
+    use std::io::Cursor;
+
+    #[test]
+    fn item_ids() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+
+        // Reuse the LinkTargetIDList of firefox.lnk as the alternate IDList
+        let id_list_size = u16::from_le_bytes([data[76], data[77]]) as usize;
+        let id_list = &data[78..78 + id_list_size];
+
+        let mut block = Vec::new();
+        block.extend_from_slice(&(8 + id_list.len() as u32).to_le_bytes());
+        block.extend_from_slice(&0xa000_000cu32.to_le_bytes());
+        block.extend_from_slice(id_list);
+        block.extend_from_slice(&0u32.to_le_bytes());
+
+        let extra_data = ExtraData::new(&mut Cursor::new(block.as_slice()), &lnk.header).unwrap();
+        let item_ids = extra_data.vista_and_above_idlist_props.unwrap().item_ids;
+
+        assert_eq!(item_ids, lnk.link_target_id_list.item_ids());
     }
 }
@@ -0,0 +1,104 @@
+//! A flattened view of a parsed .lnk file.
+//!
+
+use crate::{Lnk, TargetLocation};
+
+/// The commonly used fields of a [`Lnk`], resolved into plain owned values. Paths are
+/// converted to strings, zero timestamps are `None`, and absent sections leave their fields
+/// `None`. This is the shape most export and scripting consumers want.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlatLnk {
+    /// The local or UNC path of the link target, or its unexpanded environment variable path
+    pub target: Option<String>,
+
+    /// Any arguments to be passed to the target
+    pub arguments: Option<String>,
+
+    /// Working directory to use when launching the target
+    pub working_dir: Option<String>,
+
+    /// Relative path from the .lnk to the target
+    pub relative_path: Option<String>,
+
+    /// Description supplied by .lnk creator
+    pub description: Option<String>,
+
+    /// Icon displayed for the .lnk
+    pub icon_location: Option<String>,
+
+    /// The index of the icon within `icon_location`, negative for a resource ID
+    pub icon_index: i32,
+
+    /// The environment variable target path, before expansion
+    pub environment_target: Option<String>,
+
+    /// The MSI application identifier of an advertised shortcut
+    pub application_id: Option<String>,
+
+    /// The creation time of the target, as a FILETIME
+    pub creation_time: Option<u64>,
+
+    /// The access time of the target, as a FILETIME
+    pub access_time: Option<u64>,
+
+    /// The write time of the target, as a FILETIME
+    pub write_time: Option<u64>,
+
+    /// The size of the target in bytes, modulo 2^32
+    pub file_size: Option<u64>,
+
+    /// The label of the volume the target was on
+    pub volume_label: Option<String>,
+
+    /// The serial number of the volume the target was on
+    pub drive_serial_number: Option<u64>,
+
+    /// The NetBIOS name of the machine the target was last known to reside on
+    pub machine_id: Option<String>,
+
+    /// The MAC address of the machine that created the target, as `00:1A:2B:3C:4D:5E`
+    pub mac_address: Option<String>,
+}
+
+impl From<&Lnk> for FlatLnk {
+    fn from(lnk: &Lnk) -> Self {
+        let path_string = |path: Option<&std::path::PathBuf>| {
+            path.map(|path| path.to_string_lossy().into_owned())
+        };
+
+        let string_data = &lnk.string_data;
+        let extra_data = &lnk.extra_data;
+        let volume_id = lnk.link_info.volume_id.as_ref();
+        let tracker = extra_data.tracker_props.as_ref();
+
+        Self {
+            target: match lnk.target_location() {
+                TargetLocation::Local(path) => Some(path.to_string_lossy().into_owned()),
+                TargetLocation::Network(path) | TargetLocation::Environment(path) => Some(path),
+                TargetLocation::IdListOnly | TargetLocation::Unknown => None,
+            },
+            arguments: string_data.command_line_arguments.clone(),
+            working_dir: path_string(string_data.working_dir.as_ref()),
+            relative_path: path_string(string_data.relative_path.as_ref()),
+            description: string_data.name_string.clone(),
+            icon_location: path_string(string_data.icon_location.as_ref()),
+            icon_index: lnk.icon_index(),
+            environment_target: extra_data
+                .environment_props
+                .as_ref()
+                .and_then(|props| props.target()),
+            application_id: extra_data
+                .darwin_props
+                .as_ref()
+                .and_then(|props| props.application_id()),
+            creation_time: lnk.creation_time_opt(),
+            access_time: lnk.access_time_opt(),
+            write_time: lnk.write_time_opt(),
+            file_size: Some(lnk.target_file_size() as u64),
+            volume_label: volume_id.and_then(|volume_id| volume_id.volume_label.clone()),
+            drive_serial_number: volume_id.map(|volume_id| volume_id.drive_serial_number as u64),
+            machine_id: lnk.created_by_machine(),
+            mac_address: tracker.and_then(|tracker| tracker.creator_mac_string()),
+        }
+    }
+}
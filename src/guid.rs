@@ -0,0 +1,99 @@
+//! Handling of GUIDs stored in the packet representation ([MS-DTYP] section 2.3.4.2).
+//!
+
+use std::fmt;
+
+/// A GUID, holding the 16 bytes of its packet representation as they are stored in the file.
+/// The first three fields are stored little-endian and the last two big-endian, so reading
+/// the bytes as a single integer does not give the GUID's usual text form; use `Display`
+/// for that.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    /// Construct a `Guid` from its packet representation read as a little-endian `u128`
+    pub const fn from_u128(value: u128) -> Self {
+        Self(value.to_le_bytes())
+    }
+
+    /// The packet representation read as a little-endian `u128`
+    pub const fn to_u128(self) -> u128 {
+        u128::from_le_bytes(self.0)
+    }
+
+    /// The bytes of the packet representation
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Construct a `Guid` from the first 16 bytes of `data`
+    pub(crate) fn from_slice(data: &[u8]) -> Option<Self> {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(data.get(..16)?);
+        Some(Self(bytes))
+    }
+}
+
+impl From<u128> for Guid {
+    fn from(value: u128) -> Self {
+        Self::from_u128(value)
+    }
+}
+
+impl From<Guid> for u128 {
+    fn from(guid: Guid) -> Self {
+        guid.to_u128()
+    }
+}
+
+impl From<[u8; 16]> for Guid {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for Guid {
+    /// Format the GUID as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.0;
+
+        write!(
+            f,
+            "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            u16::from_le_bytes([b[4], b[5]]),
+            u16::from_le_bytes([b[6], b[7]]),
+            b[8],
+            b[9],
+            b[10],
+            b[11],
+            b[12],
+            b[13],
+            b[14],
+            b[15]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Guid;
+
+    #[test]
+    fn shell_link_clsid() {
+        assert_eq!(
+            crate::ShellLinkHeader::LINK_CLSID.to_string(),
+            "{00021401-0000-0000-C000-000000000046}"
+        );
+    }
+
+    #[test]
+    fn u128_round_trip() {
+        let value = 0x4600_0000_0000_00c0_0000_0000_0002_1401;
+        let guid = Guid::from(value);
+
+        assert_eq!(guid.as_bytes()[..4], [0x01, 0x14, 0x02, 0x00]);
+        assert_eq!(guid.to_u128(), value);
+        assert_eq!(u128::from(guid), value);
+    }
+}
@@ -3,12 +3,13 @@
 //! type.
 //!
 
+use crate::Guid;
 use bitflags::bitflags;
 use byteorder::{ReadBytesExt, LE};
 use std::io::Cursor;
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// The ShellLinkHeader structure contains identification information, timestamps, and flags that specify
 /// the presence of optional structures, including LinkTargetIDList (section 2.2), LinkInfo (section 2.3),
 /// and StringData (section 2.4).
@@ -17,7 +18,7 @@ pub struct ShellLinkHeader {
     pub header_size: u32,
 
     /// A class identifier (CLSID). This value MUST be 00021401-0000-0000-C000-000000000046.
-    pub link_clsid: u128,
+    pub link_clsid: Guid,
 
     /// A LinkFlags structure (section 2.1.1) that specifies information about the shell
     /// link and the presence of optional portions of the structure.
@@ -54,6 +55,11 @@ pub struct ShellLinkHeader {
     /// ShowCommand (4 bytes): A 32-bit unsigned integer that specifies the expected
     pub show_command: ShowCommand,
 
+    /// The `ShowCommand` as stored, including values outside the defined commands such as
+    /// `SW_SHOWDEFAULT` that `show_command` drops. This is the value that is decoded by
+    /// `Lnk::show_window` and written back when serializing.
+    pub show_command_raw: u32,
+
     /// HotKey (2 bytes): A HotKeyFlags structure (section 2.1.3) that specifies the keystrokes used to
     /// launch the application referenced by the shortcut key. This value is assigned to the application
     /// after it is launched, so that pressing the key activates that application.
@@ -68,25 +74,55 @@ pub struct ShellLinkHeader {
     /// Reserved3 (4 bytes): A value that MUST be zero.
     pub reserved3: u32,
 
-    /// Human readable created on date
+    /// Human readable created on date, `None` when the `CreationTime` is not set
     #[cfg(feature = "chrono")]
     pub created_on: Option<chrono::DateTime<chrono::Utc>>,
 
-    /// Human readable modified on date
+    /// Human readable modified on date, `None` when the `WriteTime` is not set
     #[cfg(feature = "chrono")]
     pub modified_on: Option<chrono::DateTime<chrono::Utc>>,
 
-    /// Human readable accessed on date
+    /// Human readable accessed on date, `None` when the `AccessTime` is not set
     #[cfg(feature = "chrono")]
     pub accessed_on: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-impl std::convert::TryFrom<&mut Cursor<Vec<u8>>> for ShellLinkHeader {
+/// A header for an empty shortcut: the mandatory size and CLSID are set, no flags are set,
+/// the window is shown normally and every other field is zero.
+impl Default for ShellLinkHeader {
+    fn default() -> Self {
+        Self {
+            header_size: Self::HEADER_SIZE,
+            link_clsid: Self::LINK_CLSID,
+            link_flags: LinkFlags::empty(),
+            file_attributes: FileAttributeFlags::empty(),
+            creation_time: 0,
+            access_time: 0,
+            write_time: 0,
+            file_size: 0,
+            icon_index: 0,
+            show_command: ShowCommand::SW_SHOWNORMAL,
+            show_command_raw: ShowCommand::SW_SHOWNORMAL.bits(),
+            hot_key: HotKeyFlags::default(),
+            reserved1: 0,
+            reserved2: 0,
+            reserved3: 0,
+            #[cfg(feature = "chrono")]
+            created_on: None,
+            #[cfg(feature = "chrono")]
+            modified_on: None,
+            #[cfg(feature = "chrono")]
+            accessed_on: None,
+        }
+    }
+}
+
+impl std::convert::TryFrom<&mut Cursor<&[u8]>> for ShellLinkHeader {
     type Error = crate::error::HeaderError;
-    fn try_from(cursor: &mut Cursor<Vec<u8>>) -> Result<Self, Self::Error> {
+    fn try_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Self::Error> {
         let mut header = Self {
             header_size: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
-            link_clsid: cursor.read_u128::<LE>().map_err(Self::Error::Read)?,
+            link_clsid: Guid::from(cursor.read_u128::<LE>().map_err(Self::Error::Read)?),
             link_flags: LinkFlags::from_bits_truncate(
                 cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
             ),
@@ -98,9 +134,8 @@ impl std::convert::TryFrom<&mut Cursor<Vec<u8>>> for ShellLinkHeader {
             write_time: cursor.read_u64::<LE>().map_err(Self::Error::Read)?,
             file_size: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
             icon_index: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
-            show_command: ShowCommand::from_bits_truncate(
-                cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
-            ),
+            show_command: ShowCommand::empty(),
+            show_command_raw: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
             hot_key: HotKeyFlags::from(cursor.read_u16::<LE>().map_err(Self::Error::Read)?),
             reserved1: cursor.read_u16::<LE>().map_err(Self::Error::Read)?,
             reserved2: cursor.read_u32::<LE>().map_err(Self::Error::Read)?,
@@ -113,23 +148,55 @@ impl std::convert::TryFrom<&mut Cursor<Vec<u8>>> for ShellLinkHeader {
             accessed_on: None,
         };
 
+        header.show_command = ShowCommand::from_bits_truncate(header.show_command_raw);
+
         #[cfg(feature = "chrono")]
         {
-            use chrono::{TimeZone, Utc};
+            // A zero FILETIME means the time is not set, rather than the 1601 epoch
+            let datetime = |time: u64| {
+                Some(time)
+                    .filter(|time| *time != 0)
+                    .map(|time| FileTime::from(time).to_datetime())
+            };
+            header.created_on = datetime(header.creation_time);
+            header.modified_on = datetime(header.write_time);
+            header.accessed_on = datetime(header.access_time);
+        }
 
-            let start = Utc.ymd(1601, 1, 1).and_hms(0, 0, 0);
+        Ok(header)
+    }
+}
 
-            header.created_on =
-                Some(start + chrono::Duration::milliseconds(header.creation_time as i64 / 10000));
+impl ShellLinkHeader {
+    /// The size of the structure, the only valid value of `header_size`
+    pub const HEADER_SIZE: u32 = 0x0000_004c;
 
-            header.modified_on =
-                Some(start + chrono::Duration::milliseconds(header.write_time as i64 / 10000));
+    /// The class identifier 00021401-0000-0000-C000-000000000046, the only valid value of
+    /// `link_clsid`
+    pub const LINK_CLSID: Guid = Guid::from_u128(0x4600_0000_0000_00c0_0000_0000_0002_1401);
 
-            header.accessed_on =
-                Some(start + chrono::Duration::milliseconds(header.access_time as i64 / 10000));
-        }
+    /// Whether the `reserved1`, `reserved2` and `reserved3` fields are all zero, as required.
+    /// Non-zero values suggest a crafted or corrupted file.
+    pub fn reserved_bits_clean(&self) -> bool {
+        self.reserved1 == 0 && self.reserved2 == 0 && self.reserved3 == 0
+    }
 
-        Ok(header)
+    /// Serialize the header into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.header_size.to_le_bytes());
+        buf.extend_from_slice(self.link_clsid.as_bytes());
+        buf.extend_from_slice(&self.link_flags.bits().to_le_bytes());
+        buf.extend_from_slice(&self.file_attributes.bits().to_le_bytes());
+        buf.extend_from_slice(&self.creation_time.to_le_bytes());
+        buf.extend_from_slice(&self.access_time.to_le_bytes());
+        buf.extend_from_slice(&self.write_time.to_le_bytes());
+        buf.extend_from_slice(&self.file_size.to_le_bytes());
+        buf.extend_from_slice(&self.icon_index.to_le_bytes());
+        buf.extend_from_slice(&self.show_command_raw.to_le_bytes());
+        buf.extend_from_slice(&u16::from(self.hot_key).to_le_bytes());
+        buf.extend_from_slice(&self.reserved1.to_le_bytes());
+        buf.extend_from_slice(&self.reserved2.to_le_bytes());
+        buf.extend_from_slice(&self.reserved3.to_le_bytes());
     }
 }
 
@@ -255,6 +322,70 @@ bitflags! {
     }
 }
 
+/// The name of each `LinkFlags` flag, in bit order
+const LINK_FLAG_NAMES: [(LinkFlags, &str); 27] = [
+    (
+        LinkFlags::HAS_LINK_TARGET_ID_LIST,
+        "HAS_LINK_TARGET_ID_LIST",
+    ),
+    (LinkFlags::HAS_LINK_INFO, "HAS_LINK_INFO"),
+    (LinkFlags::HAS_NAME, "HAS_NAME"),
+    (LinkFlags::HAS_RELATIVE_PATH, "HAS_RELATIVE_PATH"),
+    (LinkFlags::HAS_WORKING_DIR, "HAS_WORKING_DIR"),
+    (LinkFlags::HAS_ARGUMENTS, "HAS_ARGUMENTS"),
+    (LinkFlags::HAS_ICON_LOCATION, "HAS_ICON_LOCATION"),
+    (LinkFlags::IS_UNICODE, "IS_UNICODE"),
+    (LinkFlags::FORCE_NO_LINK_INFO, "FORCE_NO_LINK_INFO"),
+    (LinkFlags::HAS_EXP_STRING, "HAS_EXP_STRING"),
+    (
+        LinkFlags::RUN_IN_SEPARATE_PROCESS,
+        "RUN_IN_SEPARATE_PROCESS",
+    ),
+    (LinkFlags::UNUSED_1, "UNUSED_1"),
+    (LinkFlags::HAS_DARWIN_ID, "HAS_DARWIN_ID"),
+    (LinkFlags::RUN_AS_USER, "RUN_AS_USER"),
+    (LinkFlags::HAS_EXP_ICON, "HAS_EXP_ICON"),
+    (LinkFlags::NO_PID_I_ALIAS, "NO_PID_I_ALIAS"),
+    (LinkFlags::UNUSED_2, "UNUSED_2"),
+    (LinkFlags::RUN_WITH_SHIM_LAYER, "RUN_WITH_SHIM_LAYER"),
+    (LinkFlags::FORCE_NO_LINK_TRACK, "FORCE_NO_LINK_TRACK"),
+    (LinkFlags::ENABLE_TARGET_METADATA, "ENABLE_TARGET_METADATA"),
+    (
+        LinkFlags::DISABLE_LINK_PATH_TRACKING,
+        "DISABLE_LINK_PATH_TRACKING",
+    ),
+    (
+        LinkFlags::DISABLE_KNOWN_FOLDER_TRACKING,
+        "DISABLE_KNOWN_FOLDER_TRACKING",
+    ),
+    (
+        LinkFlags::DISABLE_KNOWN_FOLDER_ALIAS,
+        "DISABLE_KNOWN_FOLDER_ALIAS",
+    ),
+    (LinkFlags::ALLOW_LINK_TO_LINK, "ALLOW_LINK_TO_LINK"),
+    (LinkFlags::UNALIAS_ON_SAVE, "UNALIAS_ON_SAVE"),
+    (
+        LinkFlags::PREFER_ENVIRONMENT_PATH,
+        "PREFER_ENVIRONMENT_PATH",
+    ),
+    (
+        LinkFlags::KEEP_LOCAL_ID_LIST_FOR_UNC_TARGET,
+        "KEEP_LOCAL_ID_LIST_FOR_UNC_TARGET",
+    ),
+];
+
+impl LinkFlags {
+    /// The names of the flags that are set, in bit order
+    pub fn iter_names(&self) -> impl Iterator<Item = &'static str> {
+        let flags = *self;
+
+        LINK_FLAG_NAMES
+            .iter()
+            .filter(move |(flag, _)| flags.contains(*flag))
+            .map(|(_, name)| *name)
+    }
+}
+
 bitflags! {
     /// The FileAttributesFlags structure defines bits that specify the file attributes of the link target, if the
     /// target is a file system item. File attributes can be used if the link target is not available, or if accessing
@@ -262,49 +393,49 @@ bitflags! {
     /// value.
     pub struct FileAttributeFlags: u32 {
         /// The file or directory is read-only. For a file, if this bit is set, applications can read the file but cannot write to it or delete it. For a directory, if this bit is set, applications cannot delete the directory.
-        const FILE_ATTRIBUTE_READONLY               = 0b1000_0000_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_READONLY               = 0b0000_0000_0000_0000_0000_0000_0000_0001;
 
         /// The file or directory is hidden. If this bit is set, the file or folder is not included in an ordinary directory listing.
-        const FILE_ATTRIBUTE_HIDDEN                 = 0b0100_0000_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_HIDDEN                 = 0b0000_0000_0000_0000_0000_0000_0000_0010;
 
         /// The file or directory is part of the operating system or is used exclusively by the operating system.
-        const FILE_ATTRIBUTE_SYSTEM                 = 0b0010_0000_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_SYSTEM                 = 0b0000_0000_0000_0000_0000_0000_0000_0100;
 
         /// A bit that MUST be zero.
-        const RESERVED_1                            = 0b0001_0000_0000_0000_0000_0000_0000_0000;
+        const RESERVED_1                            = 0b0000_0000_0000_0000_0000_0000_0000_1000;
 
         /// The link target is a directory instead of a file.
-        const FILE_ATTRIBUTE_DIRECTORY              = 0b0000_1000_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_DIRECTORY              = 0b0000_0000_0000_0000_0000_0000_0001_0000;
 
         /// The file or directory is an archive file. Applications use this flag to mark files for backup or removal.
-        const FILE_ATTRIBUTE_ARCHIVE                = 0b0000_0100_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_ARCHIVE                = 0b0000_0000_0000_0000_0000_0000_0010_0000;
 
         /// A bit that MUST be zero.
-        const RESERVED_2                            = 0b0000_0010_0000_0000_0000_0000_0000_0000;
+        const RESERVED_2                            = 0b0000_0000_0000_0000_0000_0000_0100_0000;
 
         /// The file or directory has no other flags set. If this bit is 1, all other bits in this structure MUST be clear.
-        const FILE_ATTRIBUTE_NORMAL                 = 0b0000_0001_0000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_NORMAL                 = 0b0000_0000_0000_0000_0000_0000_1000_0000;
 
         /// The file is being used for temporary storage.
-        const FILE_ATTRIBUTE_TEMPORARY              = 0b0000_0000_1000_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_TEMPORARY              = 0b0000_0000_0000_0000_0000_0001_0000_0000;
 
         /// The file is a sparse file.
-        const FILE_ATTRIBUTE_SPARCE_FILE            = 0b0000_0000_0100_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_SPARCE_FILE            = 0b0000_0000_0000_0000_0000_0010_0000_0000;
 
         /// The file or directory has an associated reparse point.
-        const FILE_ATTRIBUTE_REPARSE_POINT          = 0b0000_0000_0010_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_REPARSE_POINT          = 0b0000_0000_0000_0000_0000_0100_0000_0000;
 
         /// The file or directory is compressed. For a file, this means that all data in the file is compressed. For a directory, this means that compression is the default for newly created files and subdirectories.
-        const FILE_ATTRIBUTE_COMPRESSED             = 0b0000_0000_0001_0000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_COMPRESSED             = 0b0000_0000_0000_0000_0000_1000_0000_0000;
 
         /// The data of the file is not immediately available.
-        const FILE_ATTRIBUTE_OFFLINE                = 0b0000_0000_0000_1000_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_OFFLINE                = 0b0000_0000_0000_0000_0001_0000_0000_0000;
 
         /// The contents of the file need to be indexed.
-        const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED    = 0b0000_0000_0000_0100_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED    = 0b0000_0000_0000_0000_0010_0000_0000_0000;
 
         /// The file or directory is encrypted. For a file, this means that all data in the file is encrypted. For a directory, this means that encryption is the default for newly created files and subdirectories.
-        const FILE_ATTRIBUTE_ENCRYPTED              = 0b0000_0000_0000_0010_0000_0000_0000_0000;
+        const FILE_ATTRIBUTE_ENCRYPTED              = 0b0000_0000_0000_0000_0100_0000_0000_0000;
 
     }
 }
@@ -325,8 +456,35 @@ bitflags! {
     }
 }
 
+/// The window state of an application launched by the link, decoded from its raw `ShowCommand`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShowWindow {
+    /// `SW_SHOWNORMAL`, the window is shown in a normal fashion
+    Normal,
+
+    /// `SW_SHOWMAXIMIZED`, the window is shown maximized
+    Maximized,
+
+    /// `SW_SHOWMINNOACTIVE`, the window is minimized and not given the keyboard focus
+    MinimizedNoActivate,
+
+    /// Any other value. The specification requires it to be treated as `SW_SHOWNORMAL`.
+    Unknown(u32),
+}
+
+impl From<u32> for ShowWindow {
+    fn from(show_command: u32) -> Self {
+        match show_command {
+            0x0000_0001 => Self::Normal,
+            0x0000_0003 => Self::Maximized,
+            0x0000_0007 => Self::MinimizedNoActivate,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 /// The HotKeyFlags structure specifies input generated by a combination of keyboard keys being
 /// pressed.
 pub struct HotKeyFlags {
@@ -349,7 +507,13 @@ impl From<u16> for HotKeyFlags {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl From<HotKeyFlags> for u16 {
+    fn from(hot_key: HotKeyFlags) -> Self {
+        u16::from_le_bytes([hot_key.low_byte, hot_key.high_byte])
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// Contains a 64-bit value representing the number of 100-nanosecond intervals since January 1, 1601 (UTC).
 pub struct FileTime {
     /// The low-order part of the file time.
@@ -358,3 +522,62 @@ pub struct FileTime {
     /// The high-order part of the file time.
     pub high: u32,
 }
+
+impl FileTime {
+    /// The file time as a single 64-bit value
+    pub fn to_u64(self) -> u64 {
+        (self.high as u64) << 32 | self.low as u64
+    }
+
+    /// The file time as a `DateTime`, keeping its full 100-nanosecond resolution
+    #[cfg(feature = "chrono")]
+    pub fn to_datetime(self) -> chrono::DateTime<chrono::Utc> {
+        let time = self.to_u64();
+
+        Self::epoch()
+            + chrono::Duration::seconds((time / Self::TICKS_PER_SECOND) as i64)
+            + chrono::Duration::nanoseconds((time % Self::TICKS_PER_SECOND * 100) as i64)
+    }
+
+    /// Construct a `FileTime` from a `DateTime`, truncated to 100-nanosecond resolution.
+    /// Returns `None` for a time before 1601 or beyond the range of a `FileTime`.
+    #[cfg(feature = "chrono")]
+    pub fn from_datetime(datetime: &chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        let seconds: u64 =
+            std::convert::TryFrom::try_from(datetime.timestamp() - Self::epoch().timestamp())
+                .ok()?;
+        let ticks = u64::from(datetime.timestamp_subsec_nanos() / 100);
+
+        seconds
+            .checked_mul(Self::TICKS_PER_SECOND)?
+            .checked_add(ticks)
+            .map(Self::from)
+    }
+
+    /// The number of 100-nanosecond intervals in a second
+    #[cfg(feature = "chrono")]
+    const TICKS_PER_SECOND: u64 = 10_000_000;
+
+    /// January 1, 1601 (UTC), the start of the `FileTime` epoch
+    #[cfg(feature = "chrono")]
+    fn epoch() -> chrono::DateTime<chrono::Utc> {
+        use chrono::{TimeZone, Utc};
+
+        Utc.with_ymd_and_hms(1601, 1, 1, 0, 0, 0).unwrap()
+    }
+}
+
+impl From<u64> for FileTime {
+    fn from(time: u64) -> Self {
+        Self {
+            low: time as u32,
+            high: (time >> 32) as u32,
+        }
+    }
+}
+
+impl From<FileTime> for u64 {
+    fn from(time: FileTime) -> Self {
+        time.to_u64()
+    }
+}
@@ -28,28 +28,145 @@
 
 #![warn(missing_docs)]
 
+pub mod code_page;
+mod command_line;
+mod display;
 pub mod error;
 pub mod extra_data;
+mod flat;
+mod guid;
 pub mod header;
 pub mod link_info;
 pub mod link_target_id_list;
+pub mod options;
+mod parser;
+mod scan;
 pub mod string_data;
+mod windows_path;
 
+pub use display::escape_for_display;
 pub use extra_data::*;
+pub use flat::FlatLnk;
+pub use guid::Guid;
 pub use header::*;
 pub use link_info::*;
 pub use link_target_id_list::*;
+pub use options::*;
+pub use parser::{LnkParser, Section};
+pub use scan::scan_dir;
 use std::{
+    collections::HashMap,
     convert::TryFrom,
     path::{Path, PathBuf},
 };
 pub use string_data::*;
+pub use windows_path::WindowsPath;
 
 /// Result type wrapping around `parselnk::error::Error`
 pub type Result<T> = std::result::Result<T, error::Error>;
 
-/// Represents a windows .lnk file
-#[derive(Clone, Debug)]
+/// The version of the `parselnk` crate
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Parse shell links stored back to back in `data`, as found in some data files. Each
+/// shortcut is parsed from the byte following the last one consumed by the previous, until
+/// `data` is exhausted. Iteration stops after the first error, as the position of the next
+/// shortcut is then unknown.
+///
+/// # Example
+///
+/// ```no_run
+/// let data = std::fs::read("shortcuts.bin").unwrap();
+///
+/// for lnk in parselnk::parse_many(&data) {
+///     println!("{:?}", lnk.map(|lnk| lnk.target_path()));
+/// }
+/// ```
+///
+pub fn parse_many(data: &[u8]) -> impl Iterator<Item = Result<Lnk>> + '_ {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        if offset >= data.len() {
+            return None;
+        }
+
+        match Lnk::parse_prefix(data, offset, &LnkOptions::default()) {
+            Ok((lnk, end)) => {
+                offset = end;
+                Some(Ok(lnk))
+            }
+            Err(e) => {
+                offset = data.len();
+                Some(Err(e))
+            }
+        }
+    })
+}
+
+/// The kind of writer a `Lnk` most likely came from, see [`Lnk::flavor`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LnkFormatFlavor {
+    /// Written by Windows XP or earlier, without any of the structures added in Vista
+    Legacy,
+
+    /// Written by Windows Vista or later
+    VistaPlus,
+
+    /// Written by a tool other than the Windows shell, deviating from what the shell writes
+    ThirdPartyGenerator,
+}
+
+/// The source of a path returned by [`Lnk::all_paths`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathKind {
+    /// The `RELATIVE_PATH` of the `StringData`
+    RelativePath,
+
+    /// The `WORKING_DIR` of the `StringData`
+    WorkingDir,
+
+    /// The `ICON_LOCATION` of the `StringData`
+    IconLocation,
+
+    /// The `LocalBasePath` of the `LinkInfo`
+    LocalBasePath,
+
+    /// The target of the `EnvironmentVariableDataBlock`
+    EnvironmentTarget,
+
+    /// The target of the `IconEnvironmentDataBlock`
+    IconEnvironmentTarget,
+
+    /// The application identifier of the `DarwinDataBlock`
+    DarwinId,
+}
+
+/// Where the target of a `Lnk` is located, see [`Lnk::target_location`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetLocation {
+    /// The target is on a local volume, at the given path
+    Local(PathBuf),
+
+    /// The target is on a network share, at the given UNC path
+    Network(String),
+
+    /// The target is only given by the `EnvironmentVariableDataBlock`, as the path with its
+    /// `%VAR%` tokens left in place
+    Environment(String),
+
+    /// The target is only described by the `LinkTargetIDList`, as for control panel items
+    IdListOnly,
+
+    /// The location of the target could not be determined
+    Unknown,
+}
+
+/// Represents a windows .lnk file. The `Default` is an empty shortcut with a valid header
+/// and no optional structures.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Lnk {
     /// Path to the `.lnk` file
     path: Option<PathBuf>,
@@ -68,6 +185,9 @@ pub struct Lnk {
 
     /// ExtraData refers to a set of structures that convey additional information about a link target. These optional structures can be present in an extra data section that is appended to the basic Shell Link Binary File Format.
     pub extra_data: ExtraData,
+
+    /// Recoverable problems encountered while parsing
+    warnings: Vec<error::ParseWarning>,
 }
 
 impl Lnk {
@@ -84,153 +204,2668 @@ impl Lnk {
     /// ```
     ///
     pub fn new<S: std::io::Read>(reader: &mut S) -> Result<Lnk> {
+        Self::from_reader_with_options(reader, &LnkOptions::default())
+    }
+
+    /// Creates a new `Lnk` from a `Read` source, rejecting any deviation from the
+    /// specification instead of recovering from it. The data is parsed with the `strict`
+    /// option, and every issue found by [`Lnk::validate`] or recovered from while parsing,
+    /// such as an unknown extra data block or trailing data, is returned as an
+    /// [`Error::Nonconforming`](error::Error::Nonconforming). This suits pipelines that
+    /// quarantine non-conforming files rather than parsing them on a best-effort basis.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    /// use std::fs::File;
+    ///
+    /// let mut file = File::open(r"c:\users\me\desktop\firefox.lnk").unwrap();
+    /// let lnk = Lnk::new_strict(&mut file);
+    /// ```
+    ///
+    pub fn new_strict<S: std::io::Read>(reader: &mut S) -> Result<Lnk> {
+        let options = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let lnk = Self::from_reader_with_options(reader, &options)?;
+
+        let mut issues = lnk.validate().err().unwrap_or_default();
+        issues.extend(
+            lnk.warnings
+                .iter()
+                .filter(|warning| **warning != error::ParseWarning::ReservedFields)
+                .cloned()
+                .map(error::ValidationIssue::Recovered),
+        );
+
+        if issues.is_empty() {
+            Ok(lnk)
+        } else {
+            Err(error::Error::Nonconforming(issues))
+        }
+    }
+
+    /// Creates a new `Lnk` from the file at `path`, which can be given as a `&str`,
+    /// `String`, `Path` or `PathBuf`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    ///
+    /// let lnk = Lnk::from_file(r"c:\users\me\desktop\firefox.lnk");
+    /// ```
+    ///
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Lnk> {
+        Self::try_from(path.as_ref())
+    }
+
+    /// Creates a new `Lnk` from a `Read` source, parsed according to `options`. At most
+    /// `max_size` bytes of the source are buffered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::{Lnk, LnkOptions};
+    /// use std::fs::File;
+    ///
+    /// let options = LnkOptions {
+    ///     ansi_code_page: Some(1251),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut file = File::open(r"c:\users\me\desktop\firefox.lnk").unwrap();
+    /// let lnk = Lnk::from_reader_with_options(&mut file, &options);
+    /// ```
+    ///
+    pub fn from_reader_with_options<S: std::io::Read>(
+        reader: &mut S,
+        options: &LnkOptions,
+    ) -> Result<Lnk> {
+        use std::io::Read;
+
         let mut data_buf = Vec::new();
         reader
+            .take(options.max_size.saturating_add(1))
             .read_to_end(&mut data_buf)
             .map_err(error::HeaderError::Read)?;
 
-        let mut cursor = std::io::Cursor::new(data_buf);
-
-        let header = ShellLinkHeader::try_from(&mut cursor)?;
-        let link_target_id_list = LinkTargetIdList::new(&mut cursor, &header)?;
-        let link_info = LinkInfo::new(&mut cursor, &header)?;
-        let string_data = StringData::new(&mut cursor, &header)?;
-        let extra_data = ExtraData::new(&mut cursor, &header)?;
-
-        Ok(Lnk {
-            path: None,
-            header,
-            string_data,
-            link_target_id_list,
-            link_info,
-            extra_data,
-        })
+        if data_buf.len() as u64 > options.max_size {
+            return Err(error::HeaderError::TooLarge(options.max_size).into());
+        }
+
+        Self::parse_with_options(&data_buf, options)
     }
 
-    /// The command line arguments supplied via the `Lnk`
-    pub fn arguments(&self) -> Option<String> {
-        self.string_data.command_line_arguments.clone()
+    /// Creates a new `Lnk` from the data in `data`, reading it in place rather than copying
+    /// it into a new buffer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    ///
+    /// let data = std::fs::read(r"c:\users\me\desktop\firefox.lnk").unwrap();
+    /// let lnk = Lnk::parse(&data);
+    /// ```
+    ///
+    /// Because nothing is copied, this also works on a memory-mapped file, for example with
+    /// the `memmap2` crate:
+    ///
+    /// ```ignore
+    /// use parselnk::Lnk;
+    ///
+    /// let file = std::fs::File::open(r"c:\users\me\desktop\firefox.lnk").unwrap();
+    /// let map = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+    /// let lnk = Lnk::parse(&map);
+    /// ```
+    ///
+    pub fn parse(data: &[u8]) -> Result<Lnk> {
+        Self::parse_with_options(data, &LnkOptions::default())
     }
 
-    /// The relative path to the resource of the `Lnk``
-    pub fn relative_path(&self) -> Option<PathBuf> {
-        self.string_data.relative_path.clone()
+    /// Creates a new `Lnk` from a shell link embedded in `data` at `offset`, as when carving
+    /// shortcuts out of OLE streams, archives or memory dumps. The `HeaderSize` and
+    /// `LinkCLSID` at `offset` are checked first, so that a wrong offset is reported as
+    /// [`HeaderError::InvalidSignature`](error::HeaderError::InvalidSignature) with the
+    /// offset into `data`. The offsets of later errors are also counted from the start of
+    /// `data`.
+    pub fn from_bytes_at(data: &[u8], offset: usize) -> Result<Lnk> {
+        Self::from_bytes_at_with_options(data, offset, &LnkOptions::default())
     }
 
-    /// The working directory of the `Lnk`
-    pub fn working_dir(&self) -> Option<PathBuf> {
-        self.string_data.working_dir.clone()
+    /// Creates a new `Lnk` from a shell link embedded in `data` at `offset`, parsed according
+    /// to `options`, see [`Lnk::from_bytes_at`]
+    pub fn from_bytes_at_with_options(
+        data: &[u8],
+        offset: usize,
+        options: &LnkOptions,
+    ) -> Result<Lnk> {
+        let mut signature = ShellLinkHeader::HEADER_SIZE.to_le_bytes().to_vec();
+        signature.extend_from_slice(ShellLinkHeader::LINK_CLSID.as_bytes());
+
+        match data.get(offset..) {
+            Some(link) if link.starts_with(&signature) => Self::parse_at(data, offset, options),
+            _ => Err(error::HeaderError::InvalidSignature(offset).into()),
+        }
     }
 
-    /// The description of the `Lnk`
-    pub fn description(&self) -> Option<String> {
-        self.string_data.name_string.clone()
+    /// Creates a new `Lnk` from the data in `data`, parsed according to `options`.
+    pub fn parse_with_options(data: &[u8], options: &LnkOptions) -> Result<Lnk> {
+        Self::parse_at(data, 0, options)
     }
 
-    /// The creation `FileTime` as a u64
-    pub fn creation_time(&self) -> u64 {
-        self.header.creation_time
+    /// Parse the shell link at `offset` in `data`, which extends to the end of `data`
+    fn parse_at(data: &[u8], offset: usize, options: &LnkOptions) -> Result<Lnk> {
+        let (mut lnk, end) = Self::parse_prefix(data, offset, options)?;
+
+        let trailing = data.len() - end;
+        if trailing > 0 {
+            lnk.warnings
+                .push(error::ParseWarning::TrailingData(trailing));
+        }
+
+        Ok(lnk)
     }
 
-    /// The access `FileTime` as a u64
-    pub fn access_time(&self) -> u64 {
-        self.header.access_time
+    /// Parse the shell link at `offset` in `data`, returning it with the offset just past
+    /// its last byte
+    fn parse_prefix(data: &[u8], offset: usize, options: &LnkOptions) -> Result<(Lnk, usize)> {
+        let mut parser = LnkParser::at(data, offset, options);
+        let mut lnk = Lnk::default();
+
+        while let Some(section) = parser.next_section()? {
+            match section {
+                Section::Header(header) => lnk.header = header,
+                Section::LinkTargetIdList(link_target_id_list) => {
+                    lnk.link_target_id_list = link_target_id_list
+                }
+                Section::LinkInfo(link_info) => lnk.link_info = link_info,
+                Section::StringData(string_data) => lnk.string_data = string_data,
+                Section::ExtraData(extra_data) => lnk.extra_data = extra_data,
+            }
+        }
+        let end = parser.position();
+
+        // Without an explicit code page, the code page of non-unicode strings is only known
+        // once the `ConsoleFEDataBlock` at the end of the file has been parsed. The LinkInfo
+        // always holds ANSI paths, whether or not its unicode variants are present.
+        if let Some(ref console_fe_props) = lnk.extra_data.console_fe_props {
+            if options.ansi_code_page.is_none() {
+                lnk.link_info = parser.reparse_link_info(console_fe_props.code_page)?;
+
+                if !lnk.header.link_flags.contains(LinkFlags::IS_UNICODE) {
+                    lnk.string_data = parser.reparse_string_data(console_fe_props.code_page)?;
+                }
+            }
+        }
+
+        lnk.warnings = parser.into_warnings();
+        if let Some(ref console_fe_props) = lnk.extra_data.console_fe_props {
+            if options.ansi_code_page.is_none()
+                && !code_page::is_supported(console_fe_props.code_page)
+            {
+                lnk.warnings.push(error::ParseWarning::UnsupportedCodePage(
+                    console_fe_props.code_page,
+                ));
+            }
+        }
+
+        Ok((lnk, end))
     }
 
-    /// The write `FileTime` as a u64
-    pub fn write_time(&self) -> u64 {
-        self.header.write_time
+    /// Serialize the shortcut into the Shell Link Binary File Format. The presence flags of
+    /// the header are updated to match the sections and strings that are set, so fields can be
+    /// changed before writing. Extra data blocks are written back as they were read, or
+    /// serialized from their parsed fields after [`Lnk::drop_raw`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    /// use std::convert::TryFrom;
+    /// use std::path::Path;
+    ///
+    /// let mut lnk = Lnk::try_from(Path::new(r"c:\users\me\desktop\firefox.lnk")).unwrap();
+    /// lnk.string_data.command_line_arguments = Some("--private-window".to_string());
+    ///
+    /// let data = lnk.to_bytes();
+    /// ```
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = self.header;
+        let string_data = &self.string_data;
+
+        for (flag, present) in [
+            (
+                LinkFlags::HAS_LINK_TARGET_ID_LIST,
+                !self.link_target_id_list.is_empty(),
+            ),
+            (
+                LinkFlags::HAS_LINK_INFO,
+                self.link_info.link_info_flags.is_some(),
+            ),
+            (LinkFlags::HAS_NAME, string_data.name_string.is_some()),
+            (
+                LinkFlags::HAS_RELATIVE_PATH,
+                string_data.relative_path.is_some(),
+            ),
+            (
+                LinkFlags::HAS_WORKING_DIR,
+                string_data.working_dir.is_some(),
+            ),
+            (
+                LinkFlags::HAS_ARGUMENTS,
+                string_data.command_line_arguments.is_some(),
+            ),
+            (
+                LinkFlags::HAS_ICON_LOCATION,
+                string_data.icon_location.is_some(),
+            ),
+        ] {
+            header.link_flags.set(flag, present);
+        }
+
+        let mut buf = Vec::new();
+        header.write(&mut buf);
+        if header
+            .link_flags
+            .contains(LinkFlags::HAS_LINK_TARGET_ID_LIST)
+        {
+            self.link_target_id_list.write(&mut buf);
+        }
+        let code_page = self
+            .extra_data
+            .console_fe_props
+            .as_ref()
+            .map(|console_fe_props| console_fe_props.code_page);
+        self.link_info.write(&mut buf, code_page);
+        string_data.write(
+            &mut buf,
+            header.link_flags.contains(LinkFlags::IS_UNICODE),
+            code_page,
+        );
+        self.extra_data.write(&mut buf);
+
+        buf
     }
 
-    /// The creation `FileTime` as a `DateTime`
-    #[cfg(feature = "chrono")]
-    pub fn created_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
-        self.header.created_on
+    /// Write the shortcut to the .lnk file at `path`, see [`Lnk::to_bytes`]. The data is first
+    /// written to a temporary file next to `path`, which then replaces `path`, so an existing
+    /// file is never left partially written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    /// use std::convert::TryFrom;
+    /// use std::path::Path;
+    ///
+    /// let mut lnk = Lnk::try_from(Path::new(r"c:\users\me\desktop\firefox.lnk")).unwrap();
+    /// lnk.string_data.command_line_arguments = Some("--private-window".to_string());
+    /// lnk.save_to_path(Path::new(r"c:\users\me\desktop\private firefox.lnk")).unwrap();
+    /// ```
+    ///
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        let file_name = path.file_name().ok_or_else(|| {
+            error::Error::Save(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "path does not name a file",
+            ))
+        })?;
+
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        std::fs::write(&temp_path, self.to_bytes())
+            .and_then(|_| std::fs::rename(&temp_path, path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                error::Error::Save(e)
+            })
     }
 
-    /// The access `FileTime` as a `DateTime`
-    #[cfg(feature = "chrono")]
-    pub fn accessed_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
-        self.header.accessed_on
+    /// Recoverable problems encountered while parsing, such as strings that could not be
+    /// decoded or unknown extra data blocks that were skipped. The affected fields are left
+    /// empty.
+    pub fn warnings(&self) -> &[error::ParseWarning] {
+        &self.warnings
     }
 
-    /// The write `FileTime` as a `DateTime`
-    #[cfg(feature = "chrono")]
-    pub fn modified_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
-        self.header.modified_on
+    /// Check the `Lnk` against the invariants of the specification, collecting every
+    /// violation rather than stopping at the first:
+    ///
+    /// * the `HeaderSize` and `LinkCLSID` have their required values, and the reserved
+    ///   fields are zero
+    /// * the `LinkInfo` header size is valid, the structure is at least as large as its
+    ///   header, and the offsets of its present fields lie within the structure
+    /// * each extra data block declares the size the specification requires for it
+    /// * each string the header declares in the `StringData` was decoded
+    pub fn validate(&self) -> std::result::Result<(), Vec<error::ValidationIssue>> {
+        use error::ValidationIssue;
+
+        let mut issues = Vec::new();
+        let header = &self.header;
+
+        if header.header_size != ShellLinkHeader::HEADER_SIZE {
+            issues.push(ValidationIssue::InvalidHeaderSize(header.header_size));
+        }
+        if header.link_clsid != ShellLinkHeader::LINK_CLSID {
+            issues.push(ValidationIssue::InvalidClsid(header.link_clsid));
+        }
+        if !header.reserved_bits_clean() {
+            issues.push(ValidationIssue::ReservedFields);
+        }
+
+        let link_info = &self.link_info;
+        if let Some(link_info_flags) = link_info.link_info_flags {
+            let header_size = link_info.link_info_header_size();
+            if header_size != 0x0000_001c && header_size < 0x0000_0024 {
+                issues.push(ValidationIssue::InvalidLinkInfoHeaderSize(header_size));
+            }
+            if link_info.link_info_size() < header_size {
+                issues.push(ValidationIssue::InvalidLinkInfoSize(
+                    link_info.link_info_size(),
+                ));
+            }
+
+            let local = link_info_flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH);
+            let network = link_info_flags
+                .contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+            let unicode = link_info.has_unicode_paths();
+
+            for (present, field, offset) in [
+                (local, "VolumeID", link_info.volume_id_offset()),
+                (local, "LocalBasePath", link_info.local_base_path_offset()),
+                (
+                    network,
+                    "CommonNetworkRelativeLink",
+                    link_info.common_network_relative_link_offset(),
+                ),
+                (
+                    true,
+                    "CommonPathSuffix",
+                    link_info.common_path_suffix_offset(),
+                ),
+                (
+                    local && unicode,
+                    "LocalBasePathUnicode",
+                    link_info
+                        .local_base_path_offset_unicode()
+                        .unwrap_or_default(),
+                ),
+                (
+                    unicode,
+                    "CommonPathSuffixUnicode",
+                    link_info
+                        .common_path_suffix_offset_unicode()
+                        .unwrap_or_default(),
+                ),
+            ] {
+                if present && !(header_size..link_info.link_info_size()).contains(&offset) {
+                    issues.push(ValidationIssue::InvalidLinkInfoOffset(field, offset));
+                }
+            }
+        }
+
+        self.extra_data.validate_block_sizes(&mut issues);
+
+        let string_data = &self.string_data;
+        for (flag, field, decoded) in [
+            (
+                LinkFlags::HAS_NAME,
+                "NameString",
+                string_data.name_string.is_some(),
+            ),
+            (
+                LinkFlags::HAS_RELATIVE_PATH,
+                "RelativePath",
+                string_data.relative_path.is_some(),
+            ),
+            (
+                LinkFlags::HAS_WORKING_DIR,
+                "WorkingDir",
+                string_data.working_dir.is_some(),
+            ),
+            (
+                LinkFlags::HAS_ARGUMENTS,
+                "CommandLineArguments",
+                string_data.command_line_arguments.is_some(),
+            ),
+            (
+                LinkFlags::HAS_ICON_LOCATION,
+                "IconLocation",
+                string_data.icon_location.is_some(),
+            ),
+        ] {
+            if header.link_flags.contains(flag) && !decoded {
+                issues.push(ValidationIssue::MissingString(field));
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
     }
-}
 
-impl TryFrom<&Path> for Lnk {
-    type Error = crate::error::Error;
+    /// Whether the `LinkInfo` is used to resolve the target. It is parsed whenever it is
+    /// present, but Windows ignores it when the `ForceNoLinkInfo` flag is set, and so do
+    /// [`Lnk::target_path`] and [`Lnk::target_location`].
+    pub fn link_info_is_honored(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::HAS_LINK_INFO)
+            && !self
+                .header
+                .link_flags
+                .contains(LinkFlags::FORCE_NO_LINK_INFO)
+    }
 
-    fn try_from(p: &Path) -> std::result::Result<Self, Self::Error> {
-        let mut f = std::fs::File::open(p).map_err(crate::error::Error::from)?;
-        Lnk::new(&mut f).map(|mut lnk| {
-            lnk.path = Some(p.to_path_buf());
-            lnk
-        })
+    /// The path of the link target, built from the `LocalBasePath` and `CommonPathSuffix`
+    /// of the `LinkInfo`, or for a network shortcut from its `NetName` and
+    /// `CommonPathSuffix`, or else taken from the `EnvironmentVariableDataBlock`. See
+    /// [`Lnk::try_target_path`] for why this may be `None`.
+    ///
+    /// Like the other string accessors, the path is escaped with [`escape_for_display`], see
+    /// [`Lnk::target_path_raw`] for the path as it is stored.
+    pub fn target_path(&self) -> Option<PathBuf> {
+        self.try_target_path().ok()
     }
-}
-impl TryFrom<PathBuf> for Lnk {
-    type Error = crate::error::Error;
 
-    fn try_from(p: PathBuf) -> std::result::Result<Self, Self::Error> {
-        Self::try_from(p.as_path())
+    /// The path of the link target as it is stored, see [`Lnk::target_path`]
+    pub fn target_path_raw(&self) -> Option<PathBuf> {
+        self.try_target_path_raw().ok()
     }
-}
 
-impl TryFrom<&[u8]> for Lnk {
-    type Error = crate::error::Error;
+    /// The path of the link target, built from the `LocalBasePath` and `CommonPathSuffix`
+    /// of the `LinkInfo`, preferring their unicode variants when present. When the
+    /// `LinkInfo` only has a `CommonNetworkRelativeLink`, this is the UNC path joining its
+    /// `NetName` and the `CommonPathSuffix`, as `\\server\share\dir\file.txt`.
+    ///
+    /// When the `LinkInfo` yields no path and the `HasExpString` flag is set, as for many
+    /// Office and Store app shortcuts, this is the target of the
+    /// `EnvironmentVariableDataBlock` with its `%VAR%` tokens left in place, see
+    /// [`Lnk::target_path_expanded`] to substitute them. The error is the reason the
+    /// `LinkInfo` yielded no path.
+    pub fn try_target_path(&self) -> std::result::Result<PathBuf, error::TargetResolutionError> {
+        self.try_target_path_str()
+            .map(|path| PathBuf::from(escape_for_display(&path)))
+    }
 
-    fn try_from(mut p: &[u8]) -> std::result::Result<Self, Self::Error> {
-        Lnk::new(&mut p)
+    /// The path of the link target as it is stored, see [`Lnk::try_target_path`]
+    pub fn try_target_path_raw(
+        &self,
+    ) -> std::result::Result<PathBuf, error::TargetResolutionError> {
+        self.try_target_path_str().map(PathBuf::from)
     }
-}
 
-impl TryFrom<Vec<u8>> for Lnk {
-    type Error = crate::error::Error;
+    /// The path of the link target as the Windows-style string stored in the `LinkInfo`,
+    /// see [`Lnk::target_path`]. Unlike a `PathBuf`, this is not interpreted by the host, and
+    /// can be split into its drive letter and components with [`WindowsPath`].
+    pub fn target_path_str(&self) -> Option<String> {
+        self.target_path_str_raw()
+            .map(|path| escape_for_display(&path))
+    }
 
-    fn try_from(p: Vec<u8>) -> std::result::Result<Self, Self::Error> {
-        Lnk::new(&mut p.as_slice())
+    /// The path of the link target as it is stored, see [`Lnk::target_path_str`]
+    pub fn target_path_str_raw(&self) -> Option<String> {
+        self.try_target_path_str().ok()
     }
-}
 
-impl TryFrom<&Vec<u8>> for Lnk {
-    type Error = crate::error::Error;
+    fn try_target_path_str(&self) -> std::result::Result<String, error::TargetResolutionError> {
+        let result = match self.try_local_path_str() {
+            Err(error::TargetResolutionError::NetworkOnly) => self
+                .network_path()
+                .ok_or(error::TargetResolutionError::NetworkOnly),
+            result => result,
+        };
 
-    fn try_from(p: &Vec<u8>) -> std::result::Result<Self, Self::Error> {
-        Lnk::new(&mut p.as_slice())
+        result.or_else(|e| self.environment_target().ok_or(e))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::Lnk;
-    use std::convert::TryFrom;
-    use std::path::Path;
+    /// The unexpanded target of the `EnvironmentVariableDataBlock`, if the `HasExpString`
+    /// flag is set
+    fn environment_target(&self) -> Option<String> {
+        self.extra_data
+            .environment_props
+            .as_ref()
+            .filter(|_| self.header.link_flags.contains(LinkFlags::HAS_EXP_STRING))
+            .and_then(|props| props.target())
+    }
 
-    #[test]
-    fn firefox() {
-        let path = Path::new("./test_data/firefox.lnk");
-        assert!(Lnk::try_from(path).is_ok());
+    /// The local path of the target, built from the `LocalBasePath` and `CommonPathSuffix`
+    fn try_local_path_str(&self) -> std::result::Result<String, error::TargetResolutionError> {
+        use error::TargetResolutionError;
+
+        if !self.header.link_flags.contains(LinkFlags::HAS_LINK_INFO) {
+            return Err(TargetResolutionError::NoLinkInfo);
+        }
+        if !self.link_info_is_honored() {
+            return Err(TargetResolutionError::LinkInfoIgnored);
+        }
+
+        let link_info_flags = self
+            .link_info
+            .link_info_flags
+            .ok_or(TargetResolutionError::NoLinkInfo)?;
+
+        if !link_info_flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH) {
+            return if link_info_flags
+                .contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX)
+            {
+                Err(TargetResolutionError::NetworkOnly)
+            } else {
+                Err(TargetResolutionError::NoLocalBasePath)
+            };
+        }
+
+        let link_info = &self.link_info;
+        let base = link_info
+            .local_base_path_unicode
+            .as_ref()
+            .filter(|base| !base.is_empty())
+            .or(link_info.local_base_path.as_ref());
+        let suffix = link_info
+            .common_path_suffix_unicode
+            .as_ref()
+            .filter(|suffix| !suffix.is_empty())
+            .or(link_info.common_path_suffix.as_ref());
+
+        let path = base.into_iter().chain(suffix).cloned().collect::<String>();
+
+        if path.is_empty() {
+            Err(TargetResolutionError::EmptyPath)
+        } else {
+            Ok(path)
+        }
     }
 
-    #[test]
-    fn commander() {
-        let path = Path::new("./test_data/commander.lnk");
-        assert!(Lnk::try_from(path).is_ok());
+    /// The path of the link target, taken from the `EnvironmentVariableDataBlock` when the
+    /// `HasExpString` flag is set, with its `%VAR%` tokens substituted from `env`. Falls back
+    /// to [`Lnk::target_path`] when there is no environment variable target.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    ///
+    /// let lnk = Lnk::from_file(r"c:\users\me\desktop\notepad.lnk").unwrap();
+    /// let env = std::env::vars().collect();
+    /// let target = lnk.target_path_expanded(&env);
+    /// ```
+    ///
+    pub fn target_path_expanded(&self, env: &HashMap<String, String>) -> Option<PathBuf> {
+        self.target_path_expanded_raw(env)
+            .map(|path| display::escape_path(&path))
     }
 
-    #[test]
-    fn notepad() {
-        let path = Path::new("./test_data/notepad.lnk");
-        assert!(Lnk::try_from(path).is_ok());
+    /// The expanded path of the link target, without escaping, see
+    /// [`Lnk::target_path_expanded`]
+    pub fn target_path_expanded_raw(&self, env: &HashMap<String, String>) -> Option<PathBuf> {
+        self.extra_data
+            .environment_props
+            .as_ref()
+            .filter(|_| self.header.link_flags.contains(LinkFlags::HAS_EXP_STRING))
+            .and_then(|props| props.target_expanded(env))
+            .map(PathBuf::from)
+            .or_else(|| self.target_path_raw())
     }
 
-    #[test]
-    fn xp_outlook_express() {
-        let path = Path::new("./test_data/outlook_express.lnk");
-        assert!(Lnk::try_from(path).is_ok());
+    /// Classify where the target is located. A local path is preferred over a network path
+    /// when the `LinkInfo` has both, and the `LinkInfo` over the environment variable target,
+    /// in the same order as [`Lnk::target_path`].
+    pub fn target_location(&self) -> TargetLocation {
+        if let Ok(path) = self.try_local_path_str() {
+            TargetLocation::Local(PathBuf::from(path))
+        } else if let Some(path) = self.network_path() {
+            TargetLocation::Network(path)
+        } else if let Some(path) = self.environment_target() {
+            TargetLocation::Environment(path)
+        } else if !self.link_target_id_list.is_empty() {
+            TargetLocation::IdListOnly
+        } else {
+            TargetLocation::Unknown
+        }
+    }
+
+    /// The extension of the target's file name, lowercased and without the dot, as `"exe"`.
+    /// The name is taken from the `LinkInfo` path of the target, the relative path, or else
+    /// the last item of the `LinkTargetIDList`.
+    pub fn target_extension(&self) -> Option<String> {
+        let path = self
+            .target_path_str_raw()
+            .or_else(|| {
+                self.string_data
+                    .relative_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned())
+            })
+            .or_else(
+                || match self.link_target_id_list.item_ids().last()?.shell_item() {
+                    ShellItem::File(name) => Some(name),
+                    _ => None,
+                },
+            )?;
+
+        let file_name = WindowsPath::new(path).file_name()?.to_string();
+        match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => {
+                Some(extension.to_lowercase())
+            }
+            _ => None,
+        }
+    }
+
+    /// The file extensions that [`Lnk::target_is_executable`] treats as executable
+    pub const EXECUTABLE_EXTENSIONS: [&'static str; 9] =
+        ["exe", "com", "bat", "cmd", "scr", "pif", "ps1", "vbs", "js"];
+
+    /// Whether the target's extension is one of [`Lnk::EXECUTABLE_EXTENSIONS`], a heuristic
+    /// for shortcuts that launch a program or script. Combined with [`Lnk::arguments`],
+    /// this surfaces shortcuts that run scripts.
+    pub fn target_is_executable(&self) -> bool {
+        self.target_is_one_of(&Self::EXECUTABLE_EXTENSIONS)
+    }
+
+    /// Whether the target's extension, see [`Lnk::target_extension`], is one of
+    /// `extensions`, compared case-insensitively
+    pub fn target_is_one_of(&self, extensions: &[&str]) -> bool {
+        self.target_extension()
+            .filter(|extension| {
+                extensions
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(extension))
+            })
+            .is_some()
+    }
+
+    /// Whether the `LinkInfo` locates the target on a network share, having a
+    /// `CommonNetworkRelativeLink` with a `NetName`. Unlike [`Lnk::target_location`], this
+    /// does not build the path.
+    pub fn is_network_target(&self) -> bool {
+        self.link_info_is_honored()
+            && matches!(
+                self.link_info.link_info_flags,
+                Some(flags)
+                    if flags.contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX)
+            )
+            && matches!(
+                self.link_info.common_network_relative_link,
+                Some(ref link) if [&link.net_name_unicode, &link.net_name]
+                    .iter()
+                    .any(|net_name| matches!(net_name, Some(name) if !name.is_empty()))
+            )
+    }
+
+    /// Whether the `LinkInfo` locates the target on a local volume, having a `VolumeID` and
+    /// a `LocalBasePath`. Unlike [`Lnk::target_location`], this does not build the path.
+    pub fn is_local_target(&self) -> bool {
+        let link_info = &self.link_info;
+
+        self.link_info_is_honored()
+            && matches!(
+                link_info.link_info_flags,
+                Some(flags) if flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH)
+            )
+            && link_info.volume_id.is_some()
+            && (link_info.local_base_path.is_some() || link_info.local_base_path_unicode.is_some())
+    }
+
+    /// Whether the link carries a path to its target, in the `LinkInfo`, the
+    /// `EnvironmentVariableDataBlock` or as a relative path.
+    /// This is `false` for shortcuts such as control panel items, whose target is only
+    /// described by the shell namespace items of the `LinkTargetIDList`.
+    pub fn has_usable_target(&self) -> bool {
+        match self.target_location() {
+            TargetLocation::Local(_)
+            | TargetLocation::Network(_)
+            | TargetLocation::Environment(_) => true,
+            TargetLocation::IdListOnly | TargetLocation::Unknown => matches!(
+                self.string_data.relative_path,
+                Some(ref path) if !path.as_os_str().is_empty()
+            ),
+        }
+    }
+
+    /// The UNC path of the target, built from the `NetName` of the
+    /// `CommonNetworkRelativeLink` and the `CommonPathSuffix` of the `LinkInfo`
+    fn network_path(&self) -> Option<String> {
+        if !self.link_info_is_honored() {
+            return None;
+        }
+
+        let link_info = &self.link_info;
+        let common_network_relative_link = link_info.common_network_relative_link.as_ref()?;
+
+        let net_name = common_network_relative_link
+            .net_name_unicode
+            .as_ref()
+            .filter(|net_name| !net_name.is_empty())
+            .or(common_network_relative_link.net_name.as_ref())
+            .filter(|net_name| !net_name.is_empty())?;
+        let suffix = link_info
+            .common_path_suffix_unicode
+            .as_ref()
+            .filter(|suffix| !suffix.is_empty())
+            .or(link_info.common_path_suffix.as_ref())
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        Some(
+            if suffix.is_empty() || net_name.ends_with('\\') || suffix.starts_with('\\') {
+                format!("{}{}", net_name, suffix)
+            } else {
+                format!("{}\\{}", net_name, suffix)
+            },
+        )
+    }
+
+    /// Whether the `StringData` strings are stored as unicode (UTF-16), as indicated by the
+    /// `IS_UNICODE` link flag. When this is `false` the strings are stored in the system default
+    /// code page of the machine that created the link, and are decoded using the
+    /// `ansi_code_page` of the [`LnkOptions`], the code page of the `ConsoleFEDataBlock` if
+    /// present, or Windows-1252 otherwise.
+    pub fn is_unicode(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::IS_UNICODE)
+    }
+
+    /// A best-effort guess at the kind of writer the `Lnk` came from, which helps explain
+    /// compatibility quirks:
+    ///
+    /// * links with a header size other than 0x4C, or with neither a `LinkTargetIDList` nor a
+    ///   `LinkInfo`, are not written by the shell and are `ThirdPartyGenerator`
+    /// * links with ANSI `StringData` are written by Windows 9x or NT and are `Legacy`
+    /// * links with a 0x24 byte `LinkInfo` header, the `EnableTargetMetadata` flag, or a
+    ///   `VistaAndAboveIDListDataBlock`, `PropertyStoreDataBlock` or `KnownFolderDataBlock`
+    ///   are `VistaPlus`
+    /// * anything else is `Legacy`
+    pub fn flavor(&self) -> LnkFormatFlavor {
+        let link_flags = self.header.link_flags;
+        let extra_data = &self.extra_data;
+
+        if self.header.header_size != ShellLinkHeader::HEADER_SIZE
+            || (self.link_target_id_list.is_empty() && self.link_info.link_info_flags.is_none())
+        {
+            LnkFormatFlavor::ThirdPartyGenerator
+        } else if !link_flags.contains(LinkFlags::IS_UNICODE) {
+            LnkFormatFlavor::Legacy
+        } else if self.link_info.has_unicode_paths()
+            || link_flags.contains(LinkFlags::ENABLE_TARGET_METADATA)
+            || extra_data.vista_and_above_idlist_props.is_some()
+            || extra_data.property_store_props.is_some()
+            || extra_data.known_folder_props.is_some()
+        {
+            LnkFormatFlavor::VistaPlus
+        } else {
+            LnkFormatFlavor::Legacy
+        }
+    }
+
+    /// The window state the target is launched in
+    pub fn show_window(&self) -> ShowWindow {
+        ShowWindow::from(self.header.show_command_raw)
+    }
+
+    /// The file attributes of the link target
+    pub fn file_attributes(&self) -> FileAttributeFlags {
+        self.header.file_attributes
+    }
+
+    /// Whether the link target is a directory
+    pub fn is_directory(&self) -> bool {
+        self.header
+            .file_attributes
+            .contains(FileAttributeFlags::FILE_ATTRIBUTE_DIRECTORY)
+    }
+
+    /// Whether the link target is read-only
+    pub fn is_readonly(&self) -> bool {
+        self.header
+            .file_attributes
+            .contains(FileAttributeFlags::FILE_ATTRIBUTE_READONLY)
+    }
+
+    /// Whether the link target is hidden
+    pub fn is_hidden(&self) -> bool {
+        self.header
+            .file_attributes
+            .contains(FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN)
+    }
+
+    /// Whether the link target is used by the operating system
+    pub fn is_system(&self) -> bool {
+        self.header
+            .file_attributes
+            .contains(FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM)
+    }
+
+    /// The size, in bytes, of the link target when the link was created. Only the least
+    /// significant 32 bits of the size are stored, so this is truncated for targets larger
+    /// than 4 GiB.
+    pub fn target_file_size(&self) -> u32 {
+        self.header.file_size
+    }
+
+    /// Whether the target file size is `0xFFFFFFFF`, a value commonly stored in place of the
+    /// size of a target too large to fit in 32 bits. This is only a hint, see
+    /// [`Lnk::target_file_size`].
+    pub fn target_file_size_is_truncated_hint(&self) -> bool {
+        self.header.file_size == u32::MAX
+    }
+
+    /// Every path the `Lnk` references, tagged with where it was found. Only values that are
+    /// present and not empty are included. The paths are escaped with [`escape_for_display`],
+    /// see [`Lnk::all_paths_raw`] for the paths as they are stored.
+    pub fn all_paths(&self) -> Vec<(PathKind, String)> {
+        self.all_paths_raw()
+            .into_iter()
+            .map(|(kind, path)| (kind, escape_for_display(&path)))
+            .collect()
+    }
+
+    /// Every path the `Lnk` references as it is stored, see [`Lnk::all_paths`]
+    pub fn all_paths_raw(&self) -> Vec<(PathKind, String)> {
+        let string_data = &self.string_data;
+        let extra_data = &self.extra_data;
+
+        let paths = [
+            (
+                PathKind::RelativePath,
+                string_data
+                    .relative_path
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().into_owned()),
+            ),
+            (
+                PathKind::WorkingDir,
+                string_data
+                    .working_dir
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().into_owned()),
+            ),
+            (
+                PathKind::IconLocation,
+                string_data
+                    .icon_location
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().into_owned()),
+            ),
+            (
+                PathKind::LocalBasePath,
+                self.link_info
+                    .local_base_path_unicode
+                    .clone()
+                    .filter(|p| !p.is_empty())
+                    .or_else(|| self.link_info.local_base_path.clone()),
+            ),
+            (
+                PathKind::EnvironmentTarget,
+                extra_data
+                    .environment_props
+                    .as_ref()
+                    .and_then(|props| props.target()),
+            ),
+            (
+                PathKind::IconEnvironmentTarget,
+                extra_data
+                    .icon_environment_props
+                    .as_ref()
+                    .and_then(|props| props.target()),
+            ),
+            (
+                PathKind::DarwinId,
+                extra_data
+                    .darwin_props
+                    .as_ref()
+                    .and_then(|props| props.application_id()),
+            ),
+        ];
+
+        IntoIterator::into_iter(paths)
+            .filter_map(|(kind, path)| path.filter(|p| !p.is_empty()).map(|p| (kind, p)))
+            .collect()
+    }
+
+    /// Whether the shortcut chains to another shortcut: the `ALLOW_LINK_TO_LINK` flag is set
+    /// and the target path has a `.lnk` extension (in any case).
+    pub fn is_link_to_link(&self) -> bool {
+        self.header
+            .link_flags
+            .contains(LinkFlags::ALLOW_LINK_TO_LINK)
+            && matches!(
+                self.target_path_raw(),
+                Some(target) if target.to_string_lossy().to_lowercase().ends_with(".lnk")
+            )
+    }
+
+    /// Whether the `RunAsUser` link flag is set, which is how the "Run as administrator"
+    /// option of a shortcut is stored
+    pub fn runs_as_user(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::RUN_AS_USER)
+    }
+
+    /// Whether launching the shortcut requests elevation, either through the `RunAsUser` link
+    /// flag or a `RunAsAdmin` compatibility layer in the `ShimDataBlock`
+    pub fn requests_elevation(&self) -> bool {
+        self.runs_as_user()
+            || self
+                .extra_data
+                .shim_props
+                .as_ref()
+                .and_then(|shim| shim.to_string().ok())
+                .filter(|layers| {
+                    layers
+                        .trim_end_matches('\0')
+                        .split_whitespace()
+                        .any(|layer| layer.eq_ignore_ascii_case("RunAsAdmin"))
+                })
+                .is_some()
+    }
+
+    /// The names of the `LinkFlags` set in the header, see [`LinkFlags::iter_names`]
+    pub fn link_flag_names(&self) -> Vec<&'static str> {
+        self.header.link_flags.iter_names().collect()
+    }
+
+    /// The command line arguments supplied via the `Lnk`, escaped with
+    /// [`escape_for_display`], see [`Lnk::arguments_raw`]
+    pub fn arguments(&self) -> Option<String> {
+        self.arguments_raw()
+            .map(|arguments| escape_for_display(&arguments))
+    }
+
+    /// The command line arguments supplied via the `Lnk` as they are stored
+    pub fn arguments_raw(&self) -> Option<String> {
+        self.string_data.command_line_arguments.clone()
+    }
+
+    /// The command line arguments supplied via the `Lnk`, split into separate arguments using
+    /// the quoting rules of `CommandLineToArgvW`. Empty if there are no arguments. Each
+    /// argument is escaped with [`escape_for_display`], see [`Lnk::arguments_split_raw`].
+    pub fn arguments_split(&self) -> Vec<String> {
+        self.arguments_split_raw()
+            .iter()
+            .map(|argument| escape_for_display(argument))
+            .collect()
+    }
+
+    /// The command line arguments split like [`Lnk::arguments_split`], as they are stored
+    pub fn arguments_split_raw(&self) -> Vec<String> {
+        self.string_data
+            .command_line_arguments
+            .as_deref()
+            .map(command_line::split)
+            .unwrap_or_default()
+    }
+
+    /// The relative path to the resource of the `Lnk`, escaped with [`escape_for_display`],
+    /// see [`Lnk::relative_path_raw`]
+    pub fn relative_path(&self) -> Option<PathBuf> {
+        self.relative_path_raw()
+            .map(|path| display::escape_path(&path))
+    }
+
+    /// The relative path to the resource of the `Lnk` as it is stored
+    pub fn relative_path_raw(&self) -> Option<PathBuf> {
+        self.string_data.relative_path.clone()
+    }
+
+    /// The relative path resolved against the directory containing the `.lnk` file, with
+    /// `.` and `..` components removed. The relative path is split on both `\` and `/`.
+    /// Returns `None` if there is no relative path or the `Lnk` was not loaded from a path.
+    /// The path is escaped with [`escape_for_display`], see
+    /// [`Lnk::relative_path_resolved_raw`].
+    pub fn relative_path_resolved(&self) -> Option<PathBuf> {
+        self.relative_path_resolved_raw()
+            .map(|path| display::escape_path(&path))
+    }
+
+    /// The resolved relative path as it is stored, see [`Lnk::relative_path_resolved`]
+    pub fn relative_path_resolved_raw(&self) -> Option<PathBuf> {
+        let mut resolved = self.path.as_deref()?.parent()?.to_path_buf();
+        let relative = self.string_data.relative_path.as_deref()?.to_string_lossy();
+
+        for component in relative.split(['\\', '/']) {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    resolved.pop();
+                }
+                component => resolved.push(component),
+            }
+        }
+
+        Some(resolved)
+    }
+
+    /// The working directory of the `Lnk`, escaped with [`escape_for_display`], see
+    /// [`Lnk::working_dir_raw`]
+    pub fn working_dir(&self) -> Option<PathBuf> {
+        self.working_dir_raw().map(|dir| display::escape_path(&dir))
+    }
+
+    /// The working directory of the `Lnk` as it is stored
+    pub fn working_dir_raw(&self) -> Option<PathBuf> {
+        self.string_data.working_dir.clone()
+    }
+
+    /// The description of the `Lnk`, escaped with [`escape_for_display`], see
+    /// [`Lnk::description_raw`]
+    pub fn description(&self) -> Option<String> {
+        self.description_raw()
+            .map(|description| escape_for_display(&description))
+    }
+
+    /// The description of the `Lnk` as it is stored
+    pub fn description_raw(&self) -> Option<String> {
+        self.string_data.name_string.clone()
+    }
+
+    /// The icon location of the `Lnk`, escaped with [`escape_for_display`], see
+    /// [`Lnk::icon_location_raw`]
+    pub fn icon_location(&self) -> Option<PathBuf> {
+        self.icon_location_raw()
+            .map(|location| display::escape_path(&location))
+    }
+
+    /// The icon location of the `Lnk` as it is stored
+    pub fn icon_location_raw(&self) -> Option<PathBuf> {
+        self.string_data.icon_location.clone()
+    }
+
+    /// The index of the icon within the icon location. The field is signed: a negative value
+    /// is the resource ID of the icon rather than its position in the file.
+    pub fn icon_index(&self) -> i32 {
+        self.header.icon_index as i32
+    }
+
+    /// The icon location together with the index of the icon within it. When the
+    /// `HasExpIcon` flag is set, the location is taken from the `IconEnvironmentDataBlock`,
+    /// with its environment variables left unexpanded. The location is escaped with
+    /// [`escape_for_display`], see [`Lnk::icon_raw`].
+    pub fn icon(&self) -> Option<(PathBuf, i32)> {
+        self.icon_raw()
+            .map(|(location, index)| (display::escape_path(&location), index))
+    }
+
+    /// The icon location as it is stored together with the index of the icon, see
+    /// [`Lnk::icon`]
+    pub fn icon_raw(&self) -> Option<(PathBuf, i32)> {
+        self.extra_data
+            .icon_environment_props
+            .as_ref()
+            .filter(|_| self.header.link_flags.contains(LinkFlags::HAS_EXP_ICON))
+            .and_then(|props| props.target())
+            .map(PathBuf::from)
+            .or_else(|| self.icon_location_raw())
+            .map(|location| (location, self.icon_index()))
+    }
+
+    /// The NetBIOS name of the machine the target was last known to reside on, from the
+    /// `TrackerDataBlock`. This is usually the machine the .lnk was created on.
+    pub fn created_by_machine(&self) -> Option<String> {
+        self.extra_data
+            .tracker_props
+            .as_ref()
+            .map(|tracker| tracker.machine_id_string())
+            .filter(|machine_id| !machine_id.is_empty())
+    }
+
+    /// The AppUserModelID used by the taskbar and jump lists to group the target's windows,
+    /// from the `System.AppUserModel.ID` property of the `PropertyStoreDataBlock`, escaped
+    /// with [`escape_for_display`], see [`Lnk::app_user_model_id_raw`]
+    pub fn app_user_model_id(&self) -> Option<String> {
+        self.app_user_model_id_raw()
+            .map(|id| escape_for_display(&id))
+    }
+
+    /// The AppUserModelID as it is stored, see [`Lnk::app_user_model_id`]
+    pub fn app_user_model_id_raw(&self) -> Option<String> {
+        match self
+            .extra_data
+            .property_store_props
+            .as_ref()?
+            .property(PropertyStoreDataBlock::APP_USER_MODEL_FMTID, 5)?
+        {
+            PropertyValue::String(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Whether the header declares a description string. This is `true` even when the
+    /// string is empty or could not be decoded, unlike checking [`Lnk::description`].
+    pub fn has_description(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::HAS_NAME)
+    }
+
+    /// Whether the header declares a relative path string, see [`Lnk::has_description`]
+    pub fn has_relative_path(&self) -> bool {
+        self.header
+            .link_flags
+            .contains(LinkFlags::HAS_RELATIVE_PATH)
+    }
+
+    /// Whether the header declares a working directory string, see [`Lnk::has_description`]
+    pub fn has_working_dir(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::HAS_WORKING_DIR)
+    }
+
+    /// Whether the header declares a command line arguments string, see
+    /// [`Lnk::has_description`]
+    pub fn has_arguments(&self) -> bool {
+        self.header.link_flags.contains(LinkFlags::HAS_ARGUMENTS)
+    }
+
+    /// Whether the header declares an icon location string, see [`Lnk::has_description`]
+    pub fn has_icon_location(&self) -> bool {
+        self.header
+            .link_flags
+            .contains(LinkFlags::HAS_ICON_LOCATION)
+    }
+
+    /// Discard the raw bytes retained for the extra data blocks, keeping only the parsed
+    /// fields. This keeps long-lived collections of parsed shortcuts small, at the cost of
+    /// [`ExtraData::raw_block`] returning `None`. [`Lnk::to_bytes`] then serializes the
+    /// extra data blocks from their parsed fields, leaving out those that were not decoded,
+    /// and [`Lnk::validate`] still checks their declared sizes.
+    pub fn drop_raw(&mut self) {
+        self.extra_data.drop_raw();
+    }
+
+    /// A copy of this `Lnk` without its retained raw bytes, see [`Lnk::drop_raw`]
+    pub fn clone_without_raw(&self) -> Lnk {
+        let mut lnk = self.clone();
+        lnk.drop_raw();
+        lnk
+    }
+
+    /// A hash of what the `Lnk` launches, for deduplicating shortcuts. Only the target path,
+    /// the command line arguments, the working directory and the icon participate,
+    /// so copies of a shortcut that differ only in their timestamps or description hash
+    /// equally. The value is only stable within a single build of the crate.
+    pub fn content_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.target_path_raw().hash(&mut hasher);
+        self.string_data.command_line_arguments.hash(&mut hasher);
+        self.string_data.working_dir.hash(&mut hasher);
+        self.icon_raw().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The creation `FileTime` as a u64
+    pub fn creation_time(&self) -> u64 {
+        self.header.creation_time
+    }
+
+    /// The access `FileTime` as a u64
+    pub fn access_time(&self) -> u64 {
+        self.header.access_time
+    }
+
+    /// The write `FileTime` as a u64
+    pub fn write_time(&self) -> u64 {
+        self.header.write_time
+    }
+
+    /// The creation `FileTime` as a u64, or `None` when it is zero, meaning not set
+    pub fn creation_time_opt(&self) -> Option<u64> {
+        Some(self.header.creation_time).filter(|time| *time != 0)
+    }
+
+    /// The access `FileTime` as a u64, or `None` when it is zero, meaning not set
+    pub fn access_time_opt(&self) -> Option<u64> {
+        Some(self.header.access_time).filter(|time| *time != 0)
+    }
+
+    /// The write `FileTime` as a u64, or `None` when it is zero, meaning not set
+    pub fn write_time_opt(&self) -> Option<u64> {
+        Some(self.header.write_time).filter(|time| *time != 0)
+    }
+
+    /// The commonly used fields of the shortcut as plain owned values, see [`FlatLnk`]
+    pub fn flatten(&self) -> FlatLnk {
+        FlatLnk::from(self)
+    }
+
+    /// Compare two shortcuts by their creation time, for use with `sort_by`. A zero
+    /// (unset) time sorts first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parselnk::Lnk;
+    ///
+    /// let mut lnks = parselnk::scan_dir(std::path::Path::new(r"c:\users\me\desktop"))
+    ///     .unwrap()
+    ///     .filter_map(|(_, lnk)| lnk.ok())
+    ///     .collect::<Vec<_>>();
+    /// lnks.sort_by(Lnk::cmp_by_created);
+    /// ```
+    ///
+    pub fn cmp_by_created(&self, other: &Lnk) -> std::cmp::Ordering {
+        self.header.creation_time.cmp(&other.header.creation_time)
+    }
+
+    /// Compare two shortcuts by their access time, for use with `sort_by`. A zero (unset)
+    /// time sorts first.
+    pub fn cmp_by_accessed(&self, other: &Lnk) -> std::cmp::Ordering {
+        self.header.access_time.cmp(&other.header.access_time)
+    }
+
+    /// Compare two shortcuts by their write time, for use with `sort_by`. A zero (unset)
+    /// time sorts first.
+    pub fn cmp_by_modified(&self, other: &Lnk) -> std::cmp::Ordering {
+        self.header.write_time.cmp(&other.header.write_time)
+    }
+
+    /// The creation `FileTime`, split into its high and low parts
+    pub fn creation_filetime(&self) -> FileTime {
+        FileTime::from(self.header.creation_time)
+    }
+
+    /// The access `FileTime`, split into its high and low parts
+    pub fn access_filetime(&self) -> FileTime {
+        FileTime::from(self.header.access_time)
+    }
+
+    /// The write `FileTime`, split into its high and low parts
+    pub fn write_filetime(&self) -> FileTime {
+        FileTime::from(self.header.write_time)
+    }
+
+    /// The creation `FileTime` as a `DateTime`, or `None` when it is not set
+    #[cfg(feature = "chrono")]
+    pub fn created_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.header.created_on
+    }
+
+    /// The access `FileTime` as a `DateTime`, or `None` when it is not set
+    #[cfg(feature = "chrono")]
+    pub fn accessed_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.header.accessed_on
+    }
+
+    /// The write `FileTime` as a `DateTime`, or `None` when it is not set
+    #[cfg(feature = "chrono")]
+    pub fn modified_on(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.header.modified_on
+    }
+
+    /// A copy of this `Lnk` with the control and invisible format characters of its strings
+    /// escaped by [`escape_for_display`], making it safe to print to a terminal. Strings in a
+    /// shortcut are attacker controlled and can carry ANSI escape sequences, or bidirectional
+    /// overrides that make a path read differently from what it is.
+    ///
+    /// The string accessors of `Lnk`, such as [`Lnk::target_path`] or [`Lnk::description`],
+    /// already escape their values, with `*_raw` variants returning them as they are stored.
+    /// This is for the public fields and the extra data blocks: it covers the `StringData`
+    /// strings, the `LinkInfo` paths, volume label and network names, the environment, icon
+    /// and Darwin targets and the string properties of the `PropertyStoreDataBlock`. The
+    /// machine ID of the `TrackerDataBlock` never contains control characters, see
+    /// [`TrackerDataBlock::machine_id_string`]. The names of the items of the
+    /// `LinkTargetIDList` are not rewritten, and should be escaped as they are displayed.
+    ///
+    /// The copy is meant for display only: [`Lnk::to_bytes`] still writes the original
+    /// ExtraData blocks.
+    pub fn sanitize_for_display(&self) -> Lnk {
+        use display::escape_path;
+
+        let mut lnk = self.clone();
+
+        let string_data = &mut lnk.string_data;
+        string_data.name_string = string_data.name_string.as_deref().map(escape_for_display);
+        string_data.relative_path = string_data.relative_path.as_deref().map(escape_path);
+        string_data.working_dir = string_data.working_dir.as_deref().map(escape_path);
+        string_data.command_line_arguments = string_data
+            .command_line_arguments
+            .as_deref()
+            .map(escape_for_display);
+        string_data.icon_location = string_data.icon_location.as_deref().map(escape_path);
+
+        let link_info = &mut lnk.link_info;
+        link_info.local_base_path = link_info.local_base_path.as_deref().map(escape_for_display);
+        link_info.common_path_suffix = link_info
+            .common_path_suffix
+            .as_deref()
+            .map(escape_for_display);
+        link_info.local_base_path_unicode = link_info
+            .local_base_path_unicode
+            .as_deref()
+            .map(escape_for_display);
+        link_info.common_path_suffix_unicode = link_info
+            .common_path_suffix_unicode
+            .as_deref()
+            .map(escape_for_display);
+
+        if let Some(volume_id) = link_info.volume_id.as_mut() {
+            volume_id.volume_label = volume_id.volume_label.as_deref().map(escape_for_display);
+        }
+        if let Some(link) = link_info.common_network_relative_link.as_mut() {
+            link.net_name = link.net_name.as_deref().map(escape_for_display);
+            link.device_name = link.device_name.as_deref().map(escape_for_display);
+            link.net_name_unicode = link.net_name_unicode.as_deref().map(escape_for_display);
+            link.device_name_unicode = link.device_name_unicode.as_deref().map(escape_for_display);
+        }
+
+        let extra_data = &mut lnk.extra_data;
+        if let Some(props) = extra_data.environment_props.as_mut() {
+            props.sanitize_for_display();
+        }
+        if let Some(props) = extra_data.icon_environment_props.as_mut() {
+            props.sanitize_for_display();
+        }
+        if let Some(props) = extra_data.darwin_props.as_mut() {
+            props.sanitize_for_display();
+        }
+        if let Some(props) = extra_data.property_store_props.as_mut() {
+            props.sanitize_for_display();
+        }
+
+        lnk
+    }
+}
+
+/// A compact, multi-line summary of what the `Lnk` launches. Control and format characters in
+/// strings are escaped, see [`Lnk::sanitize_for_display`].
+impl std::fmt::Display for Lnk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lnk = self.sanitize_for_display();
+        let string_data = &lnk.string_data;
+
+        if let Some(target) = lnk.target_path() {
+            writeln!(f, "Target:      {}", target.display())?;
+        }
+        if let Some(ref arguments) = string_data.command_line_arguments {
+            writeln!(f, "Arguments:   {}", arguments)?;
+        }
+        if let Some(ref working_dir) = string_data.working_dir {
+            writeln!(f, "Working dir: {}", working_dir.display())?;
+        }
+        if let Some(ref description) = string_data.name_string {
+            writeln!(f, "Description: {}", description)?;
+        }
+        if let Some(ref icon_location) = string_data.icon_location {
+            writeln!(f, "Icon:        {}", icon_location.display())?;
+        }
+
+        #[cfg(feature = "chrono")]
+        {
+            let format = |time: Option<chrono::DateTime<chrono::Utc>>| {
+                time.map(|time| time.to_string()).unwrap_or_default()
+            };
+            writeln!(f, "Created:     {}", format(lnk.created_on()))?;
+            writeln!(f, "Accessed:    {}", format(lnk.accessed_on()))?;
+            write!(f, "Modified:    {}", format(lnk.modified_on()))
+        }
+
+        #[cfg(not(feature = "chrono"))]
+        {
+            writeln!(f, "Created:     {}", lnk.creation_time())?;
+            writeln!(f, "Accessed:    {}", lnk.access_time())?;
+            write!(f, "Modified:    {}", lnk.write_time())
+        }
+    }
+}
+
+impl TryFrom<&Path> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(p: &Path) -> std::result::Result<Self, Self::Error> {
+        let mut f = std::fs::File::open(p).map_err(crate::error::Error::from)?;
+        Lnk::new(&mut f).map(|mut lnk| {
+            lnk.path = Some(p.to_path_buf());
+            lnk
+        })
+    }
+}
+impl TryFrom<PathBuf> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(p: PathBuf) -> std::result::Result<Self, Self::Error> {
+        Self::try_from(p.as_path())
+    }
+}
+
+impl TryFrom<std::fs::File> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(mut f: std::fs::File) -> std::result::Result<Self, Self::Error> {
+        Lnk::new(&mut f)
+    }
+}
+
+impl TryFrom<&std::fs::File> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(mut f: &std::fs::File) -> std::result::Result<Self, Self::Error> {
+        Lnk::new(&mut f)
+    }
+}
+
+impl TryFrom<&[u8]> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(p: &[u8]) -> std::result::Result<Self, Self::Error> {
+        Lnk::parse(p)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(p: Vec<u8>) -> std::result::Result<Self, Self::Error> {
+        Lnk::parse(&p)
+    }
+}
+
+impl TryFrom<&Vec<u8>> for Lnk {
+    type Error = crate::error::Error;
+
+    fn try_from(p: &Vec<u8>) -> std::result::Result<Self, Self::Error> {
+        Lnk::parse(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{
+        Error, ExtraDataError, HeaderError, ParseWarning, TargetResolutionError, ValidationIssue,
+    };
+    use crate::{
+        CommonNetworkRelativeLink, EnvironmentVariableDataBlock, FileAttributeFlags, FileTime,
+        Guid, LinkFlags, LinkInfoFlags, Lnk, LnkFormatFlavor, LnkOptions, PathKind,
+        ShellLinkHeader, ShimDataBlock, ShowWindow, TargetLocation, WindowsPath,
+    };
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn firefox() {
+        let path = Path::new("./test_data/firefox.lnk");
+        assert!(Lnk::try_from(path).is_ok());
+    }
+
+    #[test]
+    fn commander() {
+        let path = Path::new("./test_data/commander.lnk");
+        assert!(Lnk::try_from(path).is_ok());
+    }
+
+    #[test]
+    fn notepad() {
+        let path = Path::new("./test_data/notepad.lnk");
+        assert!(Lnk::try_from(path).is_ok());
+    }
+
+    #[test]
+    fn xp_outlook_express() {
+        let path = Path::new("./test_data/outlook_express.lnk");
+        assert!(Lnk::try_from(path).is_ok());
+    }
+
+    #[test]
+    fn is_unicode() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk.is_unicode());
+
+        lnk.header.link_flags.remove(LinkFlags::IS_UNICODE);
+        assert!(!lnk.is_unicode());
+    }
+
+    #[test]
+    fn ansi_string_data_uses_console_fe_code_page() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap()[..0x4c].to_vec();
+        data[0x14..0x18].copy_from_slice(&LinkFlags::HAS_NAME.bits().to_le_bytes());
+
+        let name = b"\xcf\xf0\xe8\xe2\xe5\xf2";
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name);
+
+        data.extend_from_slice(&0x0000_000cu32.to_le_bytes());
+        data.extend_from_slice(&0xa000_0004u32.to_le_bytes());
+        data.extend_from_slice(&1251u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let lnk = Lnk::try_from(data).unwrap();
+
+        assert_eq!(lnk.description(), Some("Привет".to_string()));
+    }
+
+    #[test]
+    fn file_attributes() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(
+            lnk.file_attributes(),
+            FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE
+        );
+        assert!(!lnk.is_directory());
+        assert!(!lnk.is_readonly());
+        assert!(!lnk.is_hidden());
+        assert!(!lnk.is_system());
+
+        lnk.header.file_attributes = FileAttributeFlags::FILE_ATTRIBUTE_DIRECTORY;
+        assert!(lnk.is_directory());
+
+        lnk.header.file_attributes = FileAttributeFlags::FILE_ATTRIBUTE_READONLY;
+        assert!(lnk.is_readonly());
+
+        lnk.header.file_attributes = FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN;
+        assert!(lnk.is_hidden());
+
+        lnk.header.file_attributes = FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM;
+        assert!(lnk.is_system());
+    }
+
+    #[test]
+    fn sanitize_for_display() {
+        let path = Path::new("./test_data/notepad.lnk");
+        let mut lnk = Lnk::try_from(path).unwrap();
+        lnk.string_data.command_line_arguments = Some("\x1b[2Jpwned\n".to_string());
+
+        let sanitized = lnk.sanitize_for_display();
+
+        assert_eq!(
+            sanitized.arguments(),
+            Some("\\u{1b}[2Jpwned\\u{a}".to_string())
+        );
+        assert_eq!(sanitized.working_dir(), lnk.working_dir());
+        assert_eq!(lnk.arguments(), sanitized.arguments());
+        assert_eq!(lnk.arguments_raw(), Some("\x1b[2Jpwned\n".to_string()));
+        assert_eq!(sanitized.extra_data, lnk.extra_data);
+    }
+
+    #[test]
+    fn accessors_escape() {
+        let path = Path::new("./test_data/notepad.lnk");
+        let mut lnk = Lnk::try_from(path).unwrap();
+        lnk.string_data.name_string = Some("note\u{202e}txt.exe".to_string());
+        lnk.string_data.command_line_arguments = Some("\"a\x1b[2J\" b".to_string());
+        lnk.string_data.working_dir = Some(PathBuf::from("C:\\\x1b]0;x\x07"));
+
+        assert_eq!(lnk.description(), Some(r"note\u{202e}txt.exe".to_string()));
+        assert_eq!(lnk.description_raw(), lnk.string_data.name_string);
+        assert_eq!(
+            lnk.arguments_split(),
+            vec![r"a\u{1b}[2J".to_string(), "b".to_string()]
+        );
+        assert_eq!(
+            lnk.arguments_split_raw(),
+            vec!["a\x1b[2J".to_string(), "b".to_string()]
+        );
+        assert_eq!(
+            lnk.working_dir(),
+            Some(PathBuf::from(r"C:\\u{1b}]0;x\u{7}"))
+        );
+        assert_eq!(lnk.working_dir_raw(), lnk.string_data.working_dir);
+        assert_eq!(lnk.target_path_raw(), lnk.target_path());
+    }
+
+    #[test]
+    fn sanitize_for_display_extra_data() {
+        let path = Path::new("./test_data/notepad.lnk");
+        let mut lnk = Lnk::try_from(path).unwrap();
+        lnk.header.link_flags.remove(LinkFlags::HAS_LINK_INFO);
+        lnk.header.link_flags.insert(LinkFlags::HAS_EXP_STRING);
+
+        lnk.extra_data.environment_props = Some(EnvironmentVariableDataBlock {
+            target_unicode: Some("%windir%\\invoice\u{202e}fdp.exe".encode_utf16().collect()),
+            ..Default::default()
+        });
+
+        let sanitized = lnk.sanitize_for_display();
+
+        assert_eq!(
+            sanitized.target_path_str(),
+            Some("%windir%\\invoice\\u{202e}fdp.exe".to_string())
+        );
+        assert!(sanitized
+            .to_string()
+            .starts_with("Target:      %windir%\\invoice\\u{202e}fdp.exe\n"));
+        assert_eq!(lnk.target_path_str(), sanitized.target_path_str());
+        assert_eq!(
+            lnk.target_path_str_raw(),
+            Some("%windir%\\invoice\u{202e}fdp.exe".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_slice() {
+        let data = std::fs::read("./test_data/notepad.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert_eq!(
+            lnk.arguments(),
+            Some(r"c:\windows\temp\test.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn equality() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let mut other = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(firefox, other);
+
+        other.string_data.command_line_arguments = Some("--private-window".to_string());
+        assert_ne!(firefox, other);
+        assert_ne!(
+            firefox,
+            Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap()
+        );
+    }
+
+    #[test]
+    fn reserved_fields() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(lnk.header.reserved_bits_clean());
+        assert!(lnk.warnings().is_empty());
+
+        data[68..72].copy_from_slice(&0xdead_beefu32.to_le_bytes());
+        let lnk = Lnk::parse(&data).unwrap();
+        assert_eq!(lnk.header.reserved2, 0xdead_beef);
+        assert!(!lnk.header.reserved_bits_clean());
+        assert_eq!(lnk.warnings(), [ParseWarning::ReservedFields]);
+        assert!(lnk.target_path().is_some());
+    }
+
+    #[test]
+    fn target_file_size() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(lnk.target_file_size(), 577_568);
+        assert!(!lnk.target_file_size_is_truncated_hint());
+
+        lnk.header.file_size = 0xffff_ffff;
+        assert!(lnk.target_file_size_is_truncated_hint());
+    }
+
+    #[test]
+    fn arguments_split() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap();
+        assert_eq!(lnk.arguments_split(), vec![r"c:\windows\temp\test.txt"]);
+
+        lnk.string_data.command_line_arguments =
+            Some(r#""C:\Users\Public\My Notes.txt" /A"#.to_string());
+        assert_eq!(
+            lnk.arguments_split(),
+            vec![r"C:\Users\Public\My Notes.txt", "/A"]
+        );
+
+        lnk.string_data.command_line_arguments = None;
+        assert!(lnk.arguments_split().is_empty());
+    }
+
+    #[test]
+    fn all_paths() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        assert_eq!(
+            lnk.all_paths(),
+            vec![
+                (
+                    PathKind::RelativePath,
+                    r"..\..\..\Program Files\Mozilla Firefox\firefox.exe".to_string()
+                ),
+                (
+                    PathKind::WorkingDir,
+                    r"C:\Program Files\Mozilla Firefox".to_string()
+                ),
+                (
+                    PathKind::LocalBasePath,
+                    r"C:\Program Files\Mozilla Firefox\firefox.exe".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        let commander = Lnk::try_from(Path::new("./test_data/commander.lnk")).unwrap();
+        let summary = commander.to_string();
+
+        assert!(summary.contains(r"Target:      C:\Windows\System32\cmd.exe"));
+        assert!(summary.contains(r"Working dir: C:\windows\system32"));
+        assert!(summary.contains("Description: Shortcut to cmd.exe, yay!"));
+        assert!(!summary.contains("Arguments:"));
+
+        let notepad = Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap();
+        assert!(notepad
+            .to_string()
+            .contains(r"Arguments:   c:\windows\temp\test.txt"));
+    }
+
+    #[test]
+    fn is_link_to_link() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(!lnk.is_link_to_link());
+
+        lnk.link_info.local_base_path = Some(r"C:\Users\Public\Desktop\Other.LNK".to_string());
+        assert!(!lnk.is_link_to_link());
+
+        lnk.header.link_flags |= LinkFlags::ALLOW_LINK_TO_LINK;
+        let chained = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert!(chained.is_link_to_link());
+    }
+
+    #[test]
+    fn link_flag_names() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        assert_eq!(
+            lnk.link_flag_names(),
+            vec![
+                "HAS_LINK_TARGET_ID_LIST",
+                "HAS_LINK_INFO",
+                "HAS_RELATIVE_PATH",
+                "HAS_WORKING_DIR",
+                "IS_UNICODE"
+            ]
+        );
+    }
+
+    #[test]
+    fn target_location() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(
+            lnk.target_location(),
+            TargetLocation::Local(PathBuf::from(
+                r"C:\Program Files\Mozilla Firefox\firefox.exe"
+            ))
+        );
+
+        lnk.link_info.link_info_flags =
+            Some(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+        lnk.link_info.common_network_relative_link = Some(CommonNetworkRelativeLink {
+            net_name: Some(r"\\server\share".to_string()),
+            device_name: Some("Z:".to_string()),
+            ..Default::default()
+        });
+        let network = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert!(network.warnings().is_empty());
+        assert_eq!(
+            network
+                .link_info
+                .common_network_relative_link
+                .as_ref()
+                .and_then(|link| link.device_name.as_deref()),
+            Some("Z:")
+        );
+        assert_eq!(
+            network.target_location(),
+            TargetLocation::Network(r"\\server\share".to_string())
+        );
+
+        lnk.link_info = Default::default();
+        let id_list_only = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert_eq!(id_list_only.target_location(), TargetLocation::IdListOnly);
+
+        lnk.link_target_id_list = Lnk::parse(&[0; 0x4c]).unwrap().link_target_id_list;
+        let unknown = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert_eq!(unknown.target_location(), TargetLocation::Unknown);
+    }
+
+    #[test]
+    fn target_extension() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(lnk.target_extension().as_deref(), Some("exe"));
+
+        // Only the ID list names the target
+        lnk.link_info = Default::default();
+        lnk.string_data.relative_path = None;
+        let id_list_only = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert_eq!(id_list_only.target_extension().as_deref(), Some("exe"));
+
+        lnk.string_data.relative_path = Some(PathBuf::from(r"..\Documents\Report.DOCX"));
+        assert_eq!(lnk.target_extension().as_deref(), Some("docx"));
+
+        lnk.string_data.relative_path = Some(PathBuf::from(r"..\.profile"));
+        assert_eq!(lnk.target_extension(), None);
+    }
+
+    #[test]
+    fn target_is_executable() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk.target_is_executable());
+
+        lnk.link_info = Default::default();
+        lnk.link_target_id_list = Default::default();
+        lnk.string_data.relative_path = Some(PathBuf::from(r"..\Documents\Report.docx"));
+        assert!(!lnk.target_is_executable());
+        assert!(lnk.target_is_one_of(&["DOCX", "xlsx"]));
+
+        lnk.string_data.relative_path = Some(PathBuf::from(r"..\run.PS1"));
+        assert!(lnk.target_is_executable());
+    }
+
+    #[test]
+    fn target_predicates() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk.is_local_target());
+        assert!(!lnk.is_network_target());
+
+        lnk.link_info.link_info_flags =
+            Some(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+        lnk.link_info.common_network_relative_link = Some(CommonNetworkRelativeLink {
+            net_name: Some(r"\\server\share".to_string()),
+            ..Default::default()
+        });
+        let network = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert!(network.is_network_target());
+        assert!(!network.is_local_target());
+
+        lnk.link_info = Default::default();
+        let id_list_only = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert!(!id_list_only.is_network_target());
+        assert!(!id_list_only.is_local_target());
+    }
+
+    #[test]
+    fn requests_elevation() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(!lnk.runs_as_user());
+        assert!(!lnk.requests_elevation());
+
+        let mut data = lnk.to_bytes();
+        data[0x15] |= (LinkFlags::RUN_AS_USER.bits() >> 8) as u8;
+        let run_as_user = Lnk::parse(&data).unwrap();
+        assert!(run_as_user.runs_as_user());
+        assert!(run_as_user.requests_elevation());
+
+        let mut layer_name = "~ HIGHDPIAWARE RUNASADMIN"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        layer_name.resize(0x80, 0);
+        lnk.extra_data.shim_props = Some(ShimDataBlock {
+            block_size: 0x88,
+            block_signature: 0xa000_0008,
+            layer_name: Some(layer_name),
+        });
+        assert!(!lnk.runs_as_user());
+        assert!(lnk.requests_elevation());
+    }
+
+    #[test]
+    fn drop_raw() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk.extra_data.raw_block(0xa000_0003).is_some());
+
+        let stripped = lnk.clone_without_raw();
+        assert_eq!(stripped.extra_data.raw_block(0xa000_0003), None);
+        assert_eq!(stripped.target_path(), lnk.target_path());
+        assert_eq!(stripped.created_by_machine(), lnk.created_by_machine());
+        assert_eq!(stripped.app_user_model_id(), lnk.app_user_model_id());
+        assert_eq!(
+            stripped.extra_data.block_sizes(),
+            lnk.extra_data.block_sizes()
+        );
+
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let path = PathBuf::from(format!("./test_data/{}.lnk", name));
+            let data = std::fs::read(&path).unwrap();
+
+            let mut lnk = Lnk::parse(&data).unwrap();
+            lnk.drop_raw();
+            assert_eq!(lnk.to_bytes(), data, "{}", name);
+        }
+    }
+
+    #[test]
+    fn content_hash() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        let mut copy = firefox.clone();
+        copy.header.creation_time += 1;
+        assert_eq!(firefox.content_hash(), copy.content_hash());
+
+        copy.string_data.command_line_arguments = Some("--private-window".to_string());
+        assert_ne!(firefox.content_hash(), copy.content_hash());
+
+        let mut icon = firefox.clone();
+        icon.string_data.icon_location = Some(PathBuf::from(r"C:\Windows\System32\shell32.dll"));
+        let mut other_index = icon.clone();
+        other_index.header.icon_index = 3;
+        assert_ne!(icon.content_hash(), other_index.content_hash());
+    }
+
+    #[test]
+    fn from_file() {
+        let expected = Lnk::try_from(Path::new("./test_data/commander.lnk")).unwrap();
+
+        assert_eq!(
+            Lnk::from_file("./test_data/commander.lnk").unwrap(),
+            expected
+        );
+        assert_eq!(
+            Lnk::from_file(String::from("./test_data/commander.lnk")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Lnk::from_file(Path::new("./test_data/commander.lnk")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Lnk::from_file(PathBuf::from("./test_data/commander.lnk")).unwrap(),
+            expected
+        );
+        assert!(Lnk::from_file("./test_data/missing.lnk").is_err());
+    }
+
+    #[test]
+    fn icon() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(lnk.icon(), None);
+
+        lnk.string_data.icon_location = Some(PathBuf::from(r"%SystemRoot%\System32\shell32.dll"));
+        lnk.header.icon_index = (-16_769i32) as u32;
+        let lnk = Lnk::parse(&lnk.to_bytes()).unwrap();
+
+        assert_eq!(lnk.icon_index(), -16_769);
+        assert_eq!(
+            lnk.icon(),
+            Some((PathBuf::from(r"%SystemRoot%\System32\shell32.dll"), -16_769))
+        );
+    }
+
+    #[test]
+    fn created_by_machine() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(
+            firefox.created_by_machine().as_deref(),
+            Some("desktop-q73bsl6")
+        );
+
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data.truncate(0x2d6);
+        data.extend_from_slice(&[0; 4]);
+        assert_eq!(Lnk::parse(&data).unwrap().created_by_machine(), None);
+    }
+
+    #[test]
+    fn app_user_model_id() {
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(
+            firefox.app_user_model_id().as_deref(),
+            Some("308046B0AF4A39CB")
+        );
+
+        let notepad = Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap();
+        assert_eq!(notepad.app_user_model_id(), None);
+    }
+
+    #[test]
+    fn flavor() {
+        for (name, flavor) in [
+            ("firefox", LnkFormatFlavor::VistaPlus),
+            ("commander", LnkFormatFlavor::VistaPlus),
+            ("notepad", LnkFormatFlavor::VistaPlus),
+            ("outlook_express", LnkFormatFlavor::Legacy),
+        ] {
+            let path = PathBuf::from(format!("./test_data/{}.lnk", name));
+            assert_eq!(
+                Lnk::try_from(path.as_path()).unwrap().flavor(),
+                flavor,
+                "{}",
+                name
+            );
+        }
+
+        assert_eq!(
+            Lnk::default().flavor(),
+            LnkFormatFlavor::ThirdPartyGenerator
+        );
+
+        // ANSI StringData marks a Windows 9x or NT link, even with Vista structures present
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.header.link_flags.remove(LinkFlags::IS_UNICODE);
+        let mut lnk = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert!(!lnk.is_unicode());
+        assert_eq!(lnk.flavor(), LnkFormatFlavor::Legacy);
+
+        lnk.header.header_size = 0x50;
+        assert_eq!(lnk.flavor(), LnkFormatFlavor::ThirdPartyGenerator);
+
+        assert_eq!(super::version(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn show_window() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(lnk.show_window(), ShowWindow::Normal);
+
+        let data = lnk.to_bytes();
+        for (bits, show_window) in [
+            (1u32, ShowWindow::Normal),
+            (3, ShowWindow::Maximized),
+            (7, ShowWindow::MinimizedNoActivate),
+            (0, ShowWindow::Unknown(0)),
+            (2, ShowWindow::Unknown(2)),
+            (10, ShowWindow::Unknown(10)),
+            (11, ShowWindow::Unknown(11)),
+            (0x10, ShowWindow::Unknown(0x10)),
+        ] {
+            let mut data = data.clone();
+            data[0x3c..0x40].copy_from_slice(&bits.to_le_bytes());
+
+            lnk = Lnk::parse(&data).unwrap();
+            assert_eq!(lnk.show_window(), show_window);
+            assert_eq!(lnk.header.show_command_raw, bits);
+            assert_eq!(lnk.to_bytes(), data);
+        }
+    }
+
+    #[test]
+    fn empty_arguments() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/commander.lnk")).unwrap();
+        assert!(!lnk.has_arguments());
+        assert_eq!(lnk.arguments(), None);
+
+        lnk.string_data.command_line_arguments = Some(String::new());
+        let lnk = Lnk::parse(&lnk.to_bytes()).unwrap();
+
+        assert!(lnk.has_arguments());
+        assert_eq!(lnk.arguments(), Some(String::new()));
+        assert!(lnk.has_working_dir());
+    }
+
+    #[test]
+    fn flatten() {
+        let flat = Lnk::try_from(Path::new("./test_data/firefox.lnk"))
+            .unwrap()
+            .flatten();
+
+        assert_eq!(
+            flat.target.as_deref(),
+            Some(r"C:\Program Files\Mozilla Firefox\firefox.exe")
+        );
+        assert_eq!(
+            flat.working_dir.as_deref(),
+            Some(r"C:\Program Files\Mozilla Firefox")
+        );
+        assert_eq!(flat.machine_id.as_deref(), Some("desktop-q73bsl6"));
+        assert_eq!(flat.mac_address.as_deref(), Some("08:00:27:D7:FC:D0"));
+        assert_eq!(flat.file_size, Some(577568));
+        assert_eq!(flat.arguments, None);
+    }
+
+    #[test]
+    fn cmp_by_modified() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        let mut lnks = [lnk.clone(), lnk.clone(), lnk];
+        lnks[0].header.write_time = 0x01d5_0000_0000_0000;
+        lnks[1].header.write_time = 0;
+        lnks[2].header.write_time = 0x01d4_0000_0000_0000;
+        lnks.sort_by(Lnk::cmp_by_modified);
+
+        assert_eq!(
+            lnks.iter().map(Lnk::write_time).collect::<Vec<_>>(),
+            vec![0, 0x01d4_0000_0000_0000, 0x01d5_0000_0000_0000]
+        );
+    }
+
+    #[test]
+    fn filetime_split() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        for time in &[
+            lnk.creation_filetime(),
+            lnk.access_filetime(),
+            lnk.write_filetime(),
+        ] {
+            assert_ne!(time.high, 0);
+        }
+        assert_eq!(lnk.creation_filetime().to_u64(), lnk.creation_time());
+        assert_eq!(lnk.access_filetime().to_u64(), lnk.access_time());
+        assert_eq!(lnk.write_filetime().to_u64(), lnk.write_time());
+
+        let time = FileTime::from(0x01d5_a3b2_1234_5678);
+        assert_eq!(time.high, 0x01d5_a3b2);
+        assert_eq!(time.low, 0x1234_5678);
+        assert_eq!(u64::from(time), 0x01d5_a3b2_1234_5678);
+
+        #[cfg(feature = "chrono")]
+        assert_eq!(Some(lnk.write_filetime().to_datetime()), lnk.modified_on());
+    }
+
+    #[test]
+    fn zero_time_is_not_set() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data[0x24..0x2c].copy_from_slice(&[0; 8]);
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert_eq!(lnk.access_time(), 0);
+        assert_eq!(lnk.access_time_opt(), None);
+        assert_eq!(lnk.creation_time_opt(), Some(lnk.creation_time()));
+        assert_eq!(lnk.write_time_opt(), Some(lnk.write_time()));
+
+        #[cfg(feature = "chrono")]
+        {
+            assert_eq!(lnk.accessed_on(), None);
+            assert!(lnk.created_on().is_some());
+            assert!(lnk.modified_on().is_some());
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn filetime_datetime_round_trip() {
+        use chrono::{TimeZone, Utc};
+
+        let epoch = Utc.with_ymd_and_hms(1601, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(FileTime::from(0).to_datetime(), epoch);
+        assert_eq!(FileTime::from_datetime(&epoch), Some(FileTime::from(0)));
+        assert_eq!(
+            FileTime::from_datetime(&(epoch - chrono::Duration::nanoseconds(100))),
+            None
+        );
+
+        let y2100 = Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap();
+        let time = FileTime::from_datetime(&y2100).unwrap();
+        assert_eq!(time.to_u64(), 0x022f_7163_7764_0000);
+        assert_eq!(time.to_datetime(), y2100);
+
+        // Every tick up to the year 2100 and beyond, sampled pseudo-randomly
+        let mut state = 0x853c_49e6_748f_ea9bu64;
+        let mut values = vec![1, 9_999_999, u64::from(time), u64::from(time) + 1];
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            values.push(state % 0x0400_0000_0000_0000);
+        }
+
+        for value in values {
+            let time = FileTime::from(value);
+            assert_eq!(
+                FileTime::from_datetime(&time.to_datetime()),
+                Some(time),
+                "0x{:016x}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn default() {
+        let data = Lnk::default().to_bytes();
+
+        assert_eq!(data.len(), 0x4c + 4);
+        assert_eq!(&data[..4], &0x4cu32.to_le_bytes());
+        assert_eq!(
+            &data[4..20],
+            &[0x01, 0x14, 0x02, 0, 0, 0, 0, 0, 0xc0, 0, 0, 0, 0, 0, 0, 0x46]
+        );
+        assert_eq!(&data[20..24], &[0; 4]);
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert_eq!(lnk.header.link_clsid, ShellLinkHeader::LINK_CLSID);
+        assert_eq!(
+            lnk.header.link_clsid.to_string(),
+            "{00021401-0000-0000-C000-000000000046}"
+        );
+        assert_eq!(lnk.to_bytes(), data);
+        assert!(lnk.warnings().is_empty());
+    }
+
+    #[test]
+    fn too_large() {
+        let result = Lnk::new(&mut std::io::repeat(0));
+        assert!(matches!(
+            result,
+            Err(Error::HeaderError(HeaderError::TooLarge(
+                LnkOptions::DEFAULT_MAX_SIZE
+            )))
+        ));
+
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let options = LnkOptions {
+            max_size: data.len() as u64,
+            ..Default::default()
+        };
+        assert!(Lnk::from_reader_with_options(&mut data.as_slice(), &options).is_ok());
+
+        let options = LnkOptions {
+            max_size: data.len() as u64 - 1,
+            ..Default::default()
+        };
+        assert!(Lnk::from_reader_with_options(&mut data.as_slice(), &options).is_err());
+    }
+
+    #[test]
+    fn new_strict() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let mut file = std::fs::File::open(format!("./test_data/{}.lnk", name)).unwrap();
+            assert!(Lnk::new_strict(&mut file).is_ok(), "{}", name);
+        }
+
+        data[0x44] = 1;
+        assert!(Lnk::new(&mut data.as_slice()).is_ok());
+        assert!(matches!(
+            Lnk::new_strict(&mut data.as_slice()),
+            Err(Error::Nonconforming(issues)) if issues == [ValidationIssue::ReservedFields]
+        ));
+
+        data[0x44] = 0;
+        data.extend_from_slice(&[0; 4]);
+        assert!(matches!(
+            Lnk::new_strict(&mut data.as_slice()),
+            Err(Error::Nonconforming(issues))
+                if issues == [ValidationIssue::Recovered(ParseWarning::TrailingData(4))]
+        ));
+    }
+
+    #[test]
+    fn try_from_file() {
+        let expected = Lnk::parse(&std::fs::read("./test_data/firefox.lnk").unwrap()).unwrap();
+
+        let file = std::fs::File::open("./test_data/firefox.lnk").unwrap();
+        assert_eq!(Lnk::try_from(&file).unwrap(), expected);
+
+        let file = std::fs::File::open("./test_data/firefox.lnk").unwrap();
+        assert_eq!(Lnk::try_from(file).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_many() {
+        let firefox = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let notepad = std::fs::read("./test_data/notepad.lnk").unwrap();
+        let data = [firefox.as_slice(), notepad.as_slice()].concat();
+
+        let lnks = super::parse_many(&data).collect::<Vec<_>>();
+        assert_eq!(lnks.len(), 2);
+        assert_eq!(*lnks[0].as_ref().unwrap(), Lnk::parse(&firefox).unwrap());
+        assert_eq!(*lnks[1].as_ref().unwrap(), Lnk::parse(&notepad).unwrap());
+
+        let truncated = &data[..firefox.len() + 0x20];
+        let lnks = super::parse_many(truncated).collect::<Vec<_>>();
+        assert_eq!(lnks.len(), 2);
+        assert!(lnks[0].is_ok());
+        assert!(lnks[1].is_err());
+    }
+
+    #[test]
+    fn from_bytes_at() {
+        let lnk = std::fs::read("./test_data/notepad.lnk").unwrap();
+        let mut data = b"some unrelated container data".to_vec();
+        let offset = data.len();
+        data.extend_from_slice(&lnk);
+        data.extend_from_slice(&[0xff; 16]);
+
+        let carved = Lnk::from_bytes_at(&data, offset).unwrap();
+        assert_eq!(
+            carved.target_path(),
+            Lnk::parse(&lnk).unwrap().target_path()
+        );
+
+        for offset in [offset - 1, offset + 1, data.len() + 1] {
+            let err = Lnk::from_bytes_at(&data, offset).unwrap_err();
+            assert!(matches!(
+                err,
+                crate::error::Error::HeaderError(HeaderError::InvalidSignature(o)) if o == offset
+            ));
+        }
+
+        // The TerminalBlock is cut short, and the failed read is reported in `data`
+        let options = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let truncated = &data[..offset + lnk.len() - 3];
+        assert!(matches!(
+            Lnk::parse_with_options(&lnk[..lnk.len() - 3], &options),
+            Err(Error::ExtraDataError(ExtraDataError::Read {
+                offset: 0x554,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            Lnk::from_bytes_at_with_options(truncated, offset, &options),
+            Err(Error::ExtraDataError(ExtraDataError::Read { offset: o, .. }))
+                if o == offset as u64 + 0x554
+        ));
+    }
+
+    #[test]
+    fn validate() {
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let path = PathBuf::from(format!("./test_data/{}.lnk", name));
+            assert_eq!(
+                Lnk::try_from(path.as_path()).unwrap().validate(),
+                Ok(()),
+                "{}",
+                name
+            );
+        }
+
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        data[0x04] ^= 0xff;
+        data[0x44] = 1;
+        let link_info = 78 + u16::from_le_bytes([data[76], data[77]]) as usize;
+        data[link_info + 0x10..link_info + 0x14].copy_from_slice(&0x5bu32.to_le_bytes());
+        data[0x2d6..0x2da].copy_from_slice(&0x70u32.to_le_bytes());
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert_eq!(
+            lnk.validate(),
+            Err(vec![
+                ValidationIssue::InvalidClsid(Guid::from(
+                    ShellLinkHeader::LINK_CLSID.to_u128() ^ 0xff
+                )),
+                ValidationIssue::ReservedFields,
+                ValidationIssue::InvalidLinkInfoOffset("LocalBasePath", 0x5b),
+                ValidationIssue::InvalidBlockSize {
+                    signature: 0xa000_0003,
+                    expected: 0x60,
+                    found: 0x70,
+                },
+            ])
+        );
+        assert_eq!(lnk.clone_without_raw().validate(), lnk.validate());
+    }
+
+    #[test]
+    fn to_bytes_round_trips() {
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let path = PathBuf::from(format!("./test_data/{}.lnk", name));
+            let data = std::fs::read(&path).unwrap();
+
+            assert_eq!(Lnk::parse(&data).unwrap().to_bytes(), data, "{}", name);
+        }
+    }
+
+    #[test]
+    fn mutated_input_does_not_panic() {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let data = std::fs::read(format!("./test_data/{}.lnk", name)).unwrap();
+
+            for _ in 0..2000 {
+                let mut mutated = data.clone();
+                for _ in 0..1 + next() % 8 {
+                    let at = next() as usize % mutated.len();
+                    mutated[at] = next() as u8;
+                }
+                mutated.truncate(next() as usize % (data.len() + 1));
+
+                if let Ok(lnk) = Lnk::parse(&mutated) {
+                    lnk.to_bytes();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn save_to_path() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.string_data.command_line_arguments = Some("--private-window".to_string());
+        lnk.link_info.local_base_path = Some(r"D:\Firefox\firefox.exe".to_string());
+
+        let dir = std::env::temp_dir().join(format!("parselnk-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("firefox.lnk");
+
+        lnk.save_to_path(&path).unwrap();
+        let saved = Lnk::try_from(path.as_path()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(saved.target_path(), lnk.target_path());
+        assert_eq!(saved.arguments(), Some("--private-window".to_string()));
+        assert_eq!(saved.working_dir(), lnk.working_dir());
+    }
+
+    #[test]
+    fn relative_path_resolved() {
+        let dir = std::env::temp_dir().join(format!("parselnk-{}-relative", std::process::id()));
+        let lnk_dir = dir.join("Users").join("me").join("Desktop");
+        std::fs::create_dir_all(&lnk_dir).unwrap();
+        std::fs::copy("./test_data/firefox.lnk", lnk_dir.join("firefox.lnk")).unwrap();
+
+        let lnk = Lnk::try_from(lnk_dir.join("firefox.lnk")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            lnk.relative_path_resolved(),
+            Some(
+                dir.join("Program Files")
+                    .join("Mozilla Firefox")
+                    .join("firefox.exe")
+            )
+        );
+
+        let parsed = Lnk::parse(&std::fs::read("./test_data/firefox.lnk").unwrap()).unwrap();
+        assert_eq!(parsed.relative_path_resolved(), None);
+    }
+
+    #[test]
+    fn target_path() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        assert_eq!(
+            lnk.target_path(),
+            Some(PathBuf::from(
+                r"C:\Program Files\Mozilla Firefox\firefox.exe"
+            ))
+        );
+    }
+
+    #[test]
+    fn target_path_str() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let target = WindowsPath::new(lnk.target_path_str().unwrap());
+
+        assert_eq!(
+            target.as_str(),
+            r"C:\Program Files\Mozilla Firefox\firefox.exe"
+        );
+        assert_eq!(target.drive_letter(), Some('C'));
+        assert_eq!(
+            target.components().collect::<Vec<_>>(),
+            ["Program Files", "Mozilla Firefox", "firefox.exe"]
+        );
+    }
+
+    #[test]
+    fn empty_unicode_base_path() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.link_info.local_base_path = Some(r"C:\Windows\notepad.exe".to_string());
+        lnk.link_info.local_base_path_unicode = Some(String::new());
+        lnk.link_info.common_path_suffix = Some(String::new());
+        lnk.link_info.common_path_suffix_unicode = Some(String::new());
+
+        assert_eq!(
+            lnk.target_path_str().as_deref(),
+            Some(r"C:\Windows\notepad.exe")
+        );
+    }
+
+    #[test]
+    fn target_path_expanded() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/notepad.lnk")).unwrap();
+        let env = std::iter::once(("WINDIR".to_string(), r"D:\Windows".to_string()))
+            .collect::<HashMap<_, _>>();
+
+        assert_eq!(lnk.target_path_expanded(&env), lnk.target_path());
+
+        let mut target_unicode = r"%windir%\system32\notepad.exe"
+            .encode_utf16()
+            .collect::<Vec<u16>>();
+        target_unicode.resize(260, 0);
+        lnk.extra_data.environment_props = Some(EnvironmentVariableDataBlock {
+            block_size: 0x0000_0314,
+            block_signature: 0xa000_0001,
+            target_unicode: Some(target_unicode),
+            ..Default::default()
+        });
+        assert_eq!(lnk.target_path_expanded(&env), lnk.target_path());
+
+        lnk.header.link_flags |= LinkFlags::HAS_EXP_STRING;
+        assert_eq!(
+            lnk.target_path_expanded(&env),
+            Some(PathBuf::from(r"D:\Windows\system32\notepad.exe"))
+        );
+    }
+
+    #[test]
+    fn environment_target_fallback() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.header.link_flags.remove(LinkFlags::HAS_LINK_INFO);
+
+        let mut target_unicode = r"%ProgramFiles%\Microsoft Office\root\Office16\WINWORD.EXE"
+            .encode_utf16()
+            .collect::<Vec<u16>>();
+        target_unicode.resize(260, 0);
+        lnk.extra_data.environment_props = Some(EnvironmentVariableDataBlock {
+            block_size: 0x0000_0314,
+            block_signature: 0xa000_0001,
+            target_unicode: Some(target_unicode),
+            ..Default::default()
+        });
+        assert!(matches!(
+            lnk.try_target_path(),
+            Err(TargetResolutionError::NoLinkInfo)
+        ));
+
+        lnk.header.link_flags |= LinkFlags::HAS_EXP_STRING;
+        assert_eq!(
+            lnk.target_path_str().as_deref(),
+            Some(r"%ProgramFiles%\Microsoft Office\root\Office16\WINWORD.EXE")
+        );
+        assert_eq!(lnk.target_extension().as_deref(), Some("exe"));
+        assert_eq!(
+            lnk.target_location(),
+            TargetLocation::Environment(
+                r"%ProgramFiles%\Microsoft Office\root\Office16\WINWORD.EXE".to_string()
+            )
+        );
+        assert!(lnk.has_usable_target());
+        assert_eq!(lnk.flatten().target, lnk.target_path_str());
+    }
+
+    #[test]
+    fn id_list_only() {
+        // The Control Panel root folder, {21EC2020-3AEA-1069-A2DD-08002B30309D}, under My Computer
+        let mut item = vec![0x14, 0x00, 0x1f, 0x50];
+        item.extend_from_slice(&[
+            0x20, 0x20, 0xec, 0x21, 0xea, 0x3a, 0x69, 0x10, 0xa2, 0xdd, 0x08, 0x00, 0x2b, 0x30,
+            0x30, 0x9d,
+        ]);
+
+        let mut data = Lnk::default().to_bytes();
+        data[0x14] = LinkFlags::HAS_LINK_TARGET_ID_LIST.bits() as u8;
+        let mut id_list = ((item.len() + 2) as u16).to_le_bytes().to_vec();
+        id_list.extend_from_slice(&item);
+        id_list.extend_from_slice(&[0, 0]);
+        data.splice(0x4c..0x4c, id_list);
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(lnk.warnings().is_empty());
+        assert_eq!(lnk.link_target_id_list.item_ids().len(), 1);
+        assert_eq!(lnk.target_path(), None);
+        assert_eq!(lnk.target_location(), TargetLocation::IdListOnly);
+        assert!(!lnk.has_usable_target());
+
+        let firefox = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(firefox.has_usable_target());
+    }
+
+    #[test]
+    fn force_no_link_info() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        assert!(lnk.link_info_is_honored());
+
+        lnk.header.link_flags |= LinkFlags::FORCE_NO_LINK_INFO;
+        assert!(!lnk.link_info_is_honored());
+        assert!(lnk.link_info.local_base_path.is_some());
+        assert_eq!(lnk.target_path(), None);
+        assert!(matches!(
+            lnk.try_target_path(),
+            Err(TargetResolutionError::LinkInfoIgnored)
+        ));
+        assert_eq!(lnk.target_location(), TargetLocation::IdListOnly);
+    }
+
+    #[test]
+    fn network_target_path() {
+        let mut lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.link_info.link_info_flags =
+            Some(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+        lnk.link_info.common_path_suffix = Some(r"dir\file.txt".to_string());
+        lnk.link_info.common_network_relative_link = Some(CommonNetworkRelativeLink {
+            net_name: Some(r"\\SERVER\SHARE".to_string()),
+            net_name_unicode: Some(r"\\server\share".to_string()),
+            ..Default::default()
+        });
+
+        let network = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert_eq!(
+            network.target_path_str().as_deref(),
+            Some(r"\\server\share\dir\file.txt")
+        );
+        assert_eq!(
+            network.target_path(),
+            Some(PathBuf::from(r"\\server\share\dir\file.txt"))
+        );
+        assert_eq!(
+            network.target_location(),
+            TargetLocation::Network(r"\\server\share\dir\file.txt".to_string())
+        );
+
+        lnk.link_info.common_path_suffix = Some(r"\dir\file.txt".to_string());
+        assert_eq!(
+            lnk.target_path_str().as_deref(),
+            Some(r"\\server\share\dir\file.txt")
+        );
+    }
+
+    #[test]
+    fn target_path_failures() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+
+        let mut no_link_info = lnk.clone();
+        no_link_info
+            .header
+            .link_flags
+            .remove(LinkFlags::HAS_LINK_INFO);
+        assert!(matches!(
+            no_link_info.try_target_path(),
+            Err(TargetResolutionError::NoLinkInfo)
+        ));
+
+        let mut no_local_base_path = lnk.clone();
+        no_local_base_path.link_info.link_info_flags = Some(LinkInfoFlags::empty());
+        assert!(matches!(
+            no_local_base_path.try_target_path(),
+            Err(TargetResolutionError::NoLocalBasePath)
+        ));
+
+        let mut network_only = lnk.clone();
+        network_only.link_info.link_info_flags =
+            Some(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+        assert!(matches!(
+            network_only.try_target_path(),
+            Err(TargetResolutionError::NetworkOnly)
+        ));
+
+        let mut empty = lnk;
+        empty.link_info.local_base_path = Some(String::new());
+        assert!(matches!(
+            empty.try_target_path(),
+            Err(TargetResolutionError::EmptyPath)
+        ));
     }
 }
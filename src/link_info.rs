@@ -4,12 +4,17 @@
 //!
 
 use super::Result;
-use crate::{error::LinkInfoError, header::ShellLinkHeader, LinkFlags};
+use crate::{
+    code_page,
+    error::{LinkInfoError, ParseWarning},
+    header::ShellLinkHeader,
+    LinkFlags, LnkOptions,
+};
 use bitflags::bitflags;
 use byteorder::{ReadBytesExt, LE};
 use std::io::{Cursor, Read};
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 /// The LinkInfo structure specifies information necessary to resolve a link target if it is not found in its
 /// original location. This includes information about the volume that the target was stored on, the
 /// mapped drive letter, and a Universal Naming Convention (UNC) form of the path if one existed
@@ -35,7 +40,6 @@ pub struct LinkInfo {
     /// A 32-bit, unsigned integer that specifies the location of the VolumeID
     /// field. If the VolumeIDAndLocalBasePath flag is set, this value is an offset, in bytes, from the
     /// start of the LinkInfo structure; otherwise, this value MUST be zero.
-    #[allow(dead_code)]
     volume_id_offset: u32,
 
     /// A 32-bit, unsigned integer that specifies the location of the
@@ -70,7 +74,7 @@ pub struct LinkInfo {
     /// An optional VolumeID structure (section 2.3.1) that specifies information
     /// about the volume that the link target was on when the link was created. This field is present if
     /// the VolumeIDAndLocalBasePath flag is set.
-    pub volume_id: Option<()>,
+    pub volume_id: Option<VolumeId>,
 
     /// An optional, NULL–terminated string, defined by the system default code
     /// page, which is used to construct the full path to the link item or link target by appending the
@@ -81,7 +85,7 @@ pub struct LinkInfo {
     /// An optional CommonNetworkRelativeLink structure
     /// (section 2.3.2) that specifies information about the network location where the link target is
     /// stored.
-    pub common_network_relative_link: Option<()>,
+    pub common_network_relative_link: Option<CommonNetworkRelativeLink>,
 
     /// A NULL–terminated string, defined by the system default code
     /// page, which is used to construct the full path to the link item or link target by being appended to
@@ -117,9 +121,678 @@ bitflags! {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The VolumeID structure specifies information about the volume that a link target was on when the
+/// link was created. This information is useful for resolving the link if the file is not found in its
+/// original location.
+pub struct VolumeId {
+    /// A 32-bit, unsigned integer that specifies the size, in bytes, of this
+    /// structure. This value MUST be greater than 0x00000010. All offsets specified in this structure
+    /// MUST be less than this value, and all strings contained in this structure MUST fit within the
+    /// extent defined by this size.
+    pub volume_id_size: u32,
+
+    /// A 32-bit, unsigned integer that specifies the type of drive the link target is
+    /// stored on.
+    pub drive_type: u32,
+
+    /// A 32-bit, unsigned integer that specifies the drive serial number of
+    /// the volume the link target is stored on.
+    pub drive_serial_number: u32,
+
+    /// A 32-bit, unsigned integer that specifies the location of a string that
+    /// contains the volume label of the drive that the link target is stored on. This value is an offset,
+    /// in bytes, from the start of the VolumeID structure to a NULL-terminated string of characters,
+    /// defined by the system default code page. The volume label string is located in the Data field of
+    /// this structure.
+    ///
+    /// If the value of this field is 0x00000014, it MUST be ignored, and the value of the
+    /// VolumeLabelOffsetUnicode field MUST be used to locate the volume label string.
+    pub volume_label_offset: u32,
+
+    /// An optional, 32-bit, unsigned integer that specifies the
+    /// location of a string that contains the volume label of the drive that the link target is stored on.
+    /// This value is an offset, in bytes, from the start of the VolumeID structure to a NULL-terminated
+    /// string of Unicode characters. The volume label string is located in the Data field of this
+    /// structure.
+    ///
+    /// If the value of the VolumeLabelOffset field is not 0x00000014, this field MUST NOT
+    /// be present; instead, the value of the VolumeLabelOffset field MUST be used to locate the
+    /// volume label string.
+    pub volume_label_offset_unicode: Option<u32>,
+
+    /// The label of the volume that the link target is stored on.
+    pub volume_label: Option<String>,
+}
+
+impl VolumeId {
+    /// Construct a new `VolumeId` from the data in `cursor` located at `from`. The
+    /// structure must fit before `limit`, the end of the enclosing `LinkInfo`. An ANSI volume
+    /// label is decoded using `code_page`.
+    fn new(
+        cursor: &mut Cursor<&[u8]>,
+        from: u64,
+        limit: u64,
+        code_page: Option<u32>,
+    ) -> std::result::Result<Self, LinkInfoError> {
+        let reset = cursor.position();
+        cursor.set_position(from);
+
+        let result = Self::read(cursor, from, limit, code_page);
+
+        cursor.set_position(reset);
+        result
+    }
+
+    fn read(
+        cursor: &mut Cursor<&[u8]>,
+        from: u64,
+        limit: u64,
+        code_page: Option<u32>,
+    ) -> std::result::Result<Self, LinkInfoError> {
+        let mut this = Self {
+            volume_id_size: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            drive_type: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            drive_serial_number: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            volume_label_offset: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            volume_label_offset_unicode: None,
+            volume_label: None,
+        };
+
+        if this.volume_id_size <= 0x0000_0010 || from + this.volume_id_size as u64 > limit {
+            return Err(LinkInfoError::InvalidVolumeId(this.volume_id_size));
+        }
+
+        if this.volume_label_offset == 0x0000_0014 {
+            let offset = cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?;
+            this.volume_label_offset_unicode = Some(offset);
+
+            if offset < this.volume_id_size {
+                let begin = from + offset as u64;
+                let end = from + this.volume_id_size as u64;
+
+                check_available(cursor, begin, end - begin)?;
+                let mut data = vec![0; (end - begin) as usize];
+                cursor.set_position(begin);
+                cursor.read_exact(&mut data).map_err(LinkInfoError::Read)?;
+
+                let wide = data
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                    .take_while(|c| *c != 0)
+                    .collect::<Vec<u16>>();
+                this.volume_label = Some(String::from_utf16_lossy(&wide));
+            }
+        } else if this.volume_label_offset < this.volume_id_size {
+            let begin = from + this.volume_label_offset as u64;
+            let end = from + this.volume_id_size as u64;
+
+            check_available(cursor, begin, end - begin)?;
+            let mut data = vec![0; (end - begin) as usize];
+            cursor.set_position(begin);
+            cursor.read_exact(&mut data).map_err(LinkInfoError::Read)?;
+
+            let len = data.iter().position(|c| *c == 0).unwrap_or(data.len());
+            this.volume_label = Some(code_page::decode(&data[..len], code_page));
+        }
+
+        Ok(this)
+    }
+}
+
+impl VolumeId {
+    /// Serialize the structure into `buf`. The volume label is stored as a unicode string
+    /// when `volume_label_offset_unicode` is set, and encoded using `code_page` otherwise.
+    fn write(&self, buf: &mut Vec<u8>, code_page: Option<u32>) {
+        let label = self.volume_label.as_deref().unwrap_or("");
+
+        let (label_offset, volume_label) = if self.volume_label_offset_unicode.is_some() {
+            let mut volume_label = 0x0000_0014u32.to_le_bytes().to_vec();
+            volume_label.extend(
+                label
+                    .encode_utf16()
+                    .chain(Some(0))
+                    .flat_map(u16::to_le_bytes),
+            );
+            (0x0000_0014u32, volume_label)
+        } else {
+            let mut volume_label = code_page::encode(label, code_page);
+            volume_label.push(0);
+            (0x0000_0010u32, volume_label)
+        };
+
+        buf.extend_from_slice(&(0x0000_0010 + volume_label.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.drive_type.to_le_bytes());
+        buf.extend_from_slice(&self.drive_serial_number.to_le_bytes());
+        buf.extend_from_slice(&label_offset.to_le_bytes());
+        buf.extend_from_slice(&volume_label);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The CommonNetworkRelativeLink structure specifies information about the network location where a
+/// link target is stored, including the mapped drive letter and the UNC path prefix. For details on
+/// UNC paths, see [MS-DFSNM] section 2.2.1.4.
+pub struct CommonNetworkRelativeLink {
+    /// A 32-bit, unsigned integer that specifies the size, in bytes, of the
+    /// CommonNetworkRelativeLink structure. This value MUST be greater than or equal to
+    /// 0x00000014. All offsets specified in this structure MUST be less than this value, and all
+    /// strings contained in this structure MUST fit within the extent defined by this size.
+    pub common_network_relative_size: u32,
+
+    /// Flags that specify the contents of the DeviceNameOffset and
+    /// NetProviderType fields.
+    pub common_network_relative_link_flags: CommonNetworkRelativeLinkFlags,
+
+    /// A 32-bit, unsigned integer that specifies the location of the NetName field. This
+    /// value is an offset, in bytes, from the start of the CommonNetworkRelativeLink structure.
+    pub net_name_offset: u32,
+
+    /// A 32-bit, unsigned integer that specifies the location of the DeviceName
+    /// field. If the ValidDevice flag is set, this value is an offset, in bytes, from the start of the
+    /// CommonNetworkRelativeLink structure; otherwise, this value MUST be zero.
+    pub device_name_offset: u32,
+
+    /// A 32-bit, unsigned integer that specifies the type of network
+    /// provider. If the ValidNetType flag is set, this value MUST be one of the WNNC_NET_* values;
+    /// otherwise, this value MUST be ignored.
+    pub network_provider_type: u32,
+
+    /// An optional, 32-bit, unsigned integer that specifies the location of the
+    /// NetNameUnicode field. This field MUST be present if the value of the NetNameOffset field is
+    /// greater than 0x00000014; otherwise, this field MUST NOT be present.
+    pub net_name_offset_unicode: Option<u32>,
+
+    /// An optional, 32-bit, unsigned integer that specifies the location of the
+    /// DeviceNameUnicode field. This field MUST be present if the value of the NetNameOffset field is
+    /// greater than 0x00000014; otherwise, this field MUST NOT be present.
+    pub device_name_offset_unicode: Option<u32>,
+
+    /// A NULL–terminated string, as defined by the system default code page, which specifies a
+    /// server share path; for example, "\\server\share".
+    pub net_name: Option<String>,
+
+    /// A NULL–terminated string, as defined by the system default code page, which specifies a
+    /// device; for example, the drive letter "D:". This is only read when the ValidDevice
+    /// flag is set, as the offset is otherwise meaningless.
+    pub device_name: Option<String>,
+
+    /// An optional, NULL–terminated, Unicode string that is the Unicode version of the NetName
+    /// string.
+    pub net_name_unicode: Option<String>,
+
+    /// An optional, NULL–terminated, Unicode string that is the Unicode version of the
+    /// DeviceName string. Like `device_name`, this is only read when the ValidDevice flag is
+    /// set.
+    pub device_name_unicode: Option<String>,
+}
+
+bitflags! {
+    /// Flags that specify the contents of the DeviceNameOffset and NetProviderType fields of
+    /// the CommonNetworkRelativeLink.
+    #[derive(Default)]
+    pub struct CommonNetworkRelativeLinkFlags: u32 {
+        /// If set, the DeviceNameOffset field contains an offset to the device name.
+        ///
+        /// If not set, the DeviceNameOffset field does not contain an offset to the device name, and its value MUST be zero.
+        const VALID_DEVICE = 0b0000_0000_0000_0000_0000_0000_0000_0001;
+
+        /// If set, the NetProviderType field contains the network provider type.
+        ///
+        /// If not set, the NetProviderType field does not contain the network provider type, and its value MUST be zero.
+        const VALID_NET_TYPE = 0b0000_0000_0000_0000_0000_0000_0000_0010;
+    }
+}
+
+/// The type of network provider of a `CommonNetworkRelativeLink`, one of the WNNC_NET_* values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NetworkProviderType {
+    /// WNNC_NET_LANMAN, the Microsoft SMB redirector
+    LanMan,
+
+    /// WNNC_NET_AVID
+    Avid,
+
+    /// WNNC_NET_DOCUSPACE
+    Docuspace,
+
+    /// WNNC_NET_MANGOSOFT
+    Mangosoft,
+
+    /// WNNC_NET_SERNET
+    Sernet,
+
+    /// WNNC_NET_RIVERFRONT1
+    Riverfront1,
+
+    /// WNNC_NET_RIVERFRONT2
+    Riverfront2,
+
+    /// WNNC_NET_DECORB
+    Decorb,
+
+    /// WNNC_NET_PROTSTOR
+    Protstor,
+
+    /// WNNC_NET_FJ_REDIR
+    FjRedir,
+
+    /// WNNC_NET_DISTINCT
+    Distinct,
+
+    /// WNNC_NET_TWINS
+    Twins,
+
+    /// WNNC_NET_RDR2SAMPLE
+    Rdr2Sample,
+
+    /// WNNC_NET_CSC
+    Csc,
+
+    /// WNNC_NET_3IN1
+    ThreeInOne,
+
+    /// WNNC_NET_EXTENDNET
+    ExtendNet,
+
+    /// WNNC_NET_STAC
+    Stac,
+
+    /// WNNC_NET_FOXBAT
+    Foxbat,
+
+    /// WNNC_NET_YAHOO
+    Yahoo,
+
+    /// WNNC_NET_EXIFS
+    Exifs,
+
+    /// WNNC_NET_DAV, WebDAV
+    Dav,
+
+    /// WNNC_NET_KNOWARE
+    Knoware,
+
+    /// WNNC_NET_OBJECT_DIRE
+    ObjectDire,
+
+    /// WNNC_NET_MASFAX
+    Masfax,
+
+    /// WNNC_NET_HOB_NFS
+    HobNfs,
+
+    /// WNNC_NET_SHIVA
+    Shiva,
+
+    /// WNNC_NET_IBMAL
+    Ibmal,
+
+    /// WNNC_NET_LOCK
+    Lock,
+
+    /// WNNC_NET_TERMSRV, Remote Desktop drive redirection
+    Termsrv,
+
+    /// WNNC_NET_SRT
+    Srt,
+
+    /// WNNC_NET_QUINCY
+    Quincy,
+
+    /// WNNC_NET_OPENAFS
+    OpenAfs,
+
+    /// WNNC_NET_AVID1
+    Avid1,
+
+    /// WNNC_NET_DFS, the Distributed File System
+    Dfs,
+
+    /// WNNC_NET_KWNP
+    Kwnp,
+
+    /// WNNC_NET_ZENWORKS
+    Zenworks,
+
+    /// WNNC_NET_DRIVEONWEB
+    DriveOnWeb,
+
+    /// WNNC_NET_VMWARE
+    Vmware,
+
+    /// WNNC_NET_RSFX
+    Rsfx,
+
+    /// WNNC_NET_MFILES
+    Mfiles,
+
+    /// WNNC_NET_MS_NFS
+    MsNfs,
+
+    /// WNNC_NET_GOOGLE
+    Google,
+
+    /// A value not listed in the specification
+    Other(u32),
+}
+
+impl From<u32> for NetworkProviderType {
+    fn from(value: u32) -> Self {
+        match value {
+            0x0002_0000 => NetworkProviderType::LanMan,
+            0x001A_0000 => NetworkProviderType::Avid,
+            0x001B_0000 => NetworkProviderType::Docuspace,
+            0x001C_0000 => NetworkProviderType::Mangosoft,
+            0x001D_0000 => NetworkProviderType::Sernet,
+            0x001E_0000 => NetworkProviderType::Riverfront1,
+            0x001F_0000 => NetworkProviderType::Riverfront2,
+            0x0020_0000 => NetworkProviderType::Decorb,
+            0x0021_0000 => NetworkProviderType::Protstor,
+            0x0022_0000 => NetworkProviderType::FjRedir,
+            0x0023_0000 => NetworkProviderType::Distinct,
+            0x0024_0000 => NetworkProviderType::Twins,
+            0x0025_0000 => NetworkProviderType::Rdr2Sample,
+            0x0026_0000 => NetworkProviderType::Csc,
+            0x0027_0000 => NetworkProviderType::ThreeInOne,
+            0x0029_0000 => NetworkProviderType::ExtendNet,
+            0x002A_0000 => NetworkProviderType::Stac,
+            0x002B_0000 => NetworkProviderType::Foxbat,
+            0x002C_0000 => NetworkProviderType::Yahoo,
+            0x002D_0000 => NetworkProviderType::Exifs,
+            0x002E_0000 => NetworkProviderType::Dav,
+            0x002F_0000 => NetworkProviderType::Knoware,
+            0x0030_0000 => NetworkProviderType::ObjectDire,
+            0x0031_0000 => NetworkProviderType::Masfax,
+            0x0032_0000 => NetworkProviderType::HobNfs,
+            0x0033_0000 => NetworkProviderType::Shiva,
+            0x0034_0000 => NetworkProviderType::Ibmal,
+            0x0035_0000 => NetworkProviderType::Lock,
+            0x0036_0000 => NetworkProviderType::Termsrv,
+            0x0037_0000 => NetworkProviderType::Srt,
+            0x0038_0000 => NetworkProviderType::Quincy,
+            0x0039_0000 => NetworkProviderType::OpenAfs,
+            0x003A_0000 => NetworkProviderType::Avid1,
+            0x003B_0000 => NetworkProviderType::Dfs,
+            0x003C_0000 => NetworkProviderType::Kwnp,
+            0x003D_0000 => NetworkProviderType::Zenworks,
+            0x003E_0000 => NetworkProviderType::DriveOnWeb,
+            0x003F_0000 => NetworkProviderType::Vmware,
+            0x0040_0000 => NetworkProviderType::Rsfx,
+            0x0041_0000 => NetworkProviderType::Mfiles,
+            0x0042_0000 => NetworkProviderType::MsNfs,
+            0x0043_0000 => NetworkProviderType::Google,
+            other => NetworkProviderType::Other(other),
+        }
+    }
+}
+
+impl From<NetworkProviderType> for u32 {
+    fn from(provider_type: NetworkProviderType) -> Self {
+        match provider_type {
+            NetworkProviderType::LanMan => 0x0002_0000,
+            NetworkProviderType::Avid => 0x001A_0000,
+            NetworkProviderType::Docuspace => 0x001B_0000,
+            NetworkProviderType::Mangosoft => 0x001C_0000,
+            NetworkProviderType::Sernet => 0x001D_0000,
+            NetworkProviderType::Riverfront1 => 0x001E_0000,
+            NetworkProviderType::Riverfront2 => 0x001F_0000,
+            NetworkProviderType::Decorb => 0x0020_0000,
+            NetworkProviderType::Protstor => 0x0021_0000,
+            NetworkProviderType::FjRedir => 0x0022_0000,
+            NetworkProviderType::Distinct => 0x0023_0000,
+            NetworkProviderType::Twins => 0x0024_0000,
+            NetworkProviderType::Rdr2Sample => 0x0025_0000,
+            NetworkProviderType::Csc => 0x0026_0000,
+            NetworkProviderType::ThreeInOne => 0x0027_0000,
+            NetworkProviderType::ExtendNet => 0x0029_0000,
+            NetworkProviderType::Stac => 0x002A_0000,
+            NetworkProviderType::Foxbat => 0x002B_0000,
+            NetworkProviderType::Yahoo => 0x002C_0000,
+            NetworkProviderType::Exifs => 0x002D_0000,
+            NetworkProviderType::Dav => 0x002E_0000,
+            NetworkProviderType::Knoware => 0x002F_0000,
+            NetworkProviderType::ObjectDire => 0x0030_0000,
+            NetworkProviderType::Masfax => 0x0031_0000,
+            NetworkProviderType::HobNfs => 0x0032_0000,
+            NetworkProviderType::Shiva => 0x0033_0000,
+            NetworkProviderType::Ibmal => 0x0034_0000,
+            NetworkProviderType::Lock => 0x0035_0000,
+            NetworkProviderType::Termsrv => 0x0036_0000,
+            NetworkProviderType::Srt => 0x0037_0000,
+            NetworkProviderType::Quincy => 0x0038_0000,
+            NetworkProviderType::OpenAfs => 0x0039_0000,
+            NetworkProviderType::Avid1 => 0x003A_0000,
+            NetworkProviderType::Dfs => 0x003B_0000,
+            NetworkProviderType::Kwnp => 0x003C_0000,
+            NetworkProviderType::Zenworks => 0x003D_0000,
+            NetworkProviderType::DriveOnWeb => 0x003E_0000,
+            NetworkProviderType::Vmware => 0x003F_0000,
+            NetworkProviderType::Rsfx => 0x0040_0000,
+            NetworkProviderType::Mfiles => 0x0041_0000,
+            NetworkProviderType::MsNfs => 0x0042_0000,
+            NetworkProviderType::Google => 0x0043_0000,
+            NetworkProviderType::Other(other) => other,
+        }
+    }
+}
+
+impl CommonNetworkRelativeLink {
+    /// Construct a new `CommonNetworkRelativeLink` from the data in `cursor` located at `from`.
+    /// The structure must fit before `limit`, the end of the enclosing `LinkInfo`. ANSI names
+    /// are decoded using `code_page`.
+    fn new(
+        cursor: &mut Cursor<&[u8]>,
+        from: u64,
+        limit: u64,
+        code_page: Option<u32>,
+    ) -> std::result::Result<Self, LinkInfoError> {
+        let reset = cursor.position();
+        cursor.set_position(from);
+
+        let result = Self::read(cursor, from, limit, code_page);
+
+        cursor.set_position(reset);
+        result
+    }
+
+    fn read(
+        cursor: &mut Cursor<&[u8]>,
+        from: u64,
+        limit: u64,
+        code_page: Option<u32>,
+    ) -> std::result::Result<Self, LinkInfoError> {
+        let mut this = Self {
+            common_network_relative_size: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            common_network_relative_link_flags: CommonNetworkRelativeLinkFlags::from_bits_truncate(
+                cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            ),
+            net_name_offset: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            device_name_offset: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            network_provider_type: cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?,
+            ..Default::default()
+        };
+
+        let size = this.common_network_relative_size;
+        if size < 0x0000_0014 || from + size as u64 > limit {
+            return Err(LinkInfoError::InvalidCommonNetworkRelativeLink(size));
+        }
+
+        if this.net_name_offset > 0x0000_0014 {
+            this.net_name_offset_unicode =
+                Some(cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?);
+            this.device_name_offset_unicode =
+                Some(cursor.read_u32::<LE>().map_err(LinkInfoError::Read)?);
+        }
+
+        check_available(cursor, from, size as u64)?;
+        let mut data = vec![0; size as usize];
+        cursor.set_position(from);
+        cursor.read_exact(&mut data).map_err(LinkInfoError::Read)?;
+
+        let ansi = |offset: u32| {
+            data.get(offset as usize..).map(|data| {
+                let len = data.iter().position(|c| *c == 0).unwrap_or(data.len());
+                code_page::decode(&data[..len], code_page)
+            })
+        };
+        let unicode = |offset: u32| {
+            data.get(offset as usize..).map(|data| {
+                let wide = data
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                    .take_while(|c| *c != 0)
+                    .collect::<Vec<u16>>();
+                String::from_utf16_lossy(&wide)
+            })
+        };
+
+        let valid_device = this
+            .common_network_relative_link_flags
+            .contains(CommonNetworkRelativeLinkFlags::VALID_DEVICE);
+
+        this.net_name = ansi(this.net_name_offset);
+        if valid_device {
+            this.device_name = ansi(this.device_name_offset);
+        }
+        this.net_name_unicode = this.net_name_offset_unicode.and_then(unicode);
+        this.device_name_unicode = this
+            .device_name_offset_unicode
+            .filter(|_| valid_device)
+            .and_then(unicode);
+
+        Ok(this)
+    }
+
+    /// The type of network provider, if the ValidNetType flag is set
+    pub fn provider_type(&self) -> Option<NetworkProviderType> {
+        if self
+            .common_network_relative_link_flags
+            .contains(CommonNetworkRelativeLinkFlags::VALID_NET_TYPE)
+        {
+            Some(NetworkProviderType::from(self.network_provider_type))
+        } else {
+            None
+        }
+    }
+
+    /// Serialize the structure into `buf`, laying it out afresh from its fields. The
+    /// ValidDevice flag is set to match whether a device name is present. ANSI names are
+    /// encoded using `code_page`.
+    fn write(&self, buf: &mut Vec<u8>, code_page: Option<u32>) {
+        let unicode = self.net_name_unicode.is_some() || self.device_name_unicode.is_some();
+        let header_size: usize = if unicode { 0x1c } else { 0x14 };
+
+        let net_name = ansi_string(self.net_name.as_deref(), code_page);
+        let device_name = self
+            .device_name
+            .as_deref()
+            .map(|device_name| ansi_string(Some(device_name), code_page))
+            .unwrap_or_default();
+        let (net_name_unicode, device_name_unicode) = if unicode {
+            (
+                unicode_string(self.net_name_unicode.as_deref()),
+                self.device_name_unicode
+                    .as_deref()
+                    .map(|device_name| unicode_string(Some(device_name)))
+                    .unwrap_or_default(),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let net_name_offset = header_size;
+        let device_name_offset = net_name_offset + net_name.len();
+        let net_name_offset_unicode = device_name_offset + device_name.len();
+        let device_name_offset_unicode = net_name_offset_unicode + net_name_unicode.len();
+        let size = device_name_offset_unicode + device_name_unicode.len();
+
+        let offset_if = |present: bool, offset: usize| if present { offset as u32 } else { 0 };
+
+        let mut flags = self.common_network_relative_link_flags;
+        flags.set(
+            CommonNetworkRelativeLinkFlags::VALID_DEVICE,
+            !device_name.is_empty(),
+        );
+
+        buf.extend_from_slice(&(size as u32).to_le_bytes());
+        buf.extend_from_slice(&flags.bits().to_le_bytes());
+        buf.extend_from_slice(&(net_name_offset as u32).to_le_bytes());
+        buf.extend_from_slice(
+            &offset_if(!device_name.is_empty(), device_name_offset).to_le_bytes(),
+        );
+        buf.extend_from_slice(&self.network_provider_type.to_le_bytes());
+        if unicode {
+            buf.extend_from_slice(&(net_name_offset_unicode as u32).to_le_bytes());
+            buf.extend_from_slice(
+                &offset_if(!device_name_unicode.is_empty(), device_name_offset_unicode)
+                    .to_le_bytes(),
+            );
+        }
+        buf.extend_from_slice(&net_name);
+        buf.extend_from_slice(&device_name);
+        buf.extend_from_slice(&net_name_unicode);
+        buf.extend_from_slice(&device_name_unicode);
+    }
+}
+
+/// Encode `s` as a NULL-terminated string of the code page `code_page`
+fn ansi_string(s: Option<&str>, code_page: Option<u32>) -> Vec<u8> {
+    let mut data = code_page::encode(s.unwrap_or(""), code_page);
+    data.push(0);
+    data
+}
+
+/// Encode `s` as a NULL-terminated Unicode string
+fn unicode_string(s: Option<&str>) -> Vec<u8> {
+    s.unwrap_or("")
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// The result of reading an optional `LinkInfo` string
+type StringResult = std::result::Result<Option<String>, LinkInfoError>;
+
+/// Record a failed string read as a warning, leaving the field empty
+fn warn(
+    result: StringResult,
+    field: &'static str,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<String> {
+    result
+        .map_err(|e| warnings.push(ParseWarning::StringDecode(field, e.to_string())))
+        .ok()
+        .flatten()
+}
+
 impl LinkInfo {
     /// Construct a new `LinkInfo` from the data in `cursor`
-    pub fn new(cursor: &mut Cursor<Vec<u8>>, header: &ShellLinkHeader) -> Result<Self> {
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, header: &ShellLinkHeader) -> Result<Self> {
+        Self::with_options(cursor, header, &LnkOptions::default())
+    }
+
+    /// Construct a new `LinkInfo` from the data in `cursor`, decoding its ANSI strings using
+    /// the `ansi_code_page` of `options`. Without a code page, Windows-1252 is assumed.
+    pub fn with_options<T: AsRef<[u8]>>(
+        cursor: &mut Cursor<T>,
+        header: &ShellLinkHeader,
+        options: &LnkOptions,
+    ) -> Result<Self> {
+        let code_page = options.code_page()?;
+        crate::parser::borrowed(cursor, |cursor| {
+            Self::parse(cursor, header, code_page, options.strict, &mut Vec::new())
+        })
+    }
+
+    pub(crate) fn parse(
+        cursor: &mut Cursor<&[u8]>,
+        header: &ShellLinkHeader,
+        code_page: Option<u32>,
+        strict: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Self> {
         if header.link_flags.contains(LinkFlags::HAS_LINK_INFO) {
             let start_pos = cursor.position();
 
@@ -149,27 +822,95 @@ impl LinkInfo {
                 common_path_suffix_unicode: None,
             };
             cursor.set_position(start_pos);
+            let data_len = cursor.get_ref().len() as u64;
+
+            // A size too small to hold the header cannot be trusted, so the fields are read
+            // up to the end of the data instead, and parsing continues after the last of them
+            // rather than going back over the bytes already read
+            let link_info_size = this.link_info_size;
+            let undersized = link_info_size < this.link_info_header_size;
+            if undersized {
+                if strict {
+                    return Err(LinkInfoError::InvalidSize(link_info_size).into());
+                }
+                warnings.push(ParseWarning::InvalidLinkInfoSize(link_info_size));
+                this.link_info_size =
+                    std::convert::TryFrom::try_from(data_len - start_pos).unwrap_or(u32::MAX);
+            } else if start_pos + link_info_size as u64 > data_len {
+                warnings.push(ParseWarning::InvalidLinkInfoSize(link_info_size));
+            }
 
             if let Some(ref link_info_flags) = this.link_info_flags {
                 if link_info_flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH) {
-                    this.local_base_path = this.read_local_base_path(cursor, *link_info_flags);
-                    this.common_path_suffix = this.read_common_path_suffix(cursor);
-                    this.local_base_path_unicode =
-                        this.read_local_base_path_unicode(cursor, *link_info_flags);
-                    this.common_path_suffix_unicode =
-                        this.read_common_path_suffix_unicode(cursor, *link_info_flags);
-
-                    // TODO: Parse `VolumeID` structure
+                    this.local_base_path = warn(
+                        this.read_local_base_path(cursor, code_page),
+                        "LocalBasePath",
+                        warnings,
+                    );
+                    this.common_path_suffix = warn(
+                        this.read_common_path_suffix(cursor, code_page),
+                        "CommonPathSuffix",
+                        warnings,
+                    );
+                    this.local_base_path_unicode = warn(
+                        this.read_local_base_path_unicode(cursor),
+                        "LocalBasePathUnicode",
+                        warnings,
+                    );
+                    this.common_path_suffix_unicode = warn(
+                        this.read_common_path_suffix_unicode(cursor),
+                        "CommonPathSuffixUnicode",
+                        warnings,
+                    );
+
+                    // The base paths do not depend on the `VolumeID`, so a malformed
+                    // volume only costs us the volume information.
+                    this.volume_id = VolumeId::new(
+                        cursor,
+                        start_pos + this.volume_id_offset as u64,
+                        start_pos + this.link_info_size as u64,
+                        code_page,
+                    )
+                    .map_err(|e| warnings.push(ParseWarning::VolumeId(e.to_string())))
+                    .ok();
                 }
 
                 if link_info_flags
                     .contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX)
                 {
-                    // TODO: Parse `CommonNetworkRelativeLink` structure
+                    if !link_info_flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH) {
+                        this.common_path_suffix = warn(
+                            this.read_common_path_suffix(cursor, code_page),
+                            "CommonPathSuffix",
+                            warnings,
+                        );
+                        this.common_path_suffix_unicode = warn(
+                            this.read_common_path_suffix_unicode(cursor),
+                            "CommonPathSuffixUnicode",
+                            warnings,
+                        );
+                    }
+
+                    this.common_network_relative_link = CommonNetworkRelativeLink::new(
+                        cursor,
+                        start_pos + this.common_network_relative_link_offset as u64,
+                        start_pos + this.link_info_size as u64,
+                        code_page,
+                    )
+                    .map_err(|e| {
+                        warnings.push(ParseWarning::CommonNetworkRelativeLink(e.to_string()))
+                    })
+                    .ok();
                 }
             }
 
-            cursor.set_position(this.link_info_size as u64 + start_pos);
+            let end_pos = if undersized {
+                this.link_info_size = link_info_size;
+                this.fields_end(cursor)
+            } else {
+                start_pos + link_info_size as u64
+            };
+            cursor.set_position(end_pos.min(data_len));
 
             Ok(this)
         } else {
@@ -177,107 +918,270 @@ impl LinkInfo {
         }
     }
 
-    fn read_local_base_path(
-        &self,
-        cursor: &mut Cursor<Vec<u8>>,
-        link_info_flags: LinkInfoFlags,
-    ) -> Option<String> {
-        let start_pos = cursor.position();
-        let end_pos = if link_info_flags
-            .contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX)
-        {
-            self.common_network_relative_link_offset as u64 + start_pos
-        } else {
-            self.common_path_suffix_offset as u64 + start_pos
-        } - 1;
+    /// Serialize the structure into `buf`, laying it out afresh from its fields and encoding
+    /// its ANSI strings using `code_page`. Nothing is written if the `LinkInfo` is not present.
+    pub(crate) fn write(&self, buf: &mut Vec<u8>, code_page: Option<u32>) {
+        let link_info_flags = match self.link_info_flags {
+            Some(link_info_flags) => link_info_flags,
+            None => return,
+        };
 
-        let begin = start_pos + self.local_base_path_offset as u64;
+        let local = link_info_flags.contains(LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH);
+        let network =
+            link_info_flags.contains(LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX);
+        let unicode =
+            self.local_base_path_unicode.is_some() || self.common_path_suffix_unicode.is_some();
 
-        if end_pos > begin {
-            Self::read_string(cursor, begin, end_pos - begin).ok()
+        let link_info_header_size: u32 = if unicode { 0x0000_0024 } else { 0x0000_001c };
+
+        let mut volume_id = Vec::new();
+        let mut local_base_path = Vec::new();
+        let mut local_base_path_unicode = Vec::new();
+        if local {
+            self.volume_id
+                .clone()
+                .unwrap_or_default()
+                .write(&mut volume_id, code_page);
+            local_base_path = ansi_string(self.local_base_path.as_deref(), code_page);
+            if unicode {
+                local_base_path_unicode = unicode_string(self.local_base_path_unicode.as_deref());
+            }
+        }
+        let mut common_network_relative_link = Vec::new();
+        if network {
+            self.common_network_relative_link
+                .clone()
+                .unwrap_or_default()
+                .write(&mut common_network_relative_link, code_page);
+        }
+        let common_path_suffix = ansi_string(self.common_path_suffix.as_deref(), code_page);
+        let common_path_suffix_unicode = if unicode {
+            unicode_string(self.common_path_suffix_unicode.as_deref())
         } else {
-            None
+            Vec::new()
+        };
+
+        let offset_if = |present: bool, offset: usize| if present { offset as u32 } else { 0 };
+
+        let volume_id_offset = link_info_header_size as usize;
+        let local_base_path_offset = volume_id_offset + volume_id.len();
+        let common_network_relative_link_offset = local_base_path_offset + local_base_path.len();
+        let common_path_suffix_offset =
+            common_network_relative_link_offset + common_network_relative_link.len();
+        let local_base_path_offset_unicode = common_path_suffix_offset + common_path_suffix.len();
+        let common_path_suffix_offset_unicode =
+            local_base_path_offset_unicode + local_base_path_unicode.len();
+        let link_info_size = common_path_suffix_offset_unicode + common_path_suffix_unicode.len();
+
+        buf.extend_from_slice(&(link_info_size as u32).to_le_bytes());
+        buf.extend_from_slice(&link_info_header_size.to_le_bytes());
+        buf.extend_from_slice(&link_info_flags.bits().to_le_bytes());
+        buf.extend_from_slice(&offset_if(local, volume_id_offset).to_le_bytes());
+        buf.extend_from_slice(&offset_if(local, local_base_path_offset).to_le_bytes());
+        buf.extend_from_slice(
+            &offset_if(network, common_network_relative_link_offset).to_le_bytes(),
+        );
+        buf.extend_from_slice(&(common_path_suffix_offset as u32).to_le_bytes());
+        if unicode {
+            buf.extend_from_slice(&offset_if(local, local_base_path_offset_unicode).to_le_bytes());
+            buf.extend_from_slice(&(common_path_suffix_offset_unicode as u32).to_le_bytes());
         }
+        buf.extend_from_slice(&volume_id);
+        buf.extend_from_slice(&local_base_path);
+        buf.extend_from_slice(&common_network_relative_link);
+        buf.extend_from_slice(&common_path_suffix);
+        buf.extend_from_slice(&local_base_path_unicode);
+        buf.extend_from_slice(&common_path_suffix_unicode);
     }
 
-    fn read_common_path_suffix(&self, cursor: &mut Cursor<Vec<u8>>) -> Option<String> {
-        let start_pos = cursor.position();
+    /// The size, in bytes, of the `LinkInfo` structure
+    pub fn link_info_size(&self) -> u32 {
+        self.link_info_size
+    }
 
-        let end_pos = if self.link_info_header_size >= 0x0000_0024 {
-            self.local_base_path_offset_unicode as u64
-        } else {
-            self.link_info_size as u64
-        } + start_pos
-            - 1;
+    /// The size, in bytes, of the `LinkInfo` header section
+    pub fn link_info_header_size(&self) -> u32 {
+        self.link_info_header_size
+    }
+
+    /// Whether the header is large enough to hold the offsets of the unicode
+    /// `LocalBasePathUnicode` and `CommonPathSuffixUnicode` fields. Older links have a
+    /// 0x1C byte header without them, so their unicode paths are always `None`.
+    pub fn has_unicode_paths(&self) -> bool {
+        self.link_info_header_size >= 0x0000_0024
+    }
+
+    /// The offset of the `VolumeID` field from the start of the `LinkInfo` structure
+    pub fn volume_id_offset(&self) -> u32 {
+        self.volume_id_offset
+    }
+
+    /// The offset of the `LocalBasePath` field from the start of the `LinkInfo` structure
+    pub fn local_base_path_offset(&self) -> u32 {
+        self.local_base_path_offset
+    }
+
+    /// The offset of the `CommonNetworkRelativeLink` field from the start of the `LinkInfo`
+    /// structure
+    pub fn common_network_relative_link_offset(&self) -> u32 {
+        self.common_network_relative_link_offset
+    }
 
-        let begin = start_pos + self.common_path_suffix_offset as u64;
+    /// The offset of the `CommonPathSuffix` field from the start of the `LinkInfo` structure
+    pub fn common_path_suffix_offset(&self) -> u32 {
+        self.common_path_suffix_offset
+    }
 
-        if end_pos > begin {
-            Self::read_widestring(cursor, begin, end_pos - begin).ok()
+    /// The offset of the `LocalBasePathUnicode` field from the start of the `LinkInfo`
+    /// structure, if the header is large enough to contain it
+    pub fn local_base_path_offset_unicode(&self) -> Option<u32> {
+        if self.has_unicode_paths() {
+            Some(self.local_base_path_offset_unicode)
         } else {
             None
         }
     }
 
-    fn read_local_base_path_unicode(
+    /// The offset of the `CommonPathSuffixUnicode` field from the start of the `LinkInfo`
+    /// structure, if the header is large enough to contain it
+    pub fn common_path_suffix_offset_unicode(&self) -> Option<u32> {
+        if self.has_unicode_paths() {
+            Some(self.common_path_suffix_offset_unicode)
+        } else {
+            None
+        }
+    }
+
+    /// Check that a field offset lies within the structure, after its header, as the
+    /// specification requires
+    fn check_offset(&self, offset: u32) -> std::result::Result<(), LinkInfoError> {
+        if (self.link_info_header_size..self.link_info_size).contains(&offset) {
+            Ok(())
+        } else {
+            Err(LinkInfoError::InvalidOffset(offset))
+        }
+    }
+
+    fn read_local_base_path(
         &self,
-        cursor: &mut Cursor<Vec<u8>>,
-        _link_info_flags: LinkInfoFlags,
-    ) -> Option<String> {
-        if self.link_info_header_size >= 0x0000_0024 {
-            let start_pos = cursor.position();
+        cursor: &mut Cursor<&[u8]>,
+        code_page: Option<u32>,
+    ) -> StringResult {
+        self.check_offset(self.local_base_path_offset)?;
+        let (begin, limit) = self.field_range(cursor, self.local_base_path_offset);
 
-            let end_pos = self.common_path_suffix_offset_unicode as u64 + start_pos - 1;
+        Self::read_string(cursor, begin, limit, code_page).map(Some)
+    }
 
-            let begin = start_pos + self.local_base_path_offset as u64;
+    fn read_common_path_suffix(
+        &self,
+        cursor: &mut Cursor<&[u8]>,
+        code_page: Option<u32>,
+    ) -> StringResult {
+        self.check_offset(self.common_path_suffix_offset)?;
+        let (begin, limit) = self.field_range(cursor, self.common_path_suffix_offset);
 
-            if end_pos > begin {
-                Self::read_widestring(cursor, begin, end_pos - begin).ok()
-            } else {
-                None
-            }
+        Self::read_string(cursor, begin, limit, code_page).map(Some)
+    }
+
+    fn read_local_base_path_unicode(&self, cursor: &mut Cursor<&[u8]>) -> StringResult {
+        if self.has_unicode_paths() {
+            self.check_offset(self.local_base_path_offset_unicode)?;
+            let (begin, limit) = self.field_range(cursor, self.local_base_path_offset_unicode);
+
+            Self::read_widestring(cursor, begin, limit).map(Some)
         } else {
-            None
+            Ok(None)
         }
     }
 
-    fn read_common_path_suffix_unicode(
-        &self,
-        cursor: &mut Cursor<Vec<u8>>,
-        _link_info_flags: LinkInfoFlags,
-    ) -> Option<String> {
-        if self.link_info_header_size >= 0x0000_0024 {
-            let start_pos = cursor.position();
+    fn read_common_path_suffix_unicode(&self, cursor: &mut Cursor<&[u8]>) -> StringResult {
+        if self.has_unicode_paths() {
+            self.check_offset(self.common_path_suffix_offset_unicode)?;
+            let (begin, limit) = self.field_range(cursor, self.common_path_suffix_offset_unicode);
 
-            let end_pos = self.link_info_size as u64 + start_pos - 1;
+            Self::read_widestring(cursor, begin, limit).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
 
-            let begin = start_pos + self.common_path_suffix_offset_unicode as u64;
+    /// The end of the furthest field that was read, for continuing after a `LinkInfo` whose
+    /// size cannot be trusted. This is never before the end of the header.
+    fn fields_end(&self, cursor: &Cursor<&[u8]>) -> u64 {
+        let start_pos = cursor.position();
+        let data_len = cursor.get_ref().len() as u64;
+        let string_end = |offset: u32, char_size: usize| {
+            let from = start_pos + offset as u64;
+            Self::read_terminated(cursor, from, data_len, char_size)
+                .map_or(from, |data| from + (data.len() + char_size) as u64)
+        };
 
-            if end_pos > begin {
-                Self::read_widestring(cursor, begin, end_pos - begin).ok()
-            } else {
-                None
+        let mut end = start_pos + self.link_info_header_size as u64;
+        if let Some(ref volume_id) = self.volume_id {
+            end =
+                end.max(start_pos + self.volume_id_offset as u64 + volume_id.volume_id_size as u64);
+        }
+        if let Some(ref link) = self.common_network_relative_link {
+            end = end.max(
+                start_pos
+                    + self.common_network_relative_link_offset as u64
+                    + link.common_network_relative_size as u64,
+            );
+        }
+
+        for (present, offset, char_size) in [
+            (
+                self.local_base_path.is_some(),
+                self.local_base_path_offset,
+                1,
+            ),
+            (
+                self.common_path_suffix.is_some(),
+                self.common_path_suffix_offset,
+                1,
+            ),
+            (
+                self.local_base_path_unicode.is_some(),
+                self.local_base_path_offset_unicode,
+                2,
+            ),
+            (
+                self.common_path_suffix_unicode.is_some(),
+                self.common_path_suffix_offset_unicode,
+                2,
+            ),
+        ] {
+            if present {
+                end = end.max(string_end(offset, char_size));
             }
-        } else {
-            None
         }
+
+        end
     }
 
+    /// The position of the field at `offset`, for a `LinkInfo` starting at the position of
+    /// `cursor`, and the end of the structure it must lie within. The end is bounded by the
+    /// data, so that a string can still be read from a structure whose size is too large.
+    fn field_range(&self, cursor: &Cursor<&[u8]>, offset: u32) -> (u64, u64) {
+        let start_pos = cursor.position();
+        let limit = (start_pos + self.link_info_size as u64).min(cursor.get_ref().len() as u64);
+
+        (start_pos + offset as u64, limit)
+    }
+
+    /// Read the NUL-terminated UTF-16 string at `from`, which must end before `limit`. The
+    /// terminator is not included in the string.
     fn read_widestring(
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
         from: u64,
-        size: u64,
+        limit: u64,
     ) -> std::result::Result<String, LinkInfoError> {
-        let reset = cursor.position();
-        let mut data = vec![0; size as usize];
-
-        cursor.set_position(from);
-        cursor.read_exact(&mut data).map_err(LinkInfoError::Read)?;
-        cursor.set_position(reset);
+        let data = Self::read_terminated(cursor, from, limit, 2)?;
 
         let wide_data = data
             .chunks_exact(2)
-            .map(|chunks| u16::from_ne_bytes([chunks[0], chunks[1]]))
+            .map(|chunks| u16::from_le_bytes([chunks[0], chunks[1]]))
             .collect::<Vec<u16>>();
 
         let wide = widestring::U16Str::from_slice(&wide_data).to_ustring();
@@ -286,18 +1190,424 @@ impl LinkInfo {
             .map_err(LinkInfoError::WideStringConversion)
     }
 
+    /// Read the NUL-terminated string of the code page `code_page` at `from`, which must end
+    /// before `limit`. The terminator is not included in the string.
     fn read_string(
-        cursor: &mut Cursor<Vec<u8>>,
+        cursor: &mut Cursor<&[u8]>,
         from: u64,
-        size: u64,
+        limit: u64,
+        code_page: Option<u32>,
     ) -> std::result::Result<String, LinkInfoError> {
-        let reset = cursor.position();
-        let mut data = vec![0; size as usize];
+        let data = Self::read_terminated(cursor, from, limit, 1)?;
 
-        cursor.set_position(from);
-        cursor.read_exact(&mut data).map_err(LinkInfoError::Read)?;
-        cursor.set_position(reset);
+        Ok(code_page::decode(&data, code_page))
+    }
+
+    /// Read the characters of `char_size` bytes at `from` up to the first NUL character, which
+    /// must be found before `limit`. This is the one place terminators are stripped, so no
+    /// decoded string ends with a NUL.
+    fn read_terminated(
+        cursor: &Cursor<&[u8]>,
+        from: u64,
+        limit: u64,
+        char_size: usize,
+    ) -> std::result::Result<Vec<u8>, LinkInfoError> {
+        let data = cursor
+            .get_ref()
+            .get(from as usize..limit as usize)
+            .unwrap_or_default();
+
+        let len = data
+            .chunks_exact(char_size)
+            .position(|c| c.iter().all(|b| *b == 0))
+            .ok_or_else(|| {
+                LinkInfoError::Read(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+            })?;
+
+        Ok(data[..len * char_size].to_vec())
+    }
+}
+
+/// Fail with `UnexpectedEof` before allocating a buffer for `size` bytes at `from` that the
+/// underlying data does not hold
+fn check_available(
+    cursor: &Cursor<&[u8]>,
+    from: u64,
+    size: u64,
+) -> std::result::Result<(), LinkInfoError> {
+    match from.checked_add(size) {
+        Some(end) if end <= cursor.get_ref().len() as u64 => Ok(()),
+        _ => Err(LinkInfoError::Read(std::io::Error::from(
+            std::io::ErrorKind::UnexpectedEof,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CommonNetworkRelativeLink, CommonNetworkRelativeLinkFlags, LinkInfo, NetworkProviderType,
+        VolumeId,
+    };
+    use crate::error::{Error, LinkInfoError, ParseWarning, ValidationIssue};
+    use crate::{Lnk, LnkOptions};
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    /// Offset of the `LinkInfo` structure within `firefox.lnk`
+    fn link_info_start(data: &[u8]) -> usize {
+        78 + u16::from_le_bytes([data[76], data[77]]) as usize
+    }
+
+    #[test]
+    fn volume_id() {
+        let lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        let volume_id = lnk.link_info.volume_id.unwrap();
+
+        assert_eq!(volume_id.volume_id_size, 0x11);
+        assert_eq!(volume_id.drive_type, 3);
+        assert_eq!(volume_id.drive_serial_number, 0x700f_86d4);
+        assert_eq!(volume_id.volume_label, Some(String::new()));
+    }
+
+    #[test]
+    fn unicode_volume_label() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0000_0028u32.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&0x1234_5678u32.to_le_bytes());
+        data.extend_from_slice(&0x0000_0014u32.to_le_bytes());
+        data.extend_from_slice(&0x0000_0018u32.to_le_bytes());
+        data.extend_from_slice(b"\xff\xff\xff\x00");
+        data.extend("Données".encode_utf16().flat_map(u16::to_le_bytes));
+        data.extend_from_slice(&[0, 0]);
+        assert_eq!(data.len(), 0x28);
+
+        let volume_id = VolumeId::new(&mut Cursor::new(data.as_slice()), 0, 0x28, None).unwrap();
+        assert_eq!(volume_id.volume_label_offset_unicode, Some(0x18));
+        assert_eq!(volume_id.volume_label.as_deref(), Some("Données"));
+
+        let mut written = Vec::new();
+        volume_id.write(&mut written, None);
+        let reparsed = VolumeId::new(&mut Cursor::new(written.as_slice()), 0, 0x28, None).unwrap();
+        assert_eq!(reparsed.volume_label_offset_unicode, Some(0x14));
+        assert_eq!(reparsed.volume_label, volume_id.volume_label);
+    }
+
+    #[test]
+    fn non_ascii_round_trip() {
+        let mut lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.link_info.local_base_path = Some(r"C:\Café\firefox.exe".to_string());
+        lnk.link_info.volume_id.as_mut().unwrap().volume_label = Some("Données".to_string());
+        lnk.link_info.common_network_relative_link = Some(CommonNetworkRelativeLink {
+            net_name: Some(r"\\serveur\partagé".to_string()),
+            ..Default::default()
+        });
+
+        let data = lnk.to_bytes();
+        let parsed = Lnk::parse(&data).unwrap();
+
+        assert!(parsed.warnings().is_empty());
+        assert_eq!(
+            parsed.link_info.local_base_path,
+            lnk.link_info.local_base_path
+        );
+        assert_eq!(
+            parsed.target_path_str().as_deref(),
+            Some(r"C:\Café\firefox.exe")
+        );
+        assert_eq!(
+            parsed.link_info.volume_id.unwrap().volume_label.as_deref(),
+            Some("Données")
+        );
+
+        let mut network = lnk.clone();
+        network.link_info.link_info_flags = Some(
+            super::LinkInfoFlags::VOLUME_ID_AND_LOCAL_BASE_PATH
+                | super::LinkInfoFlags::COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX,
+        );
+        let parsed = Lnk::parse(&network.to_bytes()).unwrap();
+        assert_eq!(
+            parsed
+                .link_info
+                .common_network_relative_link
+                .unwrap()
+                .net_name
+                .as_deref(),
+            Some(r"\\serveur\partagé")
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        let link_info = lnk.link_info;
+
+        assert_eq!(link_info.link_info_size(), 0x5b);
+        assert_eq!(link_info.link_info_header_size(), 0x1c);
+        assert_eq!(link_info.volume_id_offset(), 0x1c);
+        assert_eq!(link_info.local_base_path_offset(), 0x2d);
+        assert_eq!(link_info.common_network_relative_link_offset(), 0);
+        assert_eq!(link_info.common_path_suffix_offset(), 0x5a);
+        assert_eq!(link_info.local_base_path_offset_unicode(), None);
+        assert_eq!(link_info.common_path_suffix_offset_unicode(), None);
+    }
+
+    #[test]
+    fn has_unicode_paths() {
+        let mut lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        assert_eq!(lnk.link_info.link_info_header_size(), 0x1c);
+        assert!(!lnk.link_info.has_unicode_paths());
+        assert_eq!(lnk.link_info.local_base_path_unicode, None);
+
+        lnk.link_info.local_base_path_unicode = Some(r"C:\Program Files\Mozilla Firefox\".into());
+        lnk.link_info.common_path_suffix_unicode = Some("firefox.exe".into());
+        let lnk = Lnk::parse(&lnk.to_bytes()).unwrap();
+
+        assert_eq!(lnk.link_info.link_info_header_size(), 0x24);
+        assert!(lnk.link_info.has_unicode_paths());
+        assert_eq!(
+            lnk.link_info.common_path_suffix_unicode.as_deref(),
+            Some("firefox.exe")
+        );
+    }
+
+    #[test]
+    fn inflated_link_info_size() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let header = Lnk::try_from(data.as_slice()).unwrap().header;
+        let start = link_info_start(&data);
+
+        let mut link_info = data[start..start + 0x5b].to_vec();
+        link_info[..4].copy_from_slice(&0x1000u32.to_le_bytes());
+
+        let mut cursor = Cursor::new(link_info.as_slice());
+        let mut warnings = Vec::new();
+        let link_info = LinkInfo::parse(&mut cursor, &header, None, false, &mut warnings).unwrap();
+
+        // The strings are still read up to their terminators, within the data
+        assert_eq!(warnings, [ParseWarning::InvalidLinkInfoSize(0x1000)]);
+        assert_eq!(link_info.common_path_suffix.as_deref(), Some(""));
+        assert_eq!(cursor.position(), 0x5b);
+        assert_eq!(
+            link_info.local_base_path,
+            Some(r"C:\Program Files\Mozilla Firefox\firefox.exe".to_string())
+        );
+    }
+
+    #[test]
+    fn undersized_link_info_size() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let expected = Lnk::try_from(data.as_slice()).unwrap();
+        let start = link_info_start(&data);
+
+        for size in [0u32, 4, 0x10] {
+            data[start..start + 4].copy_from_slice(&size.to_le_bytes());
+
+            // The fields are still read, and parsing continues after the last of them
+            // rather than reading the LinkInfo again as StringData
+            let lnk = Lnk::parse(&data).unwrap();
+            assert_eq!(lnk.warnings(), [ParseWarning::InvalidLinkInfoSize(size)]);
+            assert_eq!(lnk.link_info.link_info_size(), size);
+            assert_eq!(
+                lnk.link_info.local_base_path,
+                expected.link_info.local_base_path
+            );
+            assert_eq!(lnk.string_data, expected.string_data);
+            assert_eq!(lnk.extra_data, expected.extra_data);
+            assert_eq!(
+                lnk.validate().unwrap_err()[0],
+                ValidationIssue::InvalidLinkInfoSize(size)
+            );
+
+            let options = LnkOptions {
+                strict: true,
+                ..Default::default()
+            };
+            assert!(matches!(
+                Lnk::parse_with_options(&data, &options),
+                Err(Error::LinkInfoError(LinkInfoError::InvalidSize(s))) if s == size
+            ));
+        }
+    }
+
+    #[test]
+    fn strings_are_unterminated() {
+        for name in &["firefox", "commander", "notepad", "outlook_express"] {
+            let path = format!("./test_data/{}.lnk", name);
+            let lnk = Lnk::try_from(std::path::Path::new(&path)).unwrap();
+            let link_info = &lnk.link_info;
+
+            assert!(link_info.local_base_path.is_some(), "{}", name);
+            for string in [
+                &link_info.local_base_path,
+                &link_info.common_path_suffix,
+                &link_info.local_base_path_unicode,
+                &link_info.common_path_suffix_unicode,
+            ]
+            .iter()
+            .filter_map(|string| string.as_deref())
+            .chain(lnk.all_paths().iter().map(|(_, path)| path.as_str()))
+            {
+                assert!(!string.ends_with('\0'), "{}: {:?}", name, string);
+            }
+        }
+    }
+
+    #[test]
+    fn ansi_common_path_suffix() {
+        let mut lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.link_info.local_base_path = Some(r"C:\Program Files\".to_string());
+        lnk.link_info.common_path_suffix = Some(r"Mozilla Firefox\firefox.exe".to_string());
+
+        let lnk = Lnk::parse(&lnk.to_bytes()).unwrap();
+        assert_eq!(
+            lnk.link_info.common_path_suffix.as_deref(),
+            Some(r"Mozilla Firefox\firefox.exe")
+        );
+        assert_eq!(
+            lnk.target_path_str().as_deref(),
+            Some(r"C:\Program Files\Mozilla Firefox\firefox.exe")
+        );
+    }
+
+    #[test]
+    fn offset_outside_link_info() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let start = link_info_start(&data);
+        data[start + 0x10..start + 0x14].copy_from_slice(&0x5bu32.to_le_bytes());
+
+        let lnk = Lnk::try_from(data).unwrap();
+
+        assert_eq!(lnk.link_info.local_base_path, None);
+        assert!(matches!(
+            lnk.warnings(),
+            [ParseWarning::StringDecode("LocalBasePath", _)]
+        ));
+        assert_eq!(
+            lnk.string_data.working_dir.as_deref(),
+            Some(std::path::Path::new(r"C:\Program Files\Mozilla Firefox"))
+        );
+    }
+
+    #[test]
+    fn zero_offsets_at_start_of_data() {
+        let mut data = [0u8; 0x24];
+        data[0..4].copy_from_slice(&0x24u32.to_le_bytes());
+        data[4..8].copy_from_slice(&0x1cu32.to_le_bytes());
+        data[8..12].copy_from_slice(&3u32.to_le_bytes());
+
+        let header = crate::ShellLinkHeader {
+            link_flags: crate::LinkFlags::HAS_LINK_INFO,
+            ..Default::default()
+        };
+
+        let link_info = LinkInfo::new(&mut Cursor::new(&data), &header).unwrap();
+        assert_eq!(link_info.local_base_path, None);
+    }
+
+    #[test]
+    fn garbled_volume_id_keeps_base_path() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let volume_id = link_info_start(&data) + 0x1c;
+        data[volume_id..volume_id + 4].copy_from_slice(&0xffff_ffffu32.to_le_bytes());
+
+        let lnk = Lnk::try_from(data).unwrap();
+
+        assert!(lnk.link_info.volume_id.is_none());
+        assert!(matches!(lnk.warnings(), [ParseWarning::VolumeId(_)]));
+        assert_eq!(
+            lnk.link_info.local_base_path,
+            Some(r"C:\Program Files\Mozilla Firefox\firefox.exe".to_string())
+        );
+    }
+
+    #[test]
+    fn common_network_relative_link_net_type_only() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x23u32.to_le_bytes());
+        data.extend_from_slice(&0x02u32.to_le_bytes());
+        data.extend_from_slice(&0x14u32.to_le_bytes());
+        // Not a valid device, so this offset must be ignored
+        data.extend_from_slice(&0x16u32.to_le_bytes());
+        data.extend_from_slice(&0x0002_0000u32.to_le_bytes());
+        data.extend_from_slice(b"\\\\server\\share\0");
+
+        let link =
+            CommonNetworkRelativeLink::new(&mut Cursor::new(&data[..]), 0, 0x23, None).unwrap();
+
+        assert_eq!(
+            link.common_network_relative_link_flags,
+            CommonNetworkRelativeLinkFlags::VALID_NET_TYPE
+        );
+        assert_eq!(link.net_name, Some(r"\\server\share".to_string()));
+        assert_eq!(link.device_name, None);
+        assert_eq!(link.net_name_offset_unicode, None);
+        assert_eq!(link.provider_type(), Some(NetworkProviderType::LanMan));
+    }
+
+    #[test]
+    fn common_network_relative_link_stale_unicode_device() {
+        let net_name = b"\\\\srv\\s\0";
+        let net_name_unicode = "\\\\srv\\s\0"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        let device_name_unicode = "Z:\0"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        let net_name_offset_unicode = 0x1c + net_name.len();
+        let device_name_offset_unicode = net_name_offset_unicode + net_name_unicode.len();
+        let size = device_name_offset_unicode + device_name_unicode.len();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&(size as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0x1cu32.to_le_bytes());
+        // Neither flag is set, so the device offsets and provider type are stale
+        data.extend_from_slice(&0x1cu32.to_le_bytes());
+        data.extend_from_slice(&0x0002_0000u32.to_le_bytes());
+        data.extend_from_slice(&(net_name_offset_unicode as u32).to_le_bytes());
+        data.extend_from_slice(&(device_name_offset_unicode as u32).to_le_bytes());
+        data.extend_from_slice(net_name);
+        data.extend_from_slice(&net_name_unicode);
+        data.extend_from_slice(&device_name_unicode);
+
+        let link =
+            CommonNetworkRelativeLink::new(&mut Cursor::new(&data[..]), 0, size as u64, None)
+                .unwrap();
+
+        assert_eq!(link.net_name_unicode, Some(r"\\srv\s".to_string()));
+        assert_eq!(link.device_name, None);
+        assert_eq!(link.device_name_unicode, None);
+        assert_eq!(link.provider_type(), None);
+    }
+
+    #[test]
+    fn network_provider_type() {
+        assert_eq!(
+            NetworkProviderType::from(0x0002_0000),
+            NetworkProviderType::LanMan
+        );
+        assert_eq!(
+            NetworkProviderType::from(0x002e_0000),
+            NetworkProviderType::Dav
+        );
+        assert_eq!(
+            NetworkProviderType::from(0x0028_0000),
+            NetworkProviderType::Other(0x0028_0000)
+        );
+        assert_eq!(
+            u32::from(NetworkProviderType::Other(0x0028_0000)),
+            0x0028_0000
+        );
+        assert_eq!(u32::from(NetworkProviderType::Google), 0x0043_0000);
 
-        String::from_utf8(data).map_err(LinkInfoError::StringConversion)
+        let link = CommonNetworkRelativeLink {
+            network_provider_type: 0x0002_0000,
+            ..Default::default()
+        };
+        assert_eq!(link.provider_type(), None);
     }
 }
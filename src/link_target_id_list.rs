@@ -3,19 +3,29 @@
 //! type.
 //!
 
-use crate::{error::LinkTargetIdListError, LinkFlags, Result, ShellLinkHeader};
+use crate::{error::LinkTargetIdListError, Guid, LinkFlags, Result, ShellLinkHeader};
 use byteorder::{ReadBytesExt, LE};
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 /// The LinkTargetIDList structure specifies the target of the link. The presence of this optional structure
 /// is specified by the HasLinkTargetIDList bit (LinkFlags section 2.1.1) in the
 /// ShellLinkHeader (section 2.1).
-#[derive(Clone, Debug)]
-pub struct LinkTargetIdList {}
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkTargetIdList {
+    /// The IDList structure (section 2.2.1), a stored list of ItemIDs followed by
+    /// the TerminalID.
+    id_list: Vec<u8>,
+}
 
 impl LinkTargetIdList {
     /// Construct a new `LinkTargetIdList`
-    pub fn new(cursor: &mut Cursor<Vec<u8>>, header: &ShellLinkHeader) -> Result<Self> {
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, header: &ShellLinkHeader) -> Result<Self> {
+        crate::parser::borrowed(cursor, |cursor| Self::parse(cursor, header))
+    }
+
+    pub(crate) fn parse(cursor: &mut Cursor<&[u8]>, header: &ShellLinkHeader) -> Result<Self> {
+        let mut id_list = Vec::new();
+
         if header
             .link_flags
             .contains(LinkFlags::HAS_LINK_TARGET_ID_LIST)
@@ -24,11 +34,298 @@ impl LinkTargetIdList {
                 .read_u16::<LE>()
                 .map_err(LinkTargetIdListError::Read)?;
 
-            let current = cursor.position();
+            let available = (cursor.get_ref().len() as u64).saturating_sub(cursor.position());
+            if u64::from(id_list_size) > available {
+                return Err(LinkTargetIdListError::InvalidSize {
+                    size: id_list_size,
+                    available,
+                }
+                .into());
+            }
+
+            id_list.resize(id_list_size as usize, 0);
+            cursor
+                .read_exact(&mut id_list)
+                .map_err(LinkTargetIdListError::Read)?;
+        }
+
+        Ok(Self { id_list })
+    }
+
+    /// Whether the list holds no IDList at all
+    pub(crate) fn is_empty(&self) -> bool {
+        self.id_list.is_empty()
+    }
+
+    /// Serialize the IDList, preceded by its size, into `buf`
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(self.id_list.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&self.id_list);
+    }
+
+    /// The raw IDList as stored in the file, for use with an external shell namespace parser.
+    /// This is `IDListSize` bytes long, ending with the TerminalID.
+    pub fn raw(&self) -> &[u8] {
+        &self.id_list
+    }
+
+    /// The ItemIDs in the list, see [`ItemId::parse_list`]
+    pub fn item_ids(&self) -> Vec<ItemId> {
+        ItemId::parse_list(&self.id_list)
+    }
+
+    /// Iterate over the `Data` of each ItemID in the list without decoding it. Iteration
+    /// stops at the TerminalID, or at the first ItemID whose size does not fit in the list.
+    pub fn iter_raw(&self) -> ItemIdIter<'_> {
+        ItemIdIter::new(&self.id_list)
+    }
+}
+
+/// An ItemID structure (section 2.2.2), an element in an IDList. The data stored in a given
+/// ItemID is defined by the source that corresponds to the location in the target namespace
+/// of the preceding ItemIDs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ItemId {
+    /// The shell data source-defined data that specifies an item.
+    pub data: Vec<u8>,
+}
+
+impl ItemId {
+    /// Parse the ItemIDs of the IDList stored in `id_list`, up to the TerminalID. This is
+    /// shared by the `LinkTargetIDList` and the `VistaAndAboveIDListDataBlock`.
+    pub fn parse_list(id_list: &[u8]) -> Vec<Self> {
+        ItemIdIter::new(id_list)
+            .map(|data| Self {
+                data: data.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Classify the ItemID by the shell item type in its first byte, decoding the name it
+    /// holds for the common drive, file, folder and network location items. This is best
+    /// effort, as the format of an ItemID is defined by the shell data source that created it.
+    pub fn shell_item(&self) -> ShellItem {
+        let data = &self.data;
+
+        match data.first().copied() {
+            Some(0x1f) => match data.get(2..).and_then(Guid::from_slice) {
+                Some(guid) => {
+                    if CONTROL_PANEL_CLSIDS.contains(&guid.to_u128()) {
+                        ShellItem::ControlPanel
+                    } else {
+                        ShellItem::Guid(guid)
+                    }
+                }
+                None => ShellItem::Unknown(0x1f),
+            },
+            Some(0x20..=0x2f) => ShellItem::Drive(ansi_name(data.get(1..).unwrap_or_default())),
+            Some(class_type @ 0x30..=0x3f) => {
+                let raw_name = data.get(12..).unwrap_or_default();
+                let name = if class_type & 0x04 != 0 {
+                    unicode_name(raw_name)
+                } else {
+                    ansi_name(raw_name)
+                };
+
+                if class_type & 0x01 != 0 {
+                    ShellItem::Folder(name)
+                } else {
+                    ShellItem::File(name)
+                }
+            }
+            Some(0x40..=0x4f) => {
+                ShellItem::NetworkLocation(ansi_name(data.get(3..).unwrap_or_default()))
+            }
+            Some(0x70 | 0x71) => ShellItem::ControlPanel,
+            Some(class_type) => ShellItem::Unknown(class_type),
+            None => ShellItem::Unknown(0),
+        }
+    }
+}
+
+/// The CLSIDs of the Control Panel root folders, in their packed representation
+const CONTROL_PANEL_CLSIDS: [u128; 2] = [
+    // {21EC2020-3AEA-1069-A2DD-08002B30309D}
+    0x9d30_302b_0008_dda2_1069_3aea_21ec_2020,
+    // {26EE0668-A00A-44D7-9371-BEB064C98683}
+    0x8386_c964_b0be_7193_44d7_a00a_26ee_0668,
+];
+
+/// Decode a NUL-terminated ANSI name
+fn ansi_name(data: &[u8]) -> String {
+    let len = data.iter().position(|c| *c == 0).unwrap_or(data.len());
+    crate::code_page::decode(&data[..len], None)
+}
+
+/// Decode a NUL-terminated UTF-16 name
+fn unicode_name(data: &[u8]) -> String {
+    let wide = data
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .take_while(|c| *c != 0)
+        .collect::<Vec<u16>>();
+    String::from_utf16_lossy(&wide)
+}
+
+/// The kind of shell item an [`ItemId`] refers to, see [`ItemId::shell_item`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellItem {
+    /// A volume, with its drive path such as `C:\`
+    Drive(String),
+
+    /// A directory, with its primary (usually 8.3) name
+    Folder(String),
+
+    /// A file, with its primary (usually 8.3) name
+    File(String),
 
-            cursor.set_position(current + id_list_size as u64);
+    /// A network share or server, with its location
+    NetworkLocation(String),
+
+    /// The Control Panel or one of its items
+    ControlPanel,
+
+    /// A shell folder identified by its CLSID, such as My Computer
+    Guid(Guid),
+
+    /// An item of a type not decoded here, with its type byte
+    Unknown(u8),
+}
+
+/// Iterator over the raw `Data` of the ItemIDs in an IDList, see
+/// [`LinkTargetIdList::iter_raw`].
+#[derive(Clone, Debug)]
+pub struct ItemIdIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ItemIdIter<'a> {
+    /// Iterate over the ItemIDs of the IDList stored in `id_list`
+    pub fn new(id_list: &'a [u8]) -> Self {
+        Self { remaining: id_list }
+    }
+}
+
+impl<'a> Iterator for ItemIdIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.len() < 2 {
+            return None;
+        }
+
+        let item_id_size = u16::from_le_bytes([self.remaining[0], self.remaining[1]]) as usize;
+
+        if item_id_size < 2 || item_id_size > self.remaining.len() {
+            self.remaining = &[];
+            return None;
         }
 
-        Ok(Self {})
+        let (item_id, rest) = self.remaining.split_at(item_id_size);
+        self.remaining = rest;
+
+        Some(&item_id[2..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ItemId, ShellItem};
+    use crate::{
+        error::{Error, LinkTargetIdListError},
+        Lnk,
+    };
+    use std::convert::TryFrom;
+    use std::path::Path;
+
+    #[test]
+    fn iter_raw() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let items = lnk.link_target_id_list.iter_raw().collect::<Vec<_>>();
+
+        assert_eq!(
+            items.iter().map(|item| item.len()).collect::<Vec<_>>(),
+            vec![18, 23, 138, 102, 96]
+        );
+        assert_eq!(&items[1][..4], b"/C:\\");
+    }
+
+    #[test]
+    fn raw() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::try_from(data.as_slice()).unwrap();
+        let raw = lnk.link_target_id_list.raw();
+
+        assert_eq!(raw.len(), u16::from_le_bytes([data[76], data[77]]) as usize);
+        assert_eq!(raw, &data[78..78 + raw.len()]);
+        assert_eq!(&raw[raw.len() - 2..], &[0, 0]);
+    }
+
+    #[test]
+    fn size_exceeds_data() {
+        let mut data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let available = data.len() as u64 - 78;
+        data[76..78].copy_from_slice(&0xfff0u16.to_le_bytes());
+
+        let result = Lnk::try_from(data.as_slice());
+        assert!(matches!(
+            result,
+            Err(Error::LinkTargetIdListError(LinkTargetIdListError::InvalidSize {
+                size: 0xfff0,
+                available: found,
+            })) if found == available
+        ));
+    }
+
+    #[test]
+    fn item_ids() {
+        let lnk = Lnk::try_from(Path::new("./test_data/firefox.lnk")).unwrap();
+        let item_ids = lnk.link_target_id_list.item_ids();
+
+        assert_eq!(item_ids.len(), 5);
+        assert_eq!(item_ids[4].data[0], 0x32);
+    }
+
+    #[test]
+    fn shell_item() {
+        let lnk = Lnk::try_from(Path::new("./test_data/outlook_express.lnk")).unwrap();
+        let items = lnk
+            .link_target_id_list
+            .item_ids()
+            .iter()
+            .map(ItemId::shell_item)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            items,
+            vec![
+                // My Computer, {20D04FE0-3AEA-1069-A2D8-08002B30309D}
+                ShellItem::Guid(0x9d30_302b_0008_d8a2_1069_3aea_20d0_4fe0.into()),
+                ShellItem::Drive(r"C:\".to_string()),
+                ShellItem::Folder("PROGRA~1".to_string()),
+                ShellItem::Folder("OUTLOO~1".to_string()),
+                ShellItem::File("msimn.exe".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn control_panel_item() {
+        let mut data = vec![0x1f, 0x50];
+        data.extend_from_slice(&0x9d30_302b_0008_dda2_1069_3aea_21ec_2020u128.to_le_bytes());
+
+        assert_eq!(ItemId { data }.shell_item(), ShellItem::ControlPanel);
+        assert_eq!(
+            ItemId { data: vec![0x74] }.shell_item(),
+            ShellItem::Unknown(0x74)
+        );
+    }
+
+    #[test]
+    fn iter_raw_stops_at_bad_size() {
+        let data = [0x04, 0x00, 0xaa, 0xbb, 0xff, 0x00, 0xcc];
+        let items = super::ItemIdIter::new(&data).collect::<Vec<_>>();
+
+        assert_eq!(items, vec![&[0xaa, 0xbb][..]]);
     }
 }
@@ -0,0 +1,63 @@
+//! Options controlling how a `Lnk` is parsed.
+//!
+
+/// Options controlling how a `Lnk` is parsed, see [`crate::Lnk::from_reader_with_options`].
+/// The default options match the behavior of [`crate::Lnk::new`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LnkOptions {
+    /// The Windows code page used to decode strings that are not stored as unicode. When
+    /// `None`, the code page of the `ConsoleFEDataBlock` is used if present, and Windows-1252
+    /// otherwise. A code page that is not supported, see [`crate::code_page::is_supported`],
+    /// is rejected with `Error::UnsupportedCodePage`. The double-byte code pages, such as
+    /// 932 (Shift-JIS), need the `encoding_rs` feature.
+    pub ansi_code_page: Option<u16>,
+
+    /// Treat recoverable parsing failures, such as a `NameString` that cannot be decoded, as
+    /// errors instead of skipping the affected field.
+    pub strict: bool,
+
+    /// When an extra data block declares a size that does not fit its contents, scan forward
+    /// for the next valid block header and continue from there instead of reading the
+    /// blocks that follow at misaligned positions. Each recovery is recorded as a
+    /// `ParseWarning::Resynchronized`. Ignored when `strict` is set.
+    pub resync_extra_data: bool,
+
+    /// The largest input, in bytes, read from a `Read` source before giving up with
+    /// `HeaderError::TooLarge`. This protects batch scanners from buffering a huge file that
+    /// was renamed to `.lnk`. Data passed to [`crate::Lnk::parse`] is not limited.
+    pub max_size: u64,
+
+    /// Decide for each StringData string whether it is stored as UTF-16 or ANSI by looking
+    /// at its bytes, instead of trusting the `IsUnicode` flag. This recovers readable strings
+    /// from files whose generator set the flag wrongly. The flag is still used when the
+    /// bytes fit either encoding, such as for UTF-16 text without any ASCII characters.
+    /// Off by default.
+    pub detect_encoding: bool,
+}
+
+impl LnkOptions {
+    /// The default `max_size`, 16 MiB, far larger than any real shell link
+    pub const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+    /// The `ansi_code_page` to decode with, or an error if it is not supported
+    pub(crate) fn code_page(&self) -> crate::Result<Option<u32>> {
+        match self.ansi_code_page {
+            Some(code_page) if !crate::code_page::is_supported(code_page.into()) => {
+                Err(crate::error::Error::UnsupportedCodePage(code_page))
+            }
+            code_page => Ok(code_page.map(u32::from)),
+        }
+    }
+}
+
+impl Default for LnkOptions {
+    fn default() -> Self {
+        Self {
+            ansi_code_page: None,
+            strict: false,
+            resync_extra_data: false,
+            max_size: Self::DEFAULT_MAX_SIZE,
+            detect_encoding: false,
+        }
+    }
+}
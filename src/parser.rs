@@ -0,0 +1,397 @@
+//! Incremental parsing of a .lnk file, one section at a time.
+//!
+
+use crate::{
+    error::ParseWarning, ExtraData, LinkInfo, LinkTargetIdList, LnkOptions, Result,
+    ShellLinkHeader, StringData,
+};
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+/// A section of a shell link, as yielded by [`LnkParser::next_section`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Section {
+    /// The ShellLinkHeader, always the first section
+    Header(ShellLinkHeader),
+
+    /// The LinkTargetIDList, empty if the header does not declare one
+    LinkTargetIdList(LinkTargetIdList),
+
+    /// The LinkInfo, empty if the header does not declare one
+    LinkInfo(LinkInfo),
+
+    /// The StringData strings declared by the header
+    StringData(StringData),
+
+    /// The ExtraData blocks, always the last section
+    ExtraData(ExtraData),
+}
+
+/// The section [`LnkParser::next_section`] parses next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Header,
+    LinkTargetIdList,
+    LinkInfo,
+    StringData,
+    ExtraData,
+    Done,
+}
+
+/// A parser that yields the sections of a shell link in the order they are stored, so callers
+/// can show partial data or stop early without parsing the rest. [`crate::Lnk::parse`] is
+/// built on top of it.
+///
+/// Without an `ansi_code_page` in the options, the ANSI strings of the LinkInfo and StringData
+/// are decoded before the `ConsoleFEDataBlock` that may specify their code page has been read,
+/// using the default code page. [`crate::Lnk::parse`] decodes them again once it is known.
+///
+/// # Example
+///
+/// ```
+/// use parselnk::{LnkParser, Section};
+///
+/// let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+/// let mut parser = LnkParser::new(&data);
+///
+/// while let Some(section) = parser.next_section().unwrap() {
+///     if let Section::LinkInfo(link_info) = section {
+///         println!("{:?}", link_info.local_base_path);
+///         break;
+///     }
+/// }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct LnkParser<'a> {
+    cursor: Cursor<&'a [u8]>,
+    options: LnkOptions,
+    state: State,
+
+    /// The header, once it has been parsed, which the other sections depend on
+    header: ShellLinkHeader,
+
+    /// The positions of the LinkInfo and StringData, to decode them again with another code
+    /// page
+    link_info_pos: u64,
+    string_data_pos: u64,
+
+    /// The warnings of each section, kept apart so that a section decoded again replaces its
+    /// own
+    warnings: Vec<ParseWarning>,
+    link_info_warnings: Vec<ParseWarning>,
+    extra_data_warnings: Vec<ParseWarning>,
+    string_data_warnings: Vec<ParseWarning>,
+}
+
+impl<'a> LnkParser<'a> {
+    /// Construct a new `LnkParser` over the shell link at the start of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_options(data, &LnkOptions::default())
+    }
+
+    /// Construct a new `LnkParser` over the shell link at the start of `data`, parsing it
+    /// according to `options`
+    pub fn with_options(data: &'a [u8], options: &LnkOptions) -> Self {
+        Self::at(data, 0, options)
+    }
+
+    /// Construct a new `LnkParser` over the shell link at `offset` in `data`. Positions, and
+    /// the offsets reported in errors, are counted from the start of `data`.
+    pub(crate) fn at(data: &'a [u8], offset: usize, options: &LnkOptions) -> Self {
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(offset as u64);
+
+        Self {
+            cursor,
+            options: *options,
+            state: State::Header,
+            header: ShellLinkHeader::default(),
+            link_info_pos: 0,
+            string_data_pos: 0,
+            warnings: Vec::new(),
+            link_info_warnings: Vec::new(),
+            extra_data_warnings: Vec::new(),
+            string_data_warnings: Vec::new(),
+        }
+    }
+
+    /// Parse the next section, returning `None` once the ExtraData has been parsed. After an
+    /// error, the position of the following section is unknown and this returns `None`.
+    pub fn next_section(&mut self) -> Result<Option<Section>> {
+        let result = self.parse_section();
+
+        self.state = match (&result, self.state) {
+            (Err(_), _) | (_, State::ExtraData) | (_, State::Done) => State::Done,
+            (_, State::Header) => State::LinkTargetIdList,
+            (_, State::LinkTargetIdList) => State::LinkInfo,
+            (_, State::LinkInfo) => State::StringData,
+            (_, State::StringData) => State::ExtraData,
+        };
+
+        result
+    }
+
+    /// The recoverable problems encountered in the sections parsed so far, in the order the
+    /// sections appear in the data
+    pub fn warnings(&self) -> impl Iterator<Item = &ParseWarning> {
+        self.warnings
+            .iter()
+            .chain(&self.link_info_warnings)
+            .chain(&self.string_data_warnings)
+            .chain(&self.extra_data_warnings)
+    }
+
+    /// The offset just past the last section parsed, or the end of the data if that is
+    /// shorter
+    pub fn position(&self) -> usize {
+        (self.cursor.position() as usize).min(self.cursor.get_ref().len())
+    }
+
+    fn parse_section(&mut self) -> Result<Option<Section>> {
+        let cursor = &mut self.cursor;
+        let header = &self.header;
+        let code_page = self.options.code_page()?;
+
+        let section = match self.state {
+            State::Header => {
+                let header = ShellLinkHeader::try_from(&mut *cursor)?;
+                if !header.reserved_bits_clean() {
+                    self.warnings.push(ParseWarning::ReservedFields);
+                }
+                self.header = header;
+                Section::Header(header)
+            }
+            State::LinkTargetIdList => {
+                Section::LinkTargetIdList(LinkTargetIdList::parse(cursor, header)?)
+            }
+            State::LinkInfo => {
+                self.link_info_pos = cursor.position();
+                Section::LinkInfo(LinkInfo::parse(
+                    cursor,
+                    header,
+                    code_page,
+                    self.options.strict,
+                    &mut self.link_info_warnings,
+                )?)
+            }
+            State::StringData => {
+                self.string_data_pos = cursor.position();
+                Section::StringData(StringData::parse(
+                    cursor,
+                    header,
+                    code_page,
+                    self.options.strict,
+                    self.options.detect_encoding,
+                    &mut self.string_data_warnings,
+                )?)
+            }
+            State::ExtraData => Section::ExtraData(ExtraData::parse(
+                cursor,
+                header,
+                &self.options,
+                &mut self.extra_data_warnings,
+            )?),
+            State::Done => return Ok(None),
+        };
+
+        Ok(Some(section))
+    }
+
+    /// Decode the LinkInfo again with the ANSI `code_page`, replacing its warnings. The
+    /// position of the parser is left where it was.
+    pub(crate) fn reparse_link_info(&mut self, code_page: u32) -> Result<LinkInfo> {
+        let position = self.cursor.position();
+        self.cursor.set_position(self.link_info_pos);
+        self.link_info_warnings.clear();
+
+        let link_info = LinkInfo::parse(
+            &mut self.cursor,
+            &self.header,
+            Some(code_page),
+            self.options.strict,
+            &mut self.link_info_warnings,
+        );
+        self.cursor.set_position(position);
+
+        link_info
+    }
+
+    /// Decode the StringData again with the ANSI `code_page`, replacing its warnings. The
+    /// position of the parser is left where it was.
+    pub(crate) fn reparse_string_data(&mut self, code_page: u32) -> Result<StringData> {
+        let position = self.cursor.position();
+        self.cursor.set_position(self.string_data_pos);
+        self.string_data_warnings.clear();
+
+        let string_data = StringData::parse(
+            &mut self.cursor,
+            &self.header,
+            Some(code_page),
+            self.options.strict,
+            self.options.detect_encoding,
+            &mut self.string_data_warnings,
+        );
+        self.cursor.set_position(position);
+
+        string_data
+    }
+
+    /// The warnings of all the sections parsed, in the order [`crate::Lnk::warnings`]
+    /// reports them
+    pub(crate) fn into_warnings(self) -> Vec<ParseWarning> {
+        let mut warnings = self.warnings;
+        warnings.extend(self.link_info_warnings);
+        warnings.extend(self.string_data_warnings);
+        warnings.extend(self.extra_data_warnings);
+        warnings
+    }
+}
+
+/// Run the section parser `f` on a cursor that borrows the data of `cursor`, from its
+/// position, and move `cursor` past what `f` read. This lets the public constructors accept
+/// both `Cursor<Vec<u8>>` and `Cursor<&[u8]>`.
+pub(crate) fn borrowed<T: AsRef<[u8]>, R>(
+    cursor: &mut Cursor<T>,
+    f: impl FnOnce(&mut Cursor<&[u8]>) -> R,
+) -> R {
+    let mut borrowed = Cursor::new(cursor.get_ref().as_ref());
+    borrowed.set_position(cursor.position());
+    let result = f(&mut borrowed);
+    let position = borrowed.position();
+    cursor.set_position(position);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LnkParser, Section};
+    use crate::error::ParseWarning;
+    use crate::{ExtraData, LinkFlags, LinkInfo, LinkTargetIdList, Lnk, StringData};
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    #[test]
+    fn sections_in_order() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+        let mut parser = LnkParser::new(&data);
+
+        match parser.next_section().unwrap() {
+            Some(Section::Header(header)) => {
+                assert_eq!(header, lnk.header);
+                assert!(header.link_flags.contains(LinkFlags::HAS_LINK_INFO));
+            }
+            section => panic!("expected the header, got {:?}", section),
+        }
+        assert_eq!(parser.position(), 0x4c);
+
+        assert_eq!(
+            parser.next_section().unwrap(),
+            Some(Section::LinkTargetIdList(lnk.link_target_id_list.clone()))
+        );
+        assert_eq!(
+            parser.next_section().unwrap(),
+            Some(Section::LinkInfo(lnk.link_info.clone()))
+        );
+        assert_eq!(
+            parser.next_section().unwrap(),
+            Some(Section::StringData(lnk.string_data.clone()))
+        );
+        assert_eq!(
+            parser.next_section().unwrap(),
+            Some(Section::ExtraData(lnk.extra_data.clone()))
+        );
+        assert_eq!(parser.position(), data.len());
+
+        assert_eq!(parser.next_section().unwrap(), None);
+        assert_eq!(parser.next_section().unwrap(), None);
+        assert_eq!(parser.warnings().count(), 0);
+    }
+
+    #[test]
+    fn warnings_in_file_order() {
+        let mut lnk = Lnk::try_from(std::path::Path::new("./test_data/firefox.lnk")).unwrap();
+        lnk.header.reserved1 = 1;
+        lnk.string_data.name_string = Some("AB".to_string());
+        let mut data = lnk.to_bytes();
+
+        // An unpaired surrogate in the NameString
+        let name = data
+            .windows(6)
+            .position(|w| w == [0x02, 0x00, 0x41, 0x00, 0x42, 0x00])
+            .unwrap();
+        data[name + 2..name + 4].copy_from_slice(&[0x00, 0xd8]);
+
+        // A LinkInfoSize smaller than its header, and an unknown block before the
+        // TerminalBlock
+        let link_info = 0x4e + u16::from_le_bytes([data[0x4c], data[0x4d]]) as usize;
+        data[link_info..link_info + 4].copy_from_slice(&0u32.to_le_bytes());
+        let terminal = data.len() - 4;
+        data.splice(
+            terminal..terminal,
+            [
+                0x0c, 0x00, 0x00, 0x00, 0x99, 0x00, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x00,
+            ],
+        );
+
+        let in_file_order = |warnings: &[&ParseWarning]| {
+            matches!(
+                warnings,
+                [
+                    ParseWarning::ReservedFields,
+                    ParseWarning::InvalidLinkInfoSize(0),
+                    ParseWarning::StringDecode("NameString", _),
+                    ParseWarning::UnknownBlock(0x0c, 0xa000_0099),
+                ]
+            )
+        };
+
+        let mut parser = LnkParser::new(&data);
+        while parser.next_section().unwrap().is_some() {}
+        assert!(in_file_order(&parser.warnings().collect::<Vec<_>>()));
+
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(in_file_order(&lnk.warnings().iter().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn stop_after_error() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let mut parser = LnkParser::new(&data[..0x50]);
+
+        assert!(matches!(
+            parser.next_section(),
+            Ok(Some(Section::Header(_)))
+        ));
+        assert!(parser.next_section().is_err());
+        assert_eq!(parser.next_section().unwrap(), None);
+    }
+
+    #[test]
+    fn owned_cursor() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+        let mut cursor = Cursor::new(data.clone());
+        cursor.set_position(0x4c);
+
+        assert_eq!(
+            LinkTargetIdList::new(&mut cursor, &lnk.header).unwrap(),
+            lnk.link_target_id_list
+        );
+        assert_eq!(
+            LinkInfo::new(&mut cursor, &lnk.header).unwrap(),
+            lnk.link_info
+        );
+        assert_eq!(
+            StringData::new(&mut cursor, &lnk.header).unwrap(),
+            lnk.string_data
+        );
+        assert_eq!(
+            ExtraData::new(&mut cursor, &lnk.header).unwrap(),
+            lnk.extra_data
+        );
+        assert_eq!(cursor.position(), data.len() as u64);
+    }
+}
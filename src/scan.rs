@@ -0,0 +1,71 @@
+//! Parsing every .lnk file in a directory.
+//!
+
+use crate::{Lnk, Result};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+/// Parse each `.lnk` file directly inside `dir`, without descending into subdirectories.
+/// Files are parsed lazily as the iterator advances, and each path is paired with its own
+/// parse result so one bad file does not stop the scan. Entries that cannot be read from the
+/// directory are skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// for (path, lnk) in parselnk::scan_dir(Path::new(r"c:\users\me\desktop")).unwrap() {
+///     match lnk {
+///         Ok(lnk) => println!("{}: {:?}", path.display(), lnk.target_path()),
+///         Err(e) => eprintln!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+///
+pub fn scan_dir(dir: &Path) -> std::io::Result<impl Iterator<Item = (PathBuf, Result<Lnk>)>> {
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| matches!(entry.file_type(), Ok(t) if t.is_file()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(path.extension(), Some(extension) if extension.eq_ignore_ascii_case("lnk"))
+        })
+        .map(|path| {
+            let lnk = Lnk::try_from(path.as_path());
+            (path, lnk)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scan_dir;
+    use std::path::Path;
+
+    #[test]
+    fn test_data() {
+        let mut names = scan_dir(Path::new("./test_data"))
+            .unwrap()
+            .map(|(path, lnk)| {
+                assert!(lnk.is_ok(), "{}", path.display());
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(
+            names,
+            vec![
+                "commander.lnk",
+                "firefox.lnk",
+                "notepad.lnk",
+                "outlook_express.lnk"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_dir() {
+        assert!(scan_dir(Path::new("./test_data/missing")).is_err());
+    }
+}
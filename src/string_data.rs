@@ -3,14 +3,23 @@
 //! type.
 //!
 
-use crate::{error::StringDataError, LinkFlags, Result, ShellLinkHeader};
+use crate::{
+    code_page,
+    error::{ParseWarning, StringDataError},
+    LinkFlags, LnkOptions, Result, ShellLinkHeader,
+};
 use byteorder::{ReadBytesExt, LE};
+use std::borrow::Cow;
 use std::io::{Cursor, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 /// StringData refers to a set of structures that convey user interface and path identification information. The presence of these optional structures is controlled by LinkFlags (section 2.1.1) in the ShellLinkHeader (section 2.1).
 /// The StringData structures conform to the following ABNF rules [RFC5234].
+///
+/// Each field is `None` when its flag is not set in the header, and `Some` of a possibly empty
+/// string when it is. A string that is present but blank is `Some("")`. Use `Lnk::has_arguments`
+/// and friends to check the flags themselves.
 pub struct StringData {
     /// Description supplied by .lnk creator
     pub name_string: Option<String>,
@@ -30,10 +39,29 @@ pub struct StringData {
 
 impl StringData {
     /// Parses the string value found at the beginning of `cursor`. If `unicode`
-    /// is `true`, attempt to parse it as a wide string.
-    fn parse_string(cursor: &mut Cursor<Vec<u8>>, unicode: bool) -> Result<String> {
-        let count_characters =
-            if unicode { 2 } else { 1 } * cursor.read_u16::<LE>().map_err(StringDataError::Read)?;
+    /// is `true`, attempt to parse it as a wide string, otherwise decode it using
+    /// `code_page`. A `CountCharacters` of zero is not an error, it yields an empty string.
+    fn parse_string(
+        cursor: &mut Cursor<&[u8]>,
+        unicode: bool,
+        code_page: Option<u32>,
+    ) -> Result<String> {
+        let count_characters = if unicode { 2 } else { 1 }
+            * cursor.read_u16::<LE>().map_err(StringDataError::Read)? as u64;
+
+        // Check the declared size before allocating for it, a bogus count should not
+        // cost more than the data that is actually there
+        let available = (cursor.get_ref().len() as u64).saturating_sub(cursor.position());
+        if count_characters > available {
+            return Err(StringDataError::Read(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "string of {} bytes exceeds the {} bytes available",
+                    count_characters, available
+                ),
+            ))
+            .into());
+        }
 
         let mut string_data: Vec<u8> = vec![0; count_characters as usize];
 
@@ -52,42 +80,342 @@ impl StringData {
             wide.to_string()
                 .map_err(|e| StringDataError::WideStringConversion(e).into())
         } else {
-            String::from_utf8(string_data).map_err(|e| StringDataError::StringConversion(e).into())
+            Ok(code_page::decode(&string_data, code_page))
         }
     }
 
     /// Build new `StringData` from data blob.
-    pub fn new(cursor: &mut Cursor<Vec<u8>>, header: &ShellLinkHeader) -> Result<Self> {
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, header: &ShellLinkHeader) -> Result<Self> {
+        Self::with_options(cursor, header, &LnkOptions::default())
+    }
+
+    /// Build new `StringData` from data blob, decoding non-unicode strings using the
+    /// `ansi_code_page` of `options`. Without a code page, Windows-1252 is assumed.
+    pub fn with_options<T: AsRef<[u8]>>(
+        cursor: &mut Cursor<T>,
+        header: &ShellLinkHeader,
+        options: &LnkOptions,
+    ) -> Result<Self> {
+        let code_page = options.code_page()?;
+        crate::parser::borrowed(cursor, |cursor| {
+            Self::parse(
+                cursor,
+                header,
+                code_page,
+                options.strict,
+                options.detect_encoding,
+                &mut Vec::new(),
+            )
+        })
+    }
+
+    pub(crate) fn parse(
+        cursor: &mut Cursor<&[u8]>,
+        header: &ShellLinkHeader,
+        code_page: Option<u32>,
+        strict: bool,
+        detect_encoding: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Self> {
         let mut this = StringData::default();
+        let flag = header.link_flags.contains(LinkFlags::IS_UNICODE);
+        let unicode = |cursor: &Cursor<&[u8]>| {
+            let remaining = cursor
+                .get_ref()
+                .get(cursor.position() as usize..)
+                .unwrap_or_default();
+
+            if detect_encoding {
+                detect_unicode(remaining).unwrap_or(flag)
+            } else {
+                flag
+            }
+        };
 
         if header.link_flags.contains(LinkFlags::HAS_NAME) {
-            this.name_string =
-                Self::parse_string(cursor, header.link_flags.contains(LinkFlags::IS_UNICODE)).ok();
+            this.name_string = match Self::parse_string(cursor, unicode(cursor), code_page) {
+                Ok(name_string) => Some(name_string),
+                Err(e) if strict => return Err(e),
+                Err(e) => {
+                    warnings.push(ParseWarning::StringDecode("NameString", e.to_string()));
+                    None
+                }
+            };
         }
         if header.link_flags.contains(LinkFlags::HAS_RELATIVE_PATH) {
             this.relative_path = Some(PathBuf::from(&Self::parse_string(
                 cursor,
-                header.link_flags.contains(LinkFlags::IS_UNICODE),
+                unicode(cursor),
+                code_page,
             )?));
         }
         if header.link_flags.contains(LinkFlags::HAS_WORKING_DIR) {
             this.working_dir = Some(PathBuf::from(&Self::parse_string(
                 cursor,
-                header.link_flags.contains(LinkFlags::IS_UNICODE),
+                unicode(cursor),
+                code_page,
             )?));
         }
         if header.link_flags.contains(LinkFlags::HAS_ARGUMENTS) {
-            this.command_line_arguments = Some(Self::parse_string(
-                cursor,
-                header.link_flags.contains(LinkFlags::IS_UNICODE),
-            )?);
+            this.command_line_arguments =
+                Some(Self::parse_string(cursor, unicode(cursor), code_page)?);
         }
         if header.link_flags.contains(LinkFlags::HAS_ICON_LOCATION) {
             this.icon_location = Some(PathBuf::from(&Self::parse_string(
                 cursor,
-                header.link_flags.contains(LinkFlags::IS_UNICODE),
+                unicode(cursor),
+                code_page,
             )?));
         }
         Ok(this)
     }
+
+    /// Serialize the strings that are present into `buf`. If `unicode` is `true`, they are
+    /// written as wide strings, otherwise they are encoded using `code_page`.
+    pub(crate) fn write(&self, buf: &mut Vec<u8>, unicode: bool, code_page: Option<u32>) {
+        let strings = [
+            self.name_string.as_deref().map(Cow::from),
+            self.relative_path.as_deref().map(Path::to_string_lossy),
+            self.working_dir.as_deref().map(Path::to_string_lossy),
+            self.command_line_arguments.as_deref().map(Cow::from),
+            self.icon_location.as_deref().map(Path::to_string_lossy),
+        ];
+
+        for string in strings.iter().flatten() {
+            if unicode {
+                let mut wide = string.encode_utf16().collect::<Vec<u16>>();
+                wide.truncate(u16::MAX as usize);
+
+                buf.extend_from_slice(&(wide.len() as u16).to_le_bytes());
+                wide.iter()
+                    .for_each(|c| buf.extend_from_slice(&c.to_le_bytes()));
+            } else {
+                let mut data = code_page::encode(string, code_page);
+                data.truncate(u16::MAX as usize);
+
+                buf.extend_from_slice(&(data.len() as u16).to_le_bytes());
+                buf.extend_from_slice(&data);
+            }
+        }
+    }
+}
+
+/// Guess whether the counted string at the start of `data` is stored as UTF-16, from the
+/// pattern of its bytes. UTF-16 text uses a single script, possibly mixed with ASCII, so the
+/// high bytes of its characters take at most one value besides zero, while ANSI text has no
+/// NUL bytes. Returns `None` when the bytes fit both or neither, such as for UTF-16 text
+/// without any ASCII characters.
+fn detect_unicode(data: &[u8]) -> Option<bool> {
+    let count = data
+        .get(..2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .filter(|count| *count > 0)?;
+    let data = &data[2..];
+
+    let looks_wide = data
+        .get(..2 * count)
+        .filter(|wide| {
+            let mut script = None;
+
+            wide.chunks_exact(2).all(|c| match (c[0], c[1]) {
+                (0, 0) => false,
+                (_, 0) => true,
+                (_, high) => *script.get_or_insert(high) == high,
+            })
+        })
+        .is_some();
+    let looks_narrow = matches!(data.get(..count), Some(narrow) if !narrow.contains(&0));
+
+    match (looks_wide, looks_narrow) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StringData;
+    use crate::error::{Error, StringDataError};
+    use crate::{LinkFlags, Lnk, LnkOptions, ShellLinkHeader};
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    /// Encode `s` as a counted ANSI `StringData` entry
+    fn ansi(s: &[u8]) -> Vec<u8> {
+        let mut data = (s.len() as u16).to_le_bytes().to_vec();
+        data.extend_from_slice(s);
+        data
+    }
+
+    /// The header of firefox.lnk with its link flags replaced by `link_flags`
+    fn header(link_flags: LinkFlags) -> ShellLinkHeader {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        ShellLinkHeader {
+            link_flags,
+            ..Lnk::try_from(data).unwrap().header
+        }
+    }
+
+    #[test]
+    fn ansi_name() {
+        let header = header(LinkFlags::HAS_NAME);
+
+        let data = ansi(b"Caf\xe9 cr\xe8me");
+        let mut cursor = Cursor::new(data.as_slice());
+        let string_data = StringData::new(&mut cursor, &header).unwrap();
+
+        assert_eq!(string_data.name_string, Some("Café crème".to_string()));
+    }
+
+    #[test]
+    fn ansi_name_with_code_page() {
+        let header = header(LinkFlags::HAS_NAME);
+
+        let options = LnkOptions {
+            ansi_code_page: Some(1251),
+            ..Default::default()
+        };
+
+        let data = ansi(b"\xcf\xf0\xe8\xe2\xe5\xf2");
+        let mut cursor = Cursor::new(data.as_slice());
+        let string_data = StringData::with_options(&mut cursor, &header, &options).unwrap();
+
+        assert_eq!(string_data.name_string, Some("Привет".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "encoding_rs")]
+    fn shift_jis_name() {
+        let header = header(LinkFlags::HAS_NAME);
+
+        let options = LnkOptions {
+            ansi_code_page: Some(932),
+            ..Default::default()
+        };
+
+        let data = ansi(b"\x83\x56\x83\x87\x81\x5b\x83\x67\x83\x4a\x83\x62\x83\x67");
+        let mut cursor = Cursor::new(data.as_slice());
+        let string_data = StringData::with_options(&mut cursor, &header, &options).unwrap();
+
+        assert_eq!(string_data.name_string, Some("ショートカット".to_string()));
+
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        assert!(Lnk::parse_with_options(&data, &options).is_ok());
+    }
+
+    #[test]
+    fn empty_name() {
+        let header = header(LinkFlags::HAS_NAME | LinkFlags::IS_UNICODE);
+
+        let data = [0x00, 0x00];
+        let string_data = StringData::new(&mut Cursor::new(&data[..]), &header).unwrap();
+        assert_eq!(string_data.name_string, Some(String::new()));
+
+        let mut lnk = Lnk::try_from(std::path::Path::new("./test_data/commander.lnk")).unwrap();
+        lnk.string_data.name_string = Some(String::new());
+        let data = lnk.to_bytes();
+        let lnk = Lnk::parse(&data).unwrap();
+
+        assert!(lnk.has_description());
+        assert_eq!(lnk.description(), Some(String::new()));
+        assert_eq!(lnk.to_bytes(), data);
+    }
+
+    #[test]
+    fn strict_name() {
+        let header = header(LinkFlags::HAS_NAME | LinkFlags::IS_UNICODE);
+
+        // An unpaired surrogate
+        let data = [0x01, 0x00, 0x00, 0xd8];
+
+        let lenient = StringData::new(&mut Cursor::new(&data[..]), &header).unwrap();
+        assert_eq!(lenient.name_string, None);
+
+        let options = LnkOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(StringData::with_options(&mut Cursor::new(&data[..]), &header, &options).is_err());
+    }
+
+    #[test]
+    fn bogus_length() {
+        let header = header(LinkFlags::HAS_ARGUMENTS | LinkFlags::IS_UNICODE);
+
+        let data = [0xff, 0xff, 0x41, 0x00, 0x42, 0x00];
+        let result = StringData::new(&mut Cursor::new(&data[..]), &header);
+
+        assert!(matches!(
+            result,
+            Err(Error::StringDataError(StringDataError::Read(_)))
+        ));
+    }
+
+    #[test]
+    fn detect_encoding() {
+        let data = std::fs::read("./test_data/firefox.lnk").unwrap();
+        let lnk = Lnk::parse(&data).unwrap();
+        assert!(lnk.header.link_flags.contains(LinkFlags::IS_UNICODE));
+        assert!(lnk.string_data.relative_path.is_some());
+
+        let options = LnkOptions {
+            detect_encoding: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Lnk::parse_with_options(&data, &options)
+                .unwrap()
+                .string_data,
+            lnk.string_data
+        );
+
+        // UTF-16 strings with IsUnicode clear
+        let mut misflagged = data.clone();
+        misflagged[0x14] &= !(LinkFlags::IS_UNICODE.bits() as u8);
+        assert_ne!(
+            Lnk::parse(&misflagged).map(|lnk| lnk.string_data).ok(),
+            Some(lnk.string_data.clone())
+        );
+        assert_eq!(
+            Lnk::parse_with_options(&misflagged, &options)
+                .unwrap()
+                .string_data,
+            lnk.string_data
+        );
+
+        // ANSI strings with IsUnicode set
+        let mut ansi = lnk.clone();
+        ansi.header.link_flags.remove(LinkFlags::IS_UNICODE);
+        let mut misflagged = ansi.to_bytes();
+        misflagged[0x14] |= LinkFlags::IS_UNICODE.bits() as u8;
+        assert_eq!(
+            Lnk::parse_with_options(&misflagged, &options)
+                .unwrap()
+                .string_data,
+            lnk.string_data
+        );
+    }
+
+    #[test]
+    fn detect_unicode() {
+        let wide = |s: &str| {
+            let wide = s.encode_utf16().collect::<Vec<u16>>();
+            let mut data = (wide.len() as u16).to_le_bytes().to_vec();
+            wide.iter()
+                .for_each(|c| data.extend_from_slice(&c.to_le_bytes()));
+            data
+        };
+
+        assert_eq!(super::detect_unicode(&wide("notepad.exe")), Some(true));
+        assert_eq!(super::detect_unicode(&wide("Я и ты")), Some(true));
+        assert_eq!(super::detect_unicode(&ansi(b"notepad.exe")), Some(false));
+        assert_eq!(
+            super::detect_unicode(&ansi(b"\xcf\xf0\xe8\xe2")),
+            Some(false)
+        );
+        // UTF-16 text without any NUL bytes could also be ANSI
+        assert_eq!(super::detect_unicode(&wide("Привет")), None);
+        assert_eq!(super::detect_unicode(&[0, 0]), None);
+    }
 }
@@ -0,0 +1,117 @@
+//! Host-independent handling of the Windows paths stored in a .lnk file.
+//!
+
+use std::fmt;
+
+/// A Windows path, normalized to use backslashes as separators. Unlike `PathBuf`, this splits
+/// paths the same way on every host, so `C:\Windows\notepad.exe` has a drive letter and two
+/// components even when parsed on Linux.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WindowsPath(String);
+
+impl WindowsPath {
+    /// Construct a new `WindowsPath`, replacing any `/` separators with `\`
+    pub fn new<S: AsRef<str>>(path: S) -> Self {
+        Self(path.as_ref().replace('/', "\\"))
+    }
+
+    /// The normalized path
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The drive letter of an absolute or drive-relative path such as `C:\Windows`, in
+    /// uppercase
+    pub fn drive_letter(&self) -> Option<char> {
+        let mut chars = self.0.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => {
+                Some(letter.to_ascii_uppercase())
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is a UNC path such as `\\server\share\file.txt`
+    pub fn is_unc(&self) -> bool {
+        self.0.starts_with("\\\\")
+    }
+
+    /// The names between the separators, after any drive letter. For a UNC path the server
+    /// and share names are the first two components.
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        let path = if self.drive_letter().is_some() {
+            &self.0[2..]
+        } else {
+            &self.0
+        };
+
+        path.split('\\').filter(|component| !component.is_empty())
+    }
+
+    /// The last component of the path, if any
+    pub fn file_name(&self) -> Option<&str> {
+        self.components().last()
+    }
+}
+
+impl fmt::Display for WindowsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for WindowsPath {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<String> for WindowsPath {
+    fn from(path: String) -> Self {
+        Self::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WindowsPath;
+
+    #[test]
+    fn local_path() {
+        let path = WindowsPath::new(r"C:\Windows\notepad.exe");
+
+        assert_eq!(path.drive_letter(), Some('C'));
+        assert!(!path.is_unc());
+        assert_eq!(
+            path.components().collect::<Vec<_>>(),
+            ["Windows", "notepad.exe"]
+        );
+        assert_eq!(path.file_name(), Some("notepad.exe"));
+    }
+
+    #[test]
+    fn mixed_separators() {
+        let path = WindowsPath::new("d:/Program Files\\app.exe");
+
+        assert_eq!(path.as_str(), r"d:\Program Files\app.exe");
+        assert_eq!(path.drive_letter(), Some('D'));
+        assert_eq!(
+            path.components().collect::<Vec<_>>(),
+            ["Program Files", "app.exe"]
+        );
+    }
+
+    #[test]
+    fn unc_path() {
+        let path = WindowsPath::new(r"\\server\share\file.txt");
+
+        assert_eq!(path.drive_letter(), None);
+        assert!(path.is_unc());
+        assert_eq!(
+            path.components().collect::<Vec<_>>(),
+            ["server", "share", "file.txt"]
+        );
+    }
+}